@@ -1,3 +1,4 @@
+import argparse
 import json
 import re
 from pathlib import Path
@@ -15,8 +16,12 @@ class ENDFHandling:
         Args:
             endf_file_path (Path): Path to ENDF file.
         """
-        self.scattering_mt = [2]
+        # Inelastic scattering is part of the scattering, and is also written separately.
+        self.scattering_mt = [2, 4]
+        self.inelastic_mt = [4]
         self.absorption_mt = range(102, 118)
+        self.fission_mt = [18]
+        self.nu_bar_mt = 452
 
         self.COLUMN_INCREMENT = 11
 
@@ -74,24 +79,7 @@ class ENDFHandling:
                         continue
                 new_section_started = False
 
-                # Extracting the line segments.
-                number_values = []
-                for increment in range(1, 7):
-                    left_slice = self.COLUMN_INCREMENT * (increment - 1)
-                    right_slice = self.COLUMN_INCREMENT * increment
-                    number_value = line[left_slice:right_slice].strip()
-
-                    # Skipping empty values.
-                    if number_value == "":
-                        continue
-
-                    # Converting scientific notation format.
-                    number_value = number_value.replace("E", "")
-                    number_value = re.sub("(?<!^)-", "e-", number_value)
-                    number_value = re.sub("(?<!^)\+", "e+", number_value)
-
-                    number_value = float(number_value)
-                    number_values.append(number_value)
+                number_values = self.parse_line(line)
 
                 # Alternating energies/cross-sections.
                 energies = number_values[0::2]
@@ -102,6 +90,85 @@ class ENDFHandling:
                 self.endf_data[str(mt)]["cross_section"].extend(cross_sections)
         return self.endf_data
 
+    def parse_line(self, line: str) -> list[float]:
+        """Extracts the numbers from the six data columns of an ENDF line.
+
+        Args:
+            line (str): ENDF line.
+
+        Returns:
+            list[float]: Numbers in the line, without the empty columns.
+        """
+        number_values = []
+        for increment in range(1, 7):
+            left_slice = self.COLUMN_INCREMENT * (increment - 1)
+            right_slice = self.COLUMN_INCREMENT * increment
+            number_value = line[left_slice:right_slice].strip()
+
+            # Skipping empty values.
+            if number_value == "":
+                continue
+
+            # Converting scientific notation format.
+            number_value = number_value.replace("E", "")
+            number_value = re.sub("(?<!^)-", "e-", number_value)
+            number_value = re.sub("(?<!^)\+", "e+", number_value)
+
+            number_value = float(number_value)
+            number_values.append(number_value)
+
+        return number_values
+
+    def get_resonance_flag(self) -> int:
+        """Reads LRP from MF1/MT451, which is 1 if the resonances still have to be added to the MF3 cross-sections.
+
+        Returns:
+            int: LRP flag.
+        """
+        with open(self.endf_file_path) as f:
+            for line in f:
+                if line[70:75] == " 1451":
+                    return int(line[22:33])
+        return 0
+
+    def get_nu_bar(self) -> tuple[list, list]:
+        """Extracts the tabulated total nu bar from MF1/MT452.
+
+        Raises:
+            ValueError: Nu bar is given as a polynomial instead of a table.
+
+        Returns:
+            tuple[list, list]: Energies and nu bar, empty if the evaluation has no nu bar.
+        """
+        section_lines = []
+        with open(self.endf_file_path) as f:
+            for line in f:
+                if len(line) < 75 or line[70:72].strip() == "" or line[72:75].strip() == "":
+                    continue
+                if int(line[70:72]) == 1 and int(line[72:75]) == self.nu_bar_mt:
+                    section_lines.append(line)
+
+        if not section_lines:
+            return [], []
+
+        # HEAD record with LNU, where 2 is a table.
+        if int(self.parse_line(section_lines[0])[3]) != 2:
+            print(f"Only tabulated nu bar is supported: {self.endf_file_path}")
+            raise ValueError
+
+        # TAB1 record with the number of interpolation ranges and points, followed by the ranges.
+        tab1_header = self.parse_line(section_lines[1])
+        interpolation_range_count = int(tab1_header[4])
+        point_count = int(tab1_header[5])
+        interpolation_line_count = (2 * interpolation_range_count + 5) // 6
+
+        number_values = []
+        for line in section_lines[2 + interpolation_line_count :]:
+            number_values.extend(self.parse_line(line))
+        number_values = number_values[: 2 * point_count]
+
+        return number_values[0::2], number_values[1::2]
+
     def to_json(self, file_path: Path, saved_data: dict = None):
         """Saves the ENDF data as a JSON.
 
@@ -207,9 +274,20 @@ class ENDFHandling:
             for energy, cross_section in zip(energies, cross_sections):
                 f.write(f"{energy},{cross_section}\n")
 
-    def create_material(self):
-        """Creates a material folder with aggregated scattering and absorption cross-sections."""
-        result_folder = Path("endf_data/ready_endfs") / Path(self.material_name)
+    def create_material(self, result_folder: Path = None):
+        """Creates a material folder with aggregated scattering and absorption cross-sections.
+        Inelastic scattering, fission and nu bar are written as well when the evaluation contains them.
+
+        Args:
+            result_folder (Path, optional): Folder to write to. Defaults to endf_data/ready_endfs/[material name].
+        """
+        if self.get_resonance_flag() == 1:
+            print(
+                f"The resonances of {self.endf_file_path} are only given as parameters, reconstruct them with fetch_endf.py first."
+            )
+
+        if result_folder is None:
+            result_folder = Path("endf_data/ready_endfs") / Path(self.material_name)
         result_folder.mkdir(exist_ok=True, parents=True)
 
         scattering_energies, scattering_cross_sections = self.aggregate_mts(
@@ -230,6 +308,28 @@ class ENDFHandling:
             absorption_cross_sections,
         )
 
+        for file_suffix, mt_range in [
+            ("_inelastic.csv", self.inelastic_mt),
+            ("_fission.csv", self.fission_mt),
+        ]:
+            if not self.get_subset(mt_range):
+                continue
+
+            energies, cross_sections = self.aggregate_mts(mt_range)
+            self.write_files(
+                result_folder / Path(self.material_name + file_suffix),
+                energies,
+                cross_sections,
+            )
+
+        nu_bar_energies, nu_bar = self.get_nu_bar()
+        if nu_bar:
+            self.write_files(
+                result_folder / Path(self.material_name + "_nu_bar.csv"),
+                nu_bar_energies,
+                nu_bar,
+            )
+
     def get_endf_name(self) -> str:
         """Extracts the name from the ENDF
 
@@ -256,3 +356,19 @@ class ENDFHandling:
                     break
 
         return endf_name
+
+
+if __name__ == "__main__":
+    parser = argparse.ArgumentParser(
+        description="Process an ENDF evaluation into the CSVs of the material library"
+    )
+    parser.add_argument("endf_file_path", type=Path, help="ENDF evaluation to process")
+    parser.add_argument(
+        "--output",
+        type=Path,
+        default=None,
+        help="Folder to write to, defaults to endf_data/ready_endfs/[material name]",
+    )
+    args = parser.parse_args()
+
+    ENDFHandling(args.endf_file_path).create_material(args.output)
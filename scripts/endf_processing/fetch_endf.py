@@ -0,0 +1,202 @@
+import argparse
+import io
+import shutil
+import subprocess
+import tempfile
+import tomllib
+import urllib.request
+import zipfile
+from pathlib import Path
+
+from endf_handling import ENDFHandling
+
+# All ENDF/B-VIII.0 neutron sublibrary evaluations, as a single archive from the NNDC.
+NEUTRON_SUBLIBRARY_URL = (
+    "https://www.nndc.bnl.gov/endf-b8.0/zips/ENDF-B-VIII.0_neutrons.zip"
+)
+
+# RECONR input, which reconstructs the resonances into pointwise MF3 cross-sections to within 0.1%.
+RECONR_INPUT = """reconr
+20 21/
+'{evaluation_name} reconstructed by reconr'/
+{material_code} 0/
+0.001/
+0/
+stop
+"""
+
+
+def get_mf_mt(line: str) -> tuple[int, int]:
+    """Reads the MF and MT columns of an ENDF line.
+
+    Args:
+        line (str): ENDF line.
+
+    Returns:
+        tuple[int, int]: MF and MT, zero for lines without them.
+    """
+    try:
+        return int(line[70:72]), int(line[72:75])
+    except ValueError:
+        return 0, 0
+
+
+def download_sublibrary(url: str = NEUTRON_SUBLIBRARY_URL) -> zipfile.ZipFile:
+    """Downloads the neutron sublibrary archive.
+
+    Args:
+        url (str, optional): URL of the archive. Defaults to the ENDF/B-VIII.0 neutron sublibrary.
+
+    Returns:
+        zipfile.ZipFile: Archive with the evaluations.
+    """
+    print(f"Downloading {url}")
+    with urllib.request.urlopen(url) as response:
+        return zipfile.ZipFile(io.BytesIO(response.read()))
+
+
+def extract_evaluation(sublibrary: zipfile.ZipFile, evaluation_name: str, target_path: Path):
+    """Extracts a single evaluation from the archive.
+
+    Args:
+        sublibrary (zipfile.ZipFile): Archive with the evaluations.
+        evaluation_name (str): File name of the evaluation, such as n-092_U_238.endf.
+        target_path (Path): Path to write the evaluation to.
+
+    Raises:
+        FileNotFoundError: Evaluation is not part of the archive.
+    """
+    members = [
+        member
+        for member in sublibrary.namelist()
+        if Path(member).name == evaluation_name
+    ]
+    if not members:
+        print(f"Evaluation not found in the sublibrary: {evaluation_name}")
+        raise FileNotFoundError
+
+    target_path.parent.mkdir(exist_ok=True, parents=True)
+    target_path.write_bytes(sublibrary.read(members[0]))
+    print(f"Written {target_path}")
+
+
+def reconstruct_resonances(evaluation_path: Path, njoy_executable: str):
+    """Adds the resonances to the MF3 cross-sections with the RECONR module of NJOY, if the evaluation only gives them as parameters (LRP = 1).
+    The other files, such as nu bar in MF1, are kept from the evaluation, and LRP is set to 2 since MF3 now includes the resonances.
+
+    Args:
+        evaluation_path (Path): Evaluation to reconstruct in place.
+        njoy_executable (str): NJOY executable.
+    """
+    evaluation_lines = evaluation_path.read_text().splitlines(keepends=True)
+    header_index = next(
+        index
+        for index, line in enumerate(evaluation_lines)
+        if get_mf_mt(line) == (1, 451)
+    )
+    header_line = evaluation_lines[header_index]
+    if int(header_line[22:33]) != 1:
+        return
+
+    with tempfile.TemporaryDirectory() as njoy_directory:
+        shutil.copy(evaluation_path, Path(njoy_directory) / "tape20")
+        subprocess.run(
+            [njoy_executable],
+            input=RECONR_INPUT.format(
+                evaluation_name=evaluation_path.name,
+                material_code=int(header_line[66:70]),
+            ),
+            text=True,
+            cwd=njoy_directory,
+            stdout=subprocess.DEVNULL,
+            check=True,
+        )
+        pendf_lines = (
+            (Path(njoy_directory) / "tape21").read_text().splitlines(keepends=True)
+        )
+
+    # MF3 of the evaluation is replaced as a whole, including the section end records.
+    reconstructed_lines = [line for line in pendf_lines if get_mf_mt(line)[0] == 3]
+    merged_lines = []
+    for index, line in enumerate(evaluation_lines):
+        if get_mf_mt(line)[0] == 3:
+            merged_lines.extend(reconstructed_lines)
+            reconstructed_lines = []
+            continue
+        if index == header_index:
+            line = line[:22] + f"{2:>11}" + line[33:]
+        merged_lines.append(line)
+
+    evaluation_path.write_text("".join(merged_lines))
+    print(f"Reconstructed the resonances of {evaluation_path}")
+
+
+def get_library_evaluation_paths(library_path: Path) -> list[Path]:
+    """Collects the evaluations referenced by a material library TOML, including those of the isotopes of natural elements.
+
+    Args:
+        library_path (Path): Material library TOML.
+
+    Returns:
+        list[Path]: Paths of the evaluations, as given in the library.
+    """
+    with open(library_path, "rb") as f:
+        materials = tomllib.load(f)["materials"]
+
+    evaluation_paths = []
+    for material in materials:
+        for entry in [material] + material.get("isotopes", []):
+            if "endf_path" in entry:
+                evaluation_paths.append(Path(entry["endf_path"]))
+
+    return evaluation_paths
+
+
+if __name__ == "__main__":
+    parser = argparse.ArgumentParser(
+        description="Download ENDF/B-VIII.0 evaluations from the NNDC and process them into the CSVs of the material library"
+    )
+    parser.add_argument(
+        "evaluations",
+        nargs="*",
+        help="File names of the evaluations, such as n-092_U_238.endf",
+    )
+    parser.add_argument(
+        "--output", type=Path, help="Folder to write the evaluations to"
+    )
+    parser.add_argument(
+        "--library",
+        type=Path,
+        help="Material library TOML, whose evaluations are all written to the paths listed in it",
+    )
+    parser.add_argument(
+        "--process",
+        action="store_true",
+        help="Also write the cross-section, nu bar and inelastic CSVs next to the evaluations",
+    )
+    parser.add_argument(
+        "--njoy",
+        default="njoy",
+        help="NJOY executable, which is needed for evaluations with resonance parameters",
+    )
+    args = parser.parse_args()
+
+    if args.evaluations and args.output is None:
+        parser.error("--output is required for single evaluations")
+    if not args.evaluations and args.library is None:
+        parser.error("either evaluations or --library are required")
+
+    sublibrary = download_sublibrary()
+
+    for evaluation_name in args.evaluations:
+        evaluation_path = args.output / evaluation_name
+        extract_evaluation(sublibrary, evaluation_name, evaluation_path)
+        reconstruct_resonances(evaluation_path, args.njoy)
+
+        if args.process:
+            ENDFHandling(evaluation_path).create_material(args.output)
+
+    if args.library is not None:
+        for evaluation_path in get_library_evaluation_paths(args.library):
+            extract_evaluation(sublibrary, evaluation_path.name, evaluation_path)
+            reconstruct_resonances(evaluation_path, args.njoy)
@@ -0,0 +1,159 @@
+use env_logger::{Builder, Env};
+use nuclear::geometry::components::PartComposition;
+use nuclear::materials::compounds::{
+    get_compound_by_name, load_compound_library, DEFAULT_COMPOUND_LIBRARY_PATH,
+};
+use nuclear::materials::data_inspection::{
+    get_log_energy_grid, inspect_composition, write_cross_section_samples,
+};
+use nuclear::materials::data_validation::{
+    validate_material_library, IssueSeverity, COVERAGE_ENERGY_MAX, COVERAGE_ENERGY_MIN,
+};
+use nuclear::materials::material_library::{
+    load_selected_materials, read_material_tomls, DEFAULT_MATERIAL_LIBRARY_PATH,
+};
+use std::collections::HashMap;
+use std::env;
+use std::path::Path;
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage:
+    nuclear_data validate [--library <path>]
+        Checks every data file of the material library, and exits with an error if any table is unusable.
+
+    nuclear_data inspect <material or compound> <output CSV> [--library <path>] [--compounds <path>]
+                         [--density <kg/m3>] [--energy-min <eV>] [--energy-max <eV>] [--points <count>]
+        Writes the interpolated microscopic and macroscopic cross-sections on a logarithmic energy grid.
+        Single materials use their library number density; the density overrides a compound's.";
+
+/// Splits the arguments into the positional ones and the ```--name value``` options.
+fn parse_arguments(arguments: &[String]) -> (Vec<&str>, HashMap<&str, &str>) {
+    let mut positional_arguments = Vec::new();
+    let mut options = HashMap::new();
+
+    let mut argument_iterator = arguments.iter();
+    while let Some(argument) = argument_iterator.next() {
+        match argument.strip_prefix("--") {
+            Some(option_name) => {
+                let value = argument_iterator
+                    .next()
+                    .unwrap_or_else(|| panic!("Option --{} requires a value.", option_name));
+                options.insert(option_name, value.as_str());
+            }
+            None => positional_arguments.push(argument.as_str()),
+        }
+    }
+
+    (positional_arguments, options)
+}
+
+/// Parses a numerical option, or returns the default if it is not given.
+fn get_option<T: std::str::FromStr>(options: &HashMap<&str, &str>, name: &str, default: T) -> T {
+    options.get(name).map_or(default, |value| {
+        value
+            .parse()
+            .unwrap_or_else(|_| panic!("Invalid value for --{}: {}", name, value))
+    })
+}
+
+fn validate(options: &HashMap<&str, &str>) -> ExitCode {
+    let library_path = options
+        .get("library")
+        .copied()
+        .unwrap_or(DEFAULT_MATERIAL_LIBRARY_PATH);
+
+    let issues = validate_material_library(Path::new(library_path));
+    for issue in issues.iter() {
+        println!("{}", issue);
+    }
+
+    let error_count = issues
+        .iter()
+        .filter(|issue| issue.severity == IssueSeverity::Error)
+        .count();
+    println!(
+        "{} errors and {} warnings in {}.",
+        error_count,
+        issues.len() - error_count,
+        library_path
+    );
+
+    if error_count > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn inspect(name: &str, output_path: &str, options: &HashMap<&str, &str>) -> ExitCode {
+    let library_path = options
+        .get("library")
+        .copied()
+        .unwrap_or(DEFAULT_MATERIAL_LIBRARY_PATH);
+    let is_material = read_material_tomls(Path::new(library_path))
+        .iter()
+        .any(|material_toml| material_toml.name == name);
+
+    let part_composition_vector: Vec<PartComposition> = if is_material {
+        vec![PartComposition::new(name, 1.0)]
+    } else {
+        let compound_library_path = options
+            .get("compounds")
+            .copied()
+            .unwrap_or(DEFAULT_COMPOUND_LIBRARY_PATH);
+        let compounds = load_compound_library(Path::new(compound_library_path));
+        get_compound_by_name(&compounds, name)
+            .unwrap_or_else(|| {
+                panic!(
+                    "{} is neither a material in the library nor a compound.",
+                    name
+                )
+            })
+            .get_material_composition_vector(options.get("density").map(|density| {
+                density
+                    .parse()
+                    .unwrap_or_else(|_| panic!("Invalid value for --density: {}", density))
+            }))
+    };
+
+    // Only the materials in the composition are loaded, so that missing data files of other materials do not prevent the inspection.
+    let material_names: Vec<&str> = part_composition_vector
+        .iter()
+        .map(|part_composition| part_composition.material_name.as_str())
+        .collect();
+    let material_data_vector = load_selected_materials(Path::new(library_path), &material_names);
+
+    let energies = get_log_energy_grid(
+        get_option(options, "energy-min", COVERAGE_ENERGY_MIN),
+        get_option(options, "energy-max", COVERAGE_ENERGY_MAX),
+        get_option(options, "points", 1000),
+    );
+
+    let cross_section_samples =
+        inspect_composition(&material_data_vector, &part_composition_vector, &energies);
+    write_cross_section_samples(&cross_section_samples, Path::new(output_path));
+    println!(
+        "Wrote the cross-sections of {} at {} energies to {}.",
+        name,
+        energies.len(),
+        output_path
+    );
+
+    ExitCode::SUCCESS
+}
+
+fn main() -> ExitCode {
+    Builder::from_env(Env::default().default_filter_or("warn")).init();
+
+    let arguments: Vec<String> = env::args().skip(1).collect();
+    let (positional_arguments, options) = parse_arguments(&arguments);
+
+    match positional_arguments.as_slice() {
+        ["validate"] => validate(&options),
+        ["inspect", name, output_path] => inspect(name, output_path, &options),
+        _ => {
+            eprintln!("{}", USAGE);
+            ExitCode::FAILURE
+        }
+    }
+}
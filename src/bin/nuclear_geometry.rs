@@ -0,0 +1,388 @@
+use env_logger::{Builder, Env};
+use nuclear::geometry::components::{resolve_part_composition, BoundingBox};
+use nuclear::geometry::geometry_check::{check_geometry, GeometryCheckSettings};
+use nuclear::geometry::parts::parts::PartTypes;
+use nuclear::geometry::volume_calculation::calculate_volumes;
+use nuclear::materials::compounds::{load_compound_library, DEFAULT_COMPOUND_LIBRARY_PATH};
+use nuclear::materials::material_library::{
+    load_material_constants, DEFAULT_MATERIAL_LIBRARY_PATH,
+};
+use nuclear::utils::parts_loading::{load_geometry, load_parametric_geometry};
+use nuclear::utils::vectors::Vec3D;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::hint::black_box;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::Instant;
+
+const USAGE: &str = "Usage:
+    nuclear_geometry benchmark [<geometry TOML>...] [--compounds <path>] [--points <count>]
+        Compares the part lookup with and without the part grids at random positions, and reports the build time and speedup.
+        Without geometries, all geometries in config/geometries are used.
+    nuclear_geometry check [<geometry TOML>...] [--compounds <path>] [--rays <count>] [--step <m>] [--part-points <count>] [--seed <seed>] [--parameters <name=value,...>]
+        Samples positions along random rays and inside every part, and reports equal-order overlaps, hidden parts, parts outside the simulation range and unexpected void.
+        Without geometries, all geometries in config/geometries are checked.
+    nuclear_geometry volume <geometry TOML> [--compounds <path>] [--materials <path>] [--min <x,y,z> --max <x,y,z>] [--samples <count>] [--seed <seed>] [--parameters <name=value,...>]
+        Estimates the volume and mass of every part and material, after the order-based overlay, by sampling positions uniformly in a box.
+        Without a box, the outer boundary or the box around all bounded parts is used.
+    The parameters replace those defined in the [parameters] of the geometries.";
+
+/// Directory with the bundled geometries.
+const GEOMETRIES_DIRECTORY: &str = "config/geometries";
+
+/// Splits the arguments into the positional ones and the ```--name value``` options.
+fn parse_arguments(arguments: &[String]) -> (Vec<&str>, HashMap<&str, &str>) {
+    let mut positional_arguments = Vec::new();
+    let mut options = HashMap::new();
+
+    let mut argument_iterator = arguments.iter();
+    while let Some(argument) = argument_iterator.next() {
+        match argument.strip_prefix("--") {
+            Some(option_name) => {
+                let value = argument_iterator
+                    .next()
+                    .unwrap_or_else(|| panic!("Option --{} requires a value.", option_name));
+                options.insert(option_name, value.as_str());
+            }
+            None => positional_arguments.push(argument.as_str()),
+        }
+    }
+
+    (positional_arguments, options)
+}
+
+/// Parses a position given as ```x,y,z``` in m.
+fn parse_position(value: &str) -> Vec3D {
+    let coordinates: Vec<f64> = value
+        .split(',')
+        .map(|coordinate| {
+            coordinate
+                .trim()
+                .parse()
+                .unwrap_or_else(|_| panic!("Invalid coordinate in position: {}", value))
+        })
+        .collect();
+
+    match coordinates[..] {
+        [x, y, z] => Vec3D { x, y, z },
+        _ => panic!("A position needs three coordinates, as x,y,z: {}", value),
+    }
+}
+
+/// Box around all bounded parts, used if neither a box nor an outer boundary is given.
+fn get_parts_bounding_box(parts_vector: &[PartTypes]) -> Option<BoundingBox> {
+    parts_vector
+        .iter()
+        .map(|part| *part.bounding_box())
+        .filter(BoundingBox::is_bounded)
+        .reduce(|first, second| first.union(&second))
+}
+
+/// Geometries given on the command line, or all bundled geometries.
+fn get_geometry_paths(geometry_paths: &[&str]) -> Vec<String> {
+    if !geometry_paths.is_empty() {
+        return geometry_paths.iter().map(|path| path.to_string()).collect();
+    }
+
+    let mut bundled_geometry_paths: Vec<String> = fs::read_dir(GEOMETRIES_DIRECTORY)
+        .expect("Failed to read the geometries directory.")
+        .map(|entry| entry.expect("Failed to read geometry path.").path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "toml")
+        })
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    bundled_geometry_paths.sort();
+
+    bundled_geometry_paths
+}
+
+/// Parses the ```--parameters``` option, e.g. ```plate_count=7,pitch=0.02```, into the values replacing the parameters of the geometry.
+fn get_geometry_parameters(options: &HashMap<&str, &str>) -> HashMap<String, f64> {
+    options
+        .get("parameters")
+        .map_or(HashMap::new(), |parameters| {
+            parameters
+                .split(',')
+                .map(|parameter| {
+                    let (name, value) = parameter.split_once('=').unwrap_or_else(|| {
+                        panic!("Invalid parameter {}, expected name=value.", parameter)
+                    });
+                    let value = value.trim().parse().unwrap_or_else(|_| {
+                        panic!("Invalid value for parameter {}: {}", name, value)
+                    });
+                    (name.trim().to_string(), value)
+                })
+                .collect()
+        })
+}
+
+/// Parses the value of an option, or returns the default if it is not given.
+fn get_option<T: std::str::FromStr>(options: &HashMap<&str, &str>, name: &str, default: T) -> T {
+    options.get(name).map_or(default, |value| {
+        value
+            .parse()
+            .unwrap_or_else(|_| panic!("Invalid value for --{}: {}", name, value))
+    })
+}
+
+fn benchmark(geometry_paths: &[&str], options: &HashMap<&str, &str>) -> ExitCode {
+    let compound_library_path = options
+        .get("compounds")
+        .copied()
+        .unwrap_or(DEFAULT_COMPOUND_LIBRARY_PATH);
+    let compounds = load_compound_library(Path::new(compound_library_path));
+    let point_count: usize = get_option(options, "points", 1_000_000);
+
+    println!(
+        "{:<40} {:>6} {:>12} {:>14} {:>14} {:>8}",
+        "Geometry", "Parts", "Build (ms)", "Scan (ns)", "Grid (ns)", "Speedup"
+    );
+
+    let mut is_consistent = true;
+    for geometry_path in get_geometry_paths(geometry_paths) {
+        let geometry = load_geometry(Path::new(&geometry_path), &compounds);
+        let parts_vector = geometry.parts_vector;
+        let scan_index = geometry.lattice_index;
+
+        let start_time = Instant::now();
+        let mut grid_index = scan_index.clone();
+        grid_index.build_part_grids(&parts_vector);
+        let build_time = start_time.elapsed();
+
+        // Positions are sampled in the outer boundary, or in the box around all bounded parts.
+        let sampling_box = geometry
+            .boundary
+            .map(|boundary| boundary.bounding_box)
+            .or_else(|| get_parts_bounding_box(&parts_vector));
+        let Some(sampling_box) = sampling_box else {
+            println!("{:<40} has no bounded parts to sample.", geometry_path);
+            continue;
+        };
+
+        let mut rng = SmallRng::seed_from_u64(0);
+        let positions: Vec<Vec3D> = (0..point_count)
+            .map(|_| Vec3D {
+                x: rng.gen_range(sampling_box.min.x..=sampling_box.max.x),
+                y: rng.gen_range(sampling_box.min.y..=sampling_box.max.y),
+                z: rng.gen_range(sampling_box.min.z..=sampling_box.max.z),
+            })
+            .collect();
+
+        let start_time = Instant::now();
+        let scan_part_indices: Vec<Option<usize>> = positions
+            .iter()
+            .map(|position| black_box(scan_index.get_part_index(&parts_vector, position)))
+            .collect();
+        let scan_time = start_time.elapsed();
+
+        let start_time = Instant::now();
+        let grid_part_indices: Vec<Option<usize>> = positions
+            .iter()
+            .map(|position| black_box(grid_index.get_part_index(&parts_vector, position)))
+            .collect();
+        let grid_time = start_time.elapsed();
+
+        if scan_part_indices != grid_part_indices {
+            eprintln!(
+                "The part grids of {} select different parts than the scan.",
+                geometry_path
+            );
+            is_consistent = false;
+        }
+
+        println!(
+            "{:<40} {:>6} {:>12.3} {:>14.1} {:>14.1} {:>7.2}x",
+            geometry_path,
+            parts_vector.len(),
+            build_time.as_secs_f64() * 1e3,
+            scan_time.as_secs_f64() * 1e9 / point_count as f64,
+            grid_time.as_secs_f64() * 1e9 / point_count as f64,
+            scan_time.as_secs_f64() / grid_time.as_secs_f64()
+        );
+    }
+
+    if is_consistent {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn check(geometry_paths: &[&str], options: &HashMap<&str, &str>) -> ExitCode {
+    let compound_library_path = options
+        .get("compounds")
+        .copied()
+        .unwrap_or(DEFAULT_COMPOUND_LIBRARY_PATH);
+    let compounds = load_compound_library(Path::new(compound_library_path));
+    let default_settings = GeometryCheckSettings::default();
+    let settings = GeometryCheckSettings {
+        ray_count: get_option(options, "rays", default_settings.ray_count),
+        step_length: get_option(options, "step", default_settings.step_length),
+        part_point_count: get_option(options, "part-points", default_settings.part_point_count),
+    };
+    let seed: u64 = get_option(options, "seed", 0);
+    let geometry_parameters = get_geometry_parameters(options);
+
+    let mut issue_count = 0;
+    for geometry_path in get_geometry_paths(geometry_paths) {
+        let mut geometry =
+            load_parametric_geometry(Path::new(&geometry_path), &compounds, &geometry_parameters);
+        geometry
+            .lattice_index
+            .build_part_grids(&geometry.parts_vector);
+
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let issues = check_geometry(
+            &geometry.parts_vector,
+            &geometry.lattice_index,
+            geometry.boundary.as_ref(),
+            &settings,
+            &mut rng,
+        );
+
+        println!(
+            "{}: {} parts, {} issues.",
+            geometry_path,
+            geometry.parts_vector.len(),
+            issues.len()
+        );
+        for issue in &issues {
+            println!("    {}", issue.describe(&geometry.parts_vector));
+        }
+        issue_count += issues.len();
+    }
+
+    if issue_count == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn volume(geometry_path: &str, options: &HashMap<&str, &str>) -> ExitCode {
+    let compound_library_path = options
+        .get("compounds")
+        .copied()
+        .unwrap_or(DEFAULT_COMPOUND_LIBRARY_PATH);
+    let compounds = load_compound_library(Path::new(compound_library_path));
+    let material_library_path = options
+        .get("materials")
+        .copied()
+        .unwrap_or(DEFAULT_MATERIAL_LIBRARY_PATH);
+    let material_data_vector = load_material_constants(Path::new(material_library_path));
+    let sample_count: usize = get_option(options, "samples", 1_000_000);
+    let seed: u64 = get_option(options, "seed", 0);
+    let geometry_parameters = get_geometry_parameters(options);
+
+    let mut geometry =
+        load_parametric_geometry(Path::new(geometry_path), &compounds, &geometry_parameters);
+    for part in geometry.parts_vector.iter_mut() {
+        resolve_part_composition(
+            part.material_composition_vector_mut(),
+            &material_data_vector,
+        );
+    }
+    geometry
+        .lattice_index
+        .build_part_grids(&geometry.parts_vector);
+
+    let sampling_box = match (options.get("min"), options.get("max")) {
+        (Some(min), Some(max)) => Some(BoundingBox {
+            min: parse_position(min),
+            max: parse_position(max),
+        }),
+        (None, None) => geometry
+            .boundary
+            .as_ref()
+            .map(|boundary| boundary.bounding_box)
+            .or_else(|| get_parts_bounding_box(&geometry.parts_vector)),
+        _ => panic!("The box needs both --min and --max."),
+    };
+    let Some(sampling_box) = sampling_box else {
+        eprintln!(
+            "{} has no bounded parts, so the box has to be given with --min and --max.",
+            geometry_path
+        );
+        return ExitCode::FAILURE;
+    };
+
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let volume_calculation = calculate_volumes(
+        &geometry.parts_vector,
+        &geometry.lattice_index,
+        &material_data_vector,
+        &sampling_box,
+        sample_count,
+        &mut rng,
+    );
+
+    println!(
+        "{} samples in the box from {} to {} m.",
+        sample_count, sampling_box.min, sampling_box.max
+    );
+    println!(
+        "\n{:>5} {:<16} {:<16} {:>26} {:>26}",
+        "Part", "Type", "Material", "Volume (m3)", "Mass (kg)"
+    );
+    for (part_index, (part, estimate)) in geometry
+        .parts_vector
+        .iter()
+        .zip(&volume_calculation.part_estimates)
+        .enumerate()
+    {
+        println!(
+            "{:>5} {:<16} {:<16} {:>12.5e} ± {:<11.3e} {:>12.5e} ± {:<11.3e}",
+            part_index,
+            part.name(),
+            part.material_name(),
+            estimate.volume,
+            estimate.volume_uncertainty,
+            estimate.mass,
+            estimate.mass_uncertainty
+        );
+    }
+
+    println!(
+        "\n{:<16} {:>26} {:>26}",
+        "Material", "Volume (m3)", "Mass (kg)"
+    );
+    for (material_data, estimate) in material_data_vector
+        .iter()
+        .zip(&volume_calculation.material_estimates)
+        .filter(|(_, estimate)| estimate.volume > 0.0)
+    {
+        println!(
+            "{:<16} {:>12.5e} ± {:<11.3e} {:>12.5e} ± {:<11.3e}",
+            material_data.name,
+            estimate.volume,
+            estimate.volume_uncertainty,
+            estimate.mass,
+            estimate.mass_uncertainty
+        );
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn main() -> ExitCode {
+    Builder::from_env(Env::default().default_filter_or("warn")).init();
+
+    let arguments: Vec<String> = env::args().skip(1).collect();
+    let (positional_arguments, options) = parse_arguments(&arguments);
+
+    match positional_arguments.as_slice() {
+        ["benchmark", geometry_paths @ ..] => benchmark(geometry_paths, &options),
+        ["check", geometry_paths @ ..] => check(geometry_paths, &options),
+        ["volume", geometry_path] => volume(geometry_path, &options),
+        _ => {
+            eprintln!("{}", USAGE);
+            ExitCode::FAILURE
+        }
+    }
+}
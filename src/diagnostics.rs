@@ -1,5 +1,6 @@
 use crate::diagnostics::geometry_diagnostics::GeometryDiagnostics;
 use crate::diagnostics::halt_causes::SimulationHaltCauses;
+use crate::diagnostics::multigroup::MultigroupTallies;
 use crate::utils::vectors::Vec3D;
 use serde::{Deserialize, Serialize};
 
@@ -13,6 +14,8 @@ pub mod diagnostics_tracking;
 pub mod geometry_diagnostics;
 /// Causes for halting the simulation.
 pub mod halt_causes;
+/// Tallying multigroup cross-sections per part.
+pub mod multigroup;
 /// Plotting results for ParaView/Matplotlib.
 pub mod plotting;
 
@@ -51,4 +54,6 @@ pub struct NeutronDiagnostics {
     pub total_fissions: i64,
     pub power_generated: f64,
     pub total_energy: f64,
+
+    pub multigroup_tallies: Option<MultigroupTallies>,
 }
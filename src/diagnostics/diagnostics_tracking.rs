@@ -1,5 +1,6 @@
 use crate::diagnostics::geometry_diagnostics::GeometryDiagnostics;
 use crate::diagnostics::halt_causes::SimulationHaltCauses;
+use crate::neutrons::neutron_dynamics::InteractionTypes;
 use crate::utils::vectors::Vec3D;
 
 use crate::diagnostics::{BinData, NeutronDiagnostics};
@@ -63,6 +64,7 @@ impl NeutronDiagnostics {
             neutron_position_bins_previous,
             previous_bin_generation,
             convergence_tracking,
+            multigroup_tallies: None,
         }
     }
 
@@ -100,6 +102,77 @@ impl NeutronDiagnostics {
         }
     }
 
+    pub fn track_multigroup_flux(
+        &mut self,
+        generation_number: i64,
+        part_index: Option<usize>,
+        energy: f64,
+        distance: f64,
+    ) {
+        if generation_number >= self.track_from_generation {
+            if let (Some(multigroup_tallies), Some(part_index)) =
+                (&mut self.multigroup_tallies, part_index)
+            {
+                multigroup_tallies.track_flux(part_index, energy, distance);
+            }
+        }
+    }
+
+    pub fn track_multigroup_collision(
+        &mut self,
+        generation_number: i64,
+        part_index: Option<usize>,
+        energy: f64,
+        interaction_type: &InteractionTypes,
+        nu_bar: f64,
+    ) {
+        if generation_number >= self.track_from_generation {
+            if let (Some(multigroup_tallies), Some(part_index)) =
+                (&mut self.multigroup_tallies, part_index)
+            {
+                multigroup_tallies.track_collision(part_index, energy, interaction_type, nu_bar);
+            }
+        }
+    }
+
+    pub fn track_multigroup_scattering(
+        &mut self,
+        generation_number: i64,
+        part_index: Option<usize>,
+        initial_energy: f64,
+        final_energy: f64,
+        scattering_cosine: f64,
+    ) {
+        if generation_number >= self.track_from_generation {
+            if let (Some(multigroup_tallies), Some(part_index)) =
+                (&mut self.multigroup_tallies, part_index)
+            {
+                multigroup_tallies.track_scattering(
+                    part_index,
+                    initial_energy,
+                    final_energy,
+                    scattering_cosine,
+                );
+            }
+        }
+    }
+
+    pub fn track_multigroup_fission_neutrons(
+        &mut self,
+        generation_number: i64,
+        part_index: Option<usize>,
+        energy: f64,
+        neutron_count: i32,
+    ) {
+        if generation_number >= self.track_from_generation {
+            if let (Some(multigroup_tallies), Some(part_index)) =
+                (&mut self.multigroup_tallies, part_index)
+            {
+                multigroup_tallies.track_fission_neutrons(part_index, energy, neutron_count);
+            }
+        }
+    }
+
     pub fn update_convergence(&mut self, current_generation: i64) {
         // let bin_count = self.neutron_position_bins.len() as f64;
 
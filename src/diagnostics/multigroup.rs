@@ -0,0 +1,372 @@
+use crate::neutrons::neutron_dynamics::InteractionTypes;
+use crate::utils::config_loading::MultigroupParametersTOML;
+
+use log::warn;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+/// Energy group structure. Groups are numbered from the highest energy down, so group 0 is the fastest.
+#[derive(Clone, Debug)]
+pub struct EnergyGroupStructure {
+    /// Group boundaries in eV, in descending order. Group g lies between boundaries g and g + 1.
+    pub boundaries: Vec<f64>,
+}
+
+impl EnergyGroupStructure {
+    /// Creates the group structure from boundaries in eV, given in any order.
+    pub fn new(boundaries: &[f64]) -> Self {
+        let mut boundaries = boundaries.to_vec();
+        boundaries.sort_by(|a, b| b.total_cmp(a));
+        boundaries.dedup();
+
+        assert!(
+            boundaries.len() >= 2 && boundaries.iter().all(|&boundary| boundary >= 0.0),
+            "The multigroup structure needs at least two distinct non-negative boundaries."
+        );
+
+        EnergyGroupStructure { boundaries }
+    }
+
+    pub fn group_count(&self) -> usize {
+        self.boundaries.len() - 1
+    }
+
+    /// Returns the group of an energy in eV, or None if it lies outside the group structure. Energies on a boundary belong to the faster group.
+    pub fn get_group(&self, energy: f64) -> Option<usize> {
+        if energy > self.boundaries[0] || energy < self.boundaries[self.group_count()] {
+            return None;
+        }
+
+        // Number of boundaries strictly above the energy.
+        let boundaries_above = self
+            .boundaries
+            .partition_point(|&boundary| boundary > energy);
+        Some(
+            boundaries_above
+                .saturating_sub(1)
+                .min(self.group_count() - 1),
+        )
+    }
+}
+
+/// Raw tallies of a single region. Flux is tallied as track length in m, reactions as analog collision counts.
+/// Matrices are stored row-major, with the row being the group before scattering.
+#[derive(Clone, Debug)]
+pub struct RegionTally {
+    pub part_index: usize,
+    pub flux: Vec<f64>,
+    pub total: Vec<f64>,
+    pub absorption: Vec<f64>,
+    pub fission: Vec<f64>,
+    pub nu_fission: Vec<f64>,
+    pub scattering_p0: Vec<f64>,
+    pub scattering_p1: Vec<f64>,
+    pub fission_neutrons: Vec<f64>,
+}
+
+impl RegionTally {
+    fn new(part_index: usize, group_count: usize) -> Self {
+        RegionTally {
+            part_index,
+            flux: vec![0.0; group_count],
+            total: vec![0.0; group_count],
+            absorption: vec![0.0; group_count],
+            fission: vec![0.0; group_count],
+            nu_fission: vec![0.0; group_count],
+            scattering_p0: vec![0.0; group_count * group_count],
+            scattering_p1: vec![0.0; group_count * group_count],
+            fission_neutrons: vec![0.0; group_count],
+        }
+    }
+
+    fn add(&mut self, other: &RegionTally) {
+        let pairs = [
+            (&mut self.flux, &other.flux),
+            (&mut self.total, &other.total),
+            (&mut self.absorption, &other.absorption),
+            (&mut self.fission, &other.fission),
+            (&mut self.nu_fission, &other.nu_fission),
+            (&mut self.scattering_p0, &other.scattering_p0),
+            (&mut self.scattering_p1, &other.scattering_p1),
+            (&mut self.fission_neutrons, &other.fission_neutrons),
+        ];
+
+        for (tally, other_tally) in pairs {
+            for (value, other_value) in tally.iter_mut().zip(other_tally.iter()) {
+                *value += other_value;
+            }
+        }
+    }
+}
+
+/// Tallies of the flux and reaction rates per energy group in the selected parts, from which the multigroup cross-sections are derived.
+#[derive(Clone, Debug)]
+pub struct MultigroupTallies {
+    pub group_structure: EnergyGroupStructure,
+    pub region_tallies: Vec<RegionTally>,
+    /// Region of each part index, or None if the part is not tallied.
+    pub part_regions: Vec<Option<usize>>,
+}
+
+impl MultigroupTallies {
+    pub fn new(group_boundaries: &[f64], part_indices: &[usize]) -> Self {
+        let group_structure = EnergyGroupStructure::new(group_boundaries);
+        let group_count = group_structure.group_count();
+
+        let mut part_regions = vec![None; part_indices.iter().max().map_or(0, |&index| index + 1)];
+        let mut region_tallies = Vec::new();
+        for &part_index in part_indices {
+            if part_regions[part_index].is_some() {
+                continue;
+            }
+            part_regions[part_index] = Some(region_tallies.len());
+            region_tallies.push(RegionTally::new(part_index, group_count));
+        }
+
+        MultigroupTallies {
+            group_structure,
+            region_tallies,
+            part_regions,
+        }
+    }
+
+    pub fn from_parameters(multigroup_parameters: &MultigroupParametersTOML) -> Self {
+        Self::new(
+            &multigroup_parameters.group_boundaries,
+            &multigroup_parameters.part_indices,
+        )
+    }
+
+    /// Returns the tally of the part and the group of the energy, if both are tallied.
+    fn get_tally(&mut self, part_index: usize, energy: f64) -> Option<(&mut RegionTally, usize)> {
+        let region_index = (*self.part_regions.get(part_index)?)?;
+        let group = self.group_structure.get_group(energy)?;
+
+        Some((&mut self.region_tallies[region_index], group))
+    }
+
+    /// Adds the length of a step through a part to the track-length flux.
+    pub fn track_flux(&mut self, part_index: usize, energy: f64, distance: f64) {
+        if let Some((region_tally, group)) = self.get_tally(part_index, energy) {
+            region_tally.flux[group] += distance;
+        }
+    }
+
+    /// Counts a collision with the energy before the interaction. Absorption includes fission, so it matches the usual multigroup definition.
+    pub fn track_collision(
+        &mut self,
+        part_index: usize,
+        energy: f64,
+        interaction_type: &InteractionTypes,
+        nu_bar: f64,
+    ) {
+        if let Some((region_tally, group)) = self.get_tally(part_index, energy) {
+            region_tally.total[group] += 1.0;
+
+            match interaction_type {
+                InteractionTypes::Fission => {
+                    region_tally.absorption[group] += 1.0;
+                    region_tally.fission[group] += 1.0;
+                    region_tally.nu_fission[group] += nu_bar;
+                }
+                InteractionTypes::Absorption => region_tally.absorption[group] += 1.0,
+                _ => {}
+            }
+        }
+    }
+
+    /// Counts a scattering event from the initial to the final energy, with the cosine of the scattering angle for the P1 moment.
+    pub fn track_scattering(
+        &mut self,
+        part_index: usize,
+        initial_energy: f64,
+        final_energy: f64,
+        scattering_cosine: f64,
+    ) {
+        let Some(final_group) = self.group_structure.get_group(final_energy) else {
+            return;
+        };
+        let group_count = self.group_structure.group_count();
+
+        if let Some((region_tally, initial_group)) = self.get_tally(part_index, initial_energy) {
+            let matrix_index = initial_group * group_count + final_group;
+            region_tally.scattering_p0[matrix_index] += 1.0;
+            region_tally.scattering_p1[matrix_index] += scattering_cosine;
+        }
+    }
+
+    /// Counts the neutrons born from a fission in a part, for the fission spectrum.
+    pub fn track_fission_neutrons(&mut self, part_index: usize, energy: f64, neutron_count: i32) {
+        if let Some((region_tally, group)) = self.get_tally(part_index, energy) {
+            region_tally.fission_neutrons[group] += neutron_count as f64;
+        }
+    }
+
+    /// Adds the tallies of another simulation with the same group structure and parts.
+    pub fn add(&mut self, other: &MultigroupTallies) {
+        for (region_tally, other_region_tally) in self
+            .region_tallies
+            .iter_mut()
+            .zip(other.region_tallies.iter())
+        {
+            region_tally.add(other_region_tally);
+        }
+    }
+
+    /// Derives the multigroup cross-sections of every region from the tallies.
+    pub fn get_cross_sections(&self) -> Vec<MultigroupCrossSections> {
+        self.region_tallies
+            .iter()
+            .map(|region_tally| MultigroupCrossSections::new(&self.group_structure, region_tally))
+            .collect()
+    }
+}
+
+/// Multigroup cross-sections of a region. Cross-sections are macroscopic in 1/m, and the diffusion coefficient is in m.
+/// Scattering matrices are indexed as ```[from group][to group]```.
+#[derive(Serialize, Debug)]
+pub struct MultigroupCrossSections {
+    pub part_index: usize,
+    pub group_count: usize,
+    pub group_boundaries: Vec<f64>,
+    pub flux: Vec<f64>,
+    pub total: Vec<f64>,
+    pub transport: Vec<f64>,
+    pub absorption: Vec<f64>,
+    pub fission: Vec<f64>,
+    pub nu_fission: Vec<f64>,
+    pub chi: Vec<f64>,
+    pub diffusion_coefficient: Vec<f64>,
+    pub scattering_p0: Vec<Vec<f64>>,
+    pub scattering_p1: Vec<Vec<f64>>,
+}
+
+impl MultigroupCrossSections {
+    /// Collapses the tallies of a region by dividing the reaction rates by the flux of their group.
+    /// The diffusion coefficient uses the out-scattering transport correction, D = 1 / (3 (Σ<sub>t</sub> - Σ<sub>g'</sub> Σ<sub>s1, g→g'</sub>)).
+    pub fn new(group_structure: &EnergyGroupStructure, region_tally: &RegionTally) -> Self {
+        let group_count = group_structure.group_count();
+        let flux = region_tally.flux.clone();
+
+        if flux.iter().any(|&group_flux| group_flux <= 0.0) {
+            warn!(
+                "Part {} has no flux in some energy groups; their cross-sections are set to zero.",
+                region_tally.part_index
+            );
+        }
+
+        let per_flux = |rates: &[f64]| -> Vec<f64> {
+            rates
+                .iter()
+                .zip(flux.iter())
+                .map(|(&rate, &group_flux)| {
+                    if group_flux > 0.0 {
+                        rate / group_flux
+                    } else {
+                        0.0
+                    }
+                })
+                .collect()
+        };
+        let per_flux_matrix = |rates: &[f64]| -> Vec<Vec<f64>> {
+            rates
+                .chunks(group_count)
+                .zip(flux.iter())
+                .map(|(row, &group_flux)| {
+                    row.iter()
+                        .map(|&rate| {
+                            if group_flux > 0.0 {
+                                rate / group_flux
+                            } else {
+                                0.0
+                            }
+                        })
+                        .collect()
+                })
+                .collect()
+        };
+
+        let total = per_flux(&region_tally.total);
+        let scattering_p1 = per_flux_matrix(&region_tally.scattering_p1);
+
+        let transport: Vec<f64> = total
+            .iter()
+            .zip(scattering_p1.iter())
+            .map(|(&total, row)| total - row.iter().sum::<f64>())
+            .collect();
+        let diffusion_coefficient = transport
+            .iter()
+            .map(|&transport| {
+                if transport > 0.0 {
+                    1.0 / (3.0 * transport)
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        let fission_neutron_count: f64 = region_tally.fission_neutrons.iter().sum();
+        let chi = region_tally
+            .fission_neutrons
+            .iter()
+            .map(|&count| {
+                if fission_neutron_count > 0.0 {
+                    count / fission_neutron_count
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        MultigroupCrossSections {
+            part_index: region_tally.part_index,
+            group_count,
+            group_boundaries: group_structure.boundaries.clone(),
+            flux: region_tally.flux.clone(),
+            total,
+            transport,
+            absorption: per_flux(&region_tally.absorption),
+            fission: per_flux(&region_tally.fission),
+            nu_fission: per_flux(&region_tally.nu_fission),
+            chi,
+            diffusion_coefficient,
+            scattering_p0: per_flux_matrix(&region_tally.scattering_p0),
+            scattering_p1,
+        }
+    }
+}
+
+/// Header of every multigroup file, describing the conventions for the diffusion code reading it.
+const MULTIGROUP_FILE_HEADER: &str = "\
+# Multigroup cross-sections generated from continuous-energy Monte Carlo tallies.
+# group_boundaries: group edges in eV, descending. Group g (0-based) lies between group_boundaries[g] and group_boundaries[g + 1].
+# flux: track-length flux integrated over the part, in m per tallied history (only relative values are meaningful).
+# total, transport, absorption, fission, nu_fission: macroscopic cross-sections in 1/m. Absorption includes fission.
+# chi: fraction of the fission neutrons born in the part in each group.
+# diffusion_coefficient: 1 / (3 transport) in m, with transport = total - sum over g' of scattering_p1[g][g'].
+# scattering_p0, scattering_p1: scattering matrices in 1/m, indexed [from group][to group]; P1 is weighted with the lab scattering cosine.
+";
+
+/// Writes one documented TOML file per region into the directory, named after the part index.
+pub fn write_multigroup_cross_sections(
+    multigroup_cross_sections: &[MultigroupCrossSections],
+    directory: &Path,
+) {
+    fs::create_dir_all(directory).expect("Failed to create multigroup directory.");
+
+    for region_cross_sections in multigroup_cross_sections {
+        let toml_string = toml::to_string(region_cross_sections)
+            .expect("Failed to serialize multigroup cross-sections.");
+
+        let file_path = directory.join(format!(
+            "part_{}_cross_sections.toml",
+            region_cross_sections.part_index
+        ));
+        fs::write(
+            &file_path,
+            format!("{}\n{}", MULTIGROUP_FILE_HEADER, toml_string),
+        )
+        .unwrap_or_else(|_| panic!("Failed to write multigroup file {:?}.", file_path));
+    }
+}
@@ -1,5 +1,4 @@
 use crate::diagnostics::geometry_diagnostics::GeometryDiagnostics;
-use crate::materials::material_properties::map_indices_to_enum;
 use crate::simulation::Simulation;
 use crate::utils::vectors::Vec3D;
 use log::{debug, error, info};
@@ -129,7 +128,7 @@ pub fn plot_geometry_slice(simulation: &mut Simulation) {
                     "Currently at x, y: {:.2}, {:.2}. Encountering: {:?}",
                     combined_position.x,
                     combined_position.y,
-                    map_indices_to_enum(material_index)
+                    simulation.components.material_data_vector[material_index].name
                 );
             }
 
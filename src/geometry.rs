@@ -1,3 +1,9 @@
+pub mod boundary;
 pub mod components;
+pub mod geometry_check;
+pub mod lattice;
+pub mod part_grid;
 pub mod parts;
 pub mod presets;
+pub mod transform;
+pub mod volume_calculation;
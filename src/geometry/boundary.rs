@@ -0,0 +1,163 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+use crate::geometry::components::BoundingBox;
+use crate::utils::vectors::Vec3D;
+
+/// What happens to a neutron that crosses a face of the outer boundary.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoundaryCondition {
+    /// The neutron escapes and is discarded.
+    #[default]
+    Vacuum,
+    /// The neutron is mirrored back, as if the geometry continued symmetrically behind the face.
+    Reflective,
+    /// The neutron re-enters through the opposite face, as in an infinite repetition of the geometry. Both faces along the axis have to be periodic.
+    Periodic,
+    /// The neutron is sent back in a cosine-distributed random direction, which approximates a reflected lattice without its directional correlations.
+    White,
+}
+
+/// Loading in the outer boundary from the geometry TOML. The condition applies to all faces, unless overridden per face.
+#[derive(Deserialize, Debug)]
+pub struct BoundaryTOML {
+    pub min: Vec3D,
+    pub max: Vec3D,
+    #[serde(default)]
+    pub condition: BoundaryCondition,
+    pub x_min: Option<BoundaryCondition>,
+    pub x_max: Option<BoundaryCondition>,
+    pub y_min: Option<BoundaryCondition>,
+    pub y_max: Option<BoundaryCondition>,
+    pub z_min: Option<BoundaryCondition>,
+    pub z_max: Option<BoundaryCondition>,
+}
+
+/// Axis-aligned outer boundary with a condition on each face. If set, it replaces the spherical simulation range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Boundary {
+    pub bounding_box: BoundingBox,
+    /// Conditions per axis, on the minimum and the maximum face.
+    pub conditions: [[BoundaryCondition; 2]; 3],
+}
+
+impl Boundary {
+    pub fn new(bounding_box: BoundingBox, conditions: [[BoundaryCondition; 2]; 3]) -> Self {
+        let (min, max) = (bounding_box.min.to_array(), bounding_box.max.to_array());
+        assert!(bounding_box.is_bounded(), "The boundary has to be finite.");
+        for axis in 0..3 {
+            assert!(
+                min[axis] < max[axis],
+                "The minimum of the boundary has to be below its maximum along each axis."
+            );
+            assert!(
+                (conditions[axis][0] == BoundaryCondition::Periodic)
+                    == (conditions[axis][1] == BoundaryCondition::Periodic),
+                "Periodic boundary conditions have to be set on both faces along an axis."
+            );
+        }
+
+        Self {
+            bounding_box,
+            conditions,
+        }
+    }
+
+    pub fn from_toml(boundary_toml: &BoundaryTOML) -> Self {
+        let condition = |face_condition: Option<BoundaryCondition>| {
+            face_condition.unwrap_or(boundary_toml.condition)
+        };
+
+        Self::new(
+            BoundingBox {
+                min: boundary_toml.min,
+                max: boundary_toml.max,
+            },
+            [
+                [
+                    condition(boundary_toml.x_min),
+                    condition(boundary_toml.x_max),
+                ],
+                [
+                    condition(boundary_toml.y_min),
+                    condition(boundary_toml.y_max),
+                ],
+                [
+                    condition(boundary_toml.z_min),
+                    condition(boundary_toml.z_max),
+                ],
+            ],
+        )
+    }
+
+    /// Applies the conditions of the faces the neutron has crossed during its last step, moving it back inside.
+    /// The distance travelled beyond the face is continued from the face, so no path length is lost. Returns false if the neutron escapes through a vacuum face.
+    pub fn apply(
+        &self,
+        position: &mut Vec3D,
+        direction: &mut Vec3D,
+        rng: &mut rand::rngs::SmallRng,
+    ) -> bool {
+        if self.bounding_box.is_inside(position) {
+            return true;
+        }
+
+        let (min, max) = (
+            self.bounding_box.min.to_array(),
+            self.bounding_box.max.to_array(),
+        );
+        let mut position_array = position.to_array();
+        let mut direction_array = direction.to_array();
+
+        for axis in 0..3 {
+            let (face, face_position) = if position_array[axis] < min[axis] {
+                (0, min[axis])
+            } else if position_array[axis] > max[axis] {
+                (1, max[axis])
+            } else {
+                continue;
+            };
+
+            match self.conditions[axis][face] {
+                BoundaryCondition::Vacuum => return false,
+                BoundaryCondition::Reflective => {
+                    position_array[axis] = 2.0 * face_position - position_array[axis];
+                    direction_array[axis] = -direction_array[axis];
+                }
+                BoundaryCondition::Periodic => {
+                    let width = max[axis] - min[axis];
+                    position_array[axis] += if face == 0 { width } else { -width };
+                }
+                BoundaryCondition::White => {
+                    let overshoot = (position_array[axis] - face_position).abs();
+                    direction_array = sample_cosine_direction(axis, face, rng);
+                    position_array[axis] = face_position + direction_array[axis] * overshoot;
+                }
+            }
+
+            // A step longer than the box could still leave it on the other side.
+            position_array[axis] = position_array[axis].clamp(min[axis], max[axis]);
+        }
+
+        *position = Vec3D::from_array(position_array);
+        *direction = Vec3D::from_array(direction_array);
+
+        true
+    }
+}
+
+/// Samples a direction into the box through the given face, with the cosine to the inward normal distributed as for an isotropic flux crossing the face.
+fn sample_cosine_direction(axis: usize, face: usize, rng: &mut rand::rngs::SmallRng) -> [f64; 3] {
+    let cos_theta = rng.gen::<f64>().sqrt();
+    let sin_theta = (1.0 - cos_theta.powi(2)).sqrt();
+    let phi = 2.0 * PI * rng.gen::<f64>();
+
+    let mut direction = [0.0; 3];
+    direction[axis] = if face == 0 { cos_theta } else { -cos_theta };
+    direction[(axis + 1) % 3] = sin_theta * phi.cos();
+    direction[(axis + 2) % 3] = sin_theta * phi.sin();
+
+    direction
+}
@@ -1,93 +1,439 @@
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
+use crate::geometry::boundary::Boundary;
+use crate::geometry::lattice::LatticeIndex;
 use crate::geometry::parts::parts::PartTypes;
-use crate::materials::material_data::MaterialData;
-use crate::materials::material_properties::{
-    map_enum_to_indices, MaterialNames, MaterialProperties,
+use crate::geometry::volume_calculation::{calculate_volumes, VolumeCalculation};
+use crate::materials::compounds::UraniumIsotope;
+use crate::materials::energy_grid::{
+    EnergyGrid, GridPosition, MaterialGridIndices, DEFAULT_ENERGY_BIN_COUNT,
 };
+use crate::materials::material_data::MaterialData;
+use crate::materials::material_library::get_material_index_by_name;
+use crate::materials::material_properties::MaterialProperties;
+use crate::neutrons::Neutron;
+use crate::utils::config_loading::CouplingParametersTOML;
 use crate::utils::vectors::Vec3D;
 
 use log::debug;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Avogadro constant in 1/mol, to convert mass densities into number densities.
+pub const AVOGADRO_CONSTANT: f64 = 6.02214076e23;
 
 /// Basic bounding-box for faster rejection: if the neutron is outside the bounding box, the more complex check is skipped.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct BoundingBox {
     pub min: Vec3D,
     pub max: Vec3D,
 }
 
+impl BoundingBox {
+    /// Bounding box of an unbounded region, such as a half-space, which never rejects a position.
+    pub fn unbounded() -> Self {
+        Self {
+            min: Vec3D {
+                x: f64::NEG_INFINITY,
+                y: f64::NEG_INFINITY,
+                z: f64::NEG_INFINITY,
+            },
+            max: Vec3D {
+                x: f64::INFINITY,
+                y: f64::INFINITY,
+                z: f64::INFINITY,
+            },
+        }
+    }
+
+    pub fn is_bounded(&self) -> bool {
+        [
+            self.min.x, self.min.y, self.min.z, self.max.x, self.max.y, self.max.z,
+        ]
+        .iter()
+        .all(|coordinate| coordinate.is_finite())
+    }
+
+    pub fn is_inside(&self, position: &Vec3D) -> bool {
+        position.x >= self.min.x
+            && position.x <= self.max.x
+            && position.y >= self.min.y
+            && position.y <= self.max.y
+            && position.z >= self.min.z
+            && position.z <= self.max.z
+    }
+
+    /// Smallest box containing both boxes.
+    pub fn union(&self, other: &BoundingBox) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// Overlap of both boxes. If they do not overlap, the minimum exceeds the maximum, so that no position is inside.
+    pub fn intersection(&self, other: &BoundingBox) -> Self {
+        Self {
+            min: self.min.max(other.min),
+            max: self.max.min(other.max),
+        }
+    }
+
+    pub fn center(&self) -> Vec3D {
+        self.min.add(self.max).scalar_dot(0.5)
+    }
+
+    pub fn translated(&self, translation: Vec3D) -> Self {
+        Self {
+            min: self.min.add(translation),
+            max: self.max.add(translation),
+        }
+    }
+}
+
+/// How the fraction of a part composition is interpreted.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FractionType {
+    /// The fraction scales the number density from the material library directly.
+    #[default]
+    Legacy,
+    /// The fraction is the share of the atoms in the mixture.
+    Atom,
+    /// The fraction is the share of the mass in the mixture.
+    Weight,
+}
+
 /// Part composition for mixed materials.
-#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+/// The material is referenced by its name in the material library; the index is resolved once when the ```Components``` are created.
+/// For atom and weight fractions, the number density is calculated from the part's mass density and the atomic masses. The fraction is then replaced by the ratio of that number density to the library's, so the cached cross-sections can be scaled the same way as for the legacy form.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PartComposition {
-    pub material_name: MaterialNames,
+    pub material_name: String,
     pub material_fraction: f64,
+    #[serde(skip)]
+    pub fraction_type: FractionType,
+    #[serde(skip)]
+    pub mass_density: f64,
+    #[serde(skip)]
+    pub material_index: usize,
+    /// Set for the uranium of an atom-fraction compound, whose fraction is that of all uranium atoms. It is reduced to the isotope's share with the library's atomic masses when the composition is resolved.
+    #[serde(skip)]
+    pub uranium_isotope: Option<UraniumIsotope>,
+}
+
+impl PartComposition {
+    pub fn new(material_name: &str, material_fraction: f64) -> Self {
+        Self {
+            material_name: material_name.to_string(),
+            material_fraction,
+            fraction_type: FractionType::Legacy,
+            mass_density: 0.0,
+            material_index: 0,
+            uranium_isotope: None,
+        }
+    }
+
+    /// Creates the compositions for a mixture with a total mass density in kg/m3, and atom or weight fractions per material.
+    pub fn from_fractions(
+        mass_density: f64,
+        material_fractions: &[(&str, f64)],
+        fraction_type: FractionType,
+    ) -> Vec<Self> {
+        material_fractions
+            .iter()
+            .map(|(material_name, material_fraction)| Self {
+                fraction_type,
+                mass_density,
+                ..Self::new(material_name, *material_fraction)
+            })
+            .collect()
+    }
+}
+
+/// Calculates the number densities for atom or weight fractions, and replaces the fractions by their ratio to the library number densities.
+/// Legacy compositions are left as-is. The fractions are normalized, so they do not have to add up to one.
+fn resolve_mixture_fractions(
+    part_composition_vector: &mut [PartComposition],
+    material_data_vector: &[MaterialData],
+) {
+    let fraction_type = match part_composition_vector.first() {
+        Some(part_composition) => part_composition.fraction_type,
+        None => return,
+    };
+
+    if fraction_type == FractionType::Legacy {
+        return;
+    }
+
+    assert!(
+        part_composition_vector
+            .iter()
+            .all(|part_composition| part_composition.fraction_type == fraction_type),
+        "Atom and weight fractions cannot be mixed within a part: {:?}",
+        part_composition_vector
+    );
+
+    for part_composition in part_composition_vector.iter_mut() {
+        if let Some(uranium_isotope) = part_composition.uranium_isotope.take() {
+            part_composition.material_fraction *=
+                uranium_isotope.atom_fraction(material_data_vector);
+        }
+    }
+
+    let fraction_sum: f64 = part_composition_vector
+        .iter()
+        .map(|part_composition| part_composition.material_fraction)
+        .sum();
+
+    assert!(
+        fraction_sum > 0.0,
+        "The fractions of a mixture have to be positive: {:?}",
+        part_composition_vector
+    );
+
+    // For atom fractions, all isotopes share the mean molar mass of the mixture.
+    let mean_molar_mass: f64 = part_composition_vector
+        .iter()
+        .map(|part_composition| {
+            part_composition.material_fraction / fraction_sum
+                * material_data_vector[part_composition.material_index].atomic_mass
+        })
+        .sum();
+
+    for part_composition in part_composition_vector.iter_mut() {
+        let material_data = &material_data_vector[part_composition.material_index];
+        let normalized_fraction = part_composition.material_fraction / fraction_sum;
+
+        assert!(
+            material_data.number_density > 0.0 && material_data.atomic_mass > 0.0,
+            "Material {} needs a number density and atomic mass in the library to be used in a mixture.",
+            material_data.name
+        );
+
+        // Mass density in kg/m3 and molar masses in g/mol.
+        let number_density = match fraction_type {
+            FractionType::Atom => {
+                normalized_fraction * part_composition.mass_density * 1e3 * AVOGADRO_CONSTANT
+                    / mean_molar_mass
+            }
+            FractionType::Weight => {
+                normalized_fraction * part_composition.mass_density * 1e3 * AVOGADRO_CONSTANT
+                    / material_data.atomic_mass
+            }
+            FractionType::Legacy => unreachable!(),
+        };
+
+        part_composition.material_fraction = number_density / material_data.number_density;
+    }
+}
+
+/// Resolves the material names of a composition into library indices, and converts atom or weight fractions into number density ratios.
+pub fn resolve_part_composition(
+    part_composition_vector: &mut [PartComposition],
+    material_data_vector: &[MaterialData],
+) {
+    for part_composition in part_composition_vector.iter_mut() {
+        part_composition.material_index =
+            get_material_index_by_name(material_data_vector, &part_composition.material_name)
+                .unwrap_or_else(|| {
+                    panic!(
+                        "Material {} is not defined in the material library.",
+                        part_composition.material_name
+                    )
+                });
+    }
+
+    resolve_mixture_fractions(part_composition_vector, material_data_vector);
+}
+
+/// Temperature state of a part, used for the neutronics-thermal feedback.
+/// The density factor accounts for thermal expansion, the Doppler factor for the broadening of the absorption resonances.
+#[derive(Copy, Clone, Debug)]
+pub struct PartFeedback {
+    pub temperature: f64,
+    pub density_factor: f64,
+    pub doppler_factor: f64,
+}
+
+impl Default for PartFeedback {
+    fn default() -> Self {
+        Self {
+            temperature: 293.15,
+            density_factor: 1.0,
+            doppler_factor: 1.0,
+        }
+    }
+}
+
+impl PartFeedback {
+    /// Calculates the feedback factors for a part at a given temperature.
+    /// The density scales with the volumetric expansion, (1 + α ΔT)^-3, and the absorption with the classical √T Doppler law.
+    pub fn from_temperature(
+        temperature: f64,
+        coupling_parameters: &CouplingParametersTOML,
+    ) -> Self {
+        let temperature_difference = temperature - coupling_parameters.reference_temperature;
+
+        let density_factor = 1.0
+            / (1.0 + coupling_parameters.thermal_expansion_coefficient * temperature_difference)
+                .powi(3);
+
+        let doppler_factor = (1.0
+            + coupling_parameters.doppler_coefficient
+                * (temperature.sqrt() - coupling_parameters.reference_temperature.sqrt()))
+        .max(0.0);
+
+        Self {
+            temperature,
+            density_factor,
+            doppler_factor,
+        }
+    }
+}
+
+/// Builds the grids for the part lookup, and logs how long that took.
+fn build_part_grids(lattice_index: &mut LatticeIndex, parts_vector: &[PartTypes]) {
+    let start_time = Instant::now();
+    lattice_index.build_part_grids(parts_vector);
+    debug!(
+        "Built the part grids for {} parts in {:.2?}.",
+        parts_vector.len(),
+        start_time.elapsed()
+    );
 }
 
 /// Struct that contains the material data, cached properties and parts - essentially all the geometry.
 #[derive(Debug)]
 pub struct Components {
-    pub material_data_vector: Vec<MaterialData>,
+    pub material_data_vector: Arc<Vec<MaterialData>>,
     pub cached_material_properties: Vec<MaterialProperties>,
+    pub energy_grid: EnergyGrid,
+    pub material_grid_indices: Vec<MaterialGridIndices>,
+    pub active_material_indices: Vec<usize>,
+    pub grid_position: GridPosition,
     pub parts_vector: Vec<PartTypes>,
+    pub lattice_index: LatticeIndex,
+    pub part_feedback_vector: Vec<PartFeedback>,
     pub cache_initialized: bool,
+    pub cache_stamp: u64,
+    pub material_cache_stamps: Vec<u64>,
+    pub part_total_cross_sections: Vec<f64>,
+    pub part_cache_stamps: Vec<u64>,
     pub simulation_range_squared: f64,
+    pub boundary: Option<Boundary>,
 }
 
 impl Components {
-    pub fn new(material_data_vector: Vec<MaterialData>, parts_vector: Vec<PartTypes>) -> Self {
+    /// Creates the components from the material library, which can be shared with other simulations, and the parts.
+    pub fn new(
+        material_data_vector: impl Into<Arc<Vec<MaterialData>>>,
+        mut parts_vector: Vec<PartTypes>,
+    ) -> Self {
+        let material_data_vector: Arc<Vec<MaterialData>> = material_data_vector.into();
         // Has to be updated after creation of the Components-code.
         let simulation_range_squared = -1.0;
 
-        let material_property = MaterialProperties::default();
         let mut cached_material_properties: Vec<MaterialProperties> = Vec::new();
 
-        for _ in material_data_vector.iter() {
-            cached_material_properties.push(material_property.clone());
+        for material_data in material_data_vector.iter() {
+            cached_material_properties.push(MaterialProperties {
+                name: material_data.name.clone(),
+                ..Default::default()
+            });
+        }
+
+        // Resolving the material names once, so that no string comparisons are needed during the simulation.
+        for part in parts_vector.iter_mut() {
+            resolve_part_composition(
+                part.material_composition_vector_mut(),
+                &material_data_vector,
+            );
+        }
+
+        let part_feedback_vector = vec![PartFeedback::default(); parts_vector.len()];
+
+        // Only the materials that are used by any of the parts are ever updated in the cache, as well as Void.
+        let mut active_material_indices: Vec<usize> = vec![0];
+        for part in parts_vector.iter() {
+            for part_composition in part.material_composition_vector() {
+                if !active_material_indices.contains(&part_composition.material_index) {
+                    active_material_indices.push(part_composition.material_index);
+                }
+            }
         }
+        active_material_indices.sort();
+
+        let energy_grid = EnergyGrid::new(&material_data_vector, DEFAULT_ENERGY_BIN_COUNT);
+        let material_grid_indices = material_data_vector
+            .iter()
+            .enumerate()
+            .map(|(index, material_data)| {
+                if active_material_indices.contains(&index) {
+                    energy_grid.hash_material(material_data)
+                } else {
+                    MaterialGridIndices::default()
+                }
+            })
+            .collect();
 
         let is_cache_initialized = false;
 
+        let material_count = material_data_vector.len();
+        let part_count = parts_vector.len();
+
+        let mut lattice_index = LatticeIndex::new(part_count);
+        build_part_grids(&mut lattice_index, &parts_vector);
+
         Components {
             material_data_vector,
             parts_vector,
+            lattice_index,
+            part_feedback_vector,
             cached_material_properties,
+            energy_grid,
+            material_grid_indices,
+            active_material_indices,
+            grid_position: GridPosition::default(),
             cache_initialized: is_cache_initialized,
+            cache_stamp: 0,
+            material_cache_stamps: vec![0; material_count],
+            part_total_cross_sections: vec![0.0; part_count],
+            part_cache_stamps: vec![0; part_count],
             simulation_range_squared,
+            boundary: None,
         }
     }
 
-    /// Automatically calculates the maximum radius squared, beyond which the neutron is discarded.
-    /// If this wasn't set correctly manually, it would mess up (if it's too small, part of the geometry would be ignored; too large, and the simulation becomes very slow if neutrons have to escape first).
-    /// The code iterates over all the parts, skipping those with order <= -1, and determines the largest bounds.
-    /// This function should be ran after creation of the simulation to set components.simulation_range_squared.
-    pub fn get_maximum_radius_squared(&mut self) {
-        let mut maximum_radius = 0.0;
+    /// Replaces the default index, in which every part is checked, by the lattices the parts were placed in by ```load_geometry```.
+    pub fn with_lattice_index(mut self, mut lattice_index: LatticeIndex) -> Self {
+        build_part_grids(&mut lattice_index, &self.parts_vector);
+        self.lattice_index = lattice_index;
 
-        for part in &self.parts_vector {
-            let (bounding_box, center, order) = match part {
-                PartTypes::Sphere(sphere) => (&sphere.bounding_box, &sphere.center, &sphere.order),
-                PartTypes::Cylinder(cylinder) => {
-                    (&cylinder.bounding_box, &cylinder.center, &cylinder.order)
-                }
-                PartTypes::Cuboid(cuboid) => (&cuboid.bounding_box, &cuboid.center, &cuboid.order),
-            };
+        self
+    }
 
-            // To allow a large background using for example a cube (computationally efficient because no squaring for the radius), without having it included in the simulation range, if the order is specified as -1 or less, it will be skipped.
-            if order <= &-1 {
-                continue;
-            }
+    /// Sets the outer boundary, which replaces the spherical simulation range for deciding whether a neutron has escaped.
+    pub fn with_boundary(mut self, boundary: Option<Boundary>) -> Self {
+        self.boundary = boundary;
 
-            let coordinate_min_radius = center.add(bounding_box.min).norm_squared();
-            let coordinate_max_radius = center.add(bounding_box.max).norm_squared();
+        self
+    }
 
-            if coordinate_max_radius > maximum_radius {
-                maximum_radius = coordinate_max_radius
-            }
-            if coordinate_min_radius > maximum_radius {
-                maximum_radius = coordinate_min_radius
-            }
+    /// Applies the outer boundary to a neutron after its step, or checks the simulation range without a boundary. Returns whether the neutron has escaped.
+    pub fn has_escaped(&self, neutron: &mut Neutron, rng: &mut rand::rngs::SmallRng) -> bool {
+        match &self.boundary {
+            Some(boundary) => !boundary.apply(&mut neutron.position, &mut neutron.direction, rng),
+            None => neutron.has_escaped(self.simulation_range_squared),
         }
+    }
+
+    /// Automatically calculates the maximum radius squared, beyond which the neutron is discarded.
+    /// If this wasn't set correctly manually, it would mess up (if it's too small, part of the geometry would be ignored; too large, and the simulation becomes very slow if neutrons have to escape first).
+    /// The code iterates over all the parts, skipping those with order <= -1 and unbounded cells and planes, and determines the largest bounds.
+    /// This function should be ran after creation of the simulation to set components.simulation_range_squared.
+    pub fn get_maximum_radius_squared(&mut self) {
+        let maximum_radius = get_simulation_range_squared(&self.parts_vector);
 
         debug!("Maximum part radius: {:.3} m.", maximum_radius.sqrt());
 
@@ -96,31 +442,102 @@ impl Components {
 
     /// Updating the cache of material properties for the given neutron's energy.
     /// This should be done any time the neutron's energy changes significantly, or whenever the simulation starts.
+    /// The energy is only located on the hashed energy grid here: the materials and parts are evaluated lazily, once the neutron actually reaches them.
     pub fn update_cache_properties(&mut self, neutron_energy: f64) {
-        for (index, material_data) in self.material_data_vector.iter().enumerate() {
-            self.cached_material_properties[index].get_properties(material_data, neutron_energy);
-        }
+        self.grid_position = self.energy_grid.get_position(neutron_energy);
+        self.cache_stamp += 1;
         self.cache_initialized = true;
     }
 
+    /// Returns the macroscopic total cross-section of a part at the current energy, including its feedback. Outside all parts, this is 0.
+    /// It is computed once per energy, together with the properties of the part's materials, and cached until the energy changes.
+    pub fn get_part_total_cross_section(&mut self, part_index: Option<usize>) -> f64 {
+        debug_assert!(self.cache_initialized, "Cache was not initialized!");
+
+        let Some(part_index) = part_index else {
+            return 0.0;
+        };
+
+        if self.part_cache_stamps[part_index] == self.cache_stamp {
+            return self.part_total_cross_sections[part_index];
+        }
+
+        let part_composition_vector = self.parts_vector[part_index].material_composition_vector();
+        for part_composition in part_composition_vector {
+            let material_index = part_composition.material_index;
+            if self.material_cache_stamps[material_index] != self.cache_stamp {
+                self.cached_material_properties[material_index].get_properties_on_grid(
+                    &self.material_data_vector[material_index],
+                    &self.material_grid_indices[material_index],
+                    &self.grid_position,
+                );
+                self.material_cache_stamps[material_index] = self.cache_stamp;
+            }
+        }
+
+        let part_total_cross_section = self.get_composition_total_cross_section(
+            self.parts_vector[part_index].material_composition_vector(),
+            &self.part_feedback_vector[part_index],
+        );
+
+        self.part_total_cross_sections[part_index] = part_total_cross_section;
+        self.part_cache_stamps[part_index] = self.cache_stamp;
+
+        part_total_cross_section
+    }
+
+    /// Returns the feedback of a part, or the default outside all parts.
+    pub fn get_part_feedback(&self, part_index: Option<usize>) -> PartFeedback {
+        match part_index {
+            Some(part_index) => self.part_feedback_vector[part_index],
+            None => PartFeedback::default(),
+        }
+    }
+
+    /// Samples the material the neutron collides with in a part, which is only needed once a collision actually occurs.
+    /// Requires ```get_part_total_cross_section``` to have been called for the part at the current energy. Outside all parts, Void is returned.
+    pub fn select_collision_material(
+        &self,
+        rng: &mut rand::rngs::SmallRng,
+        part_index: Option<usize>,
+        composition_total_cross_section: f64,
+    ) -> usize {
+        let Some(part_index) = part_index else {
+            return 0;
+        };
+
+        debug_assert_eq!(
+            self.part_cache_stamps[part_index], self.cache_stamp,
+            "Part cache was not updated!"
+        );
+
+        self.select_material_from_composition(
+            rng,
+            self.parts_vector[part_index].material_composition_vector(),
+            &self.part_feedback_vector[part_index],
+            composition_total_cross_section,
+        )
+    }
+
     /// Calculates the composition's total cross-section for a given neutron's energy from the cached properties.
     /// The total cross-section can then be used in the neutron dynamics to calculate interaction probabilities.
     /// If the cache is not updated, this will mess up.
     pub fn get_composition_total_cross_section(
         &self,
         part_composition_vector: &Vec<PartComposition>,
+        part_feedback: &PartFeedback,
     ) -> f64 {
         let mut overall_total_cross_section: f64 = 0.0;
 
         debug_assert!(self.cache_initialized, "Cache was not initialized!");
 
         for part_composition in part_composition_vector {
-            let material_name = part_composition.material_name;
-            let material_index = map_enum_to_indices(&material_name);
+            let material_index = part_composition.material_index;
             let material_composition = &self.cached_material_properties[material_index];
 
-            overall_total_cross_section +=
-                material_composition.total_cross_section() * part_composition.material_fraction;
+            overall_total_cross_section += material_composition
+                .total_cross_section_with_feedback(part_feedback.doppler_factor)
+                * part_composition.material_fraction;
 
             // debug!(
             //     "In the function: Material cross section for {:?}: {}",
@@ -137,19 +554,25 @@ impl Components {
 
         // debug!("Overall cross-section: {}", overall_total_cross_section);
 
-        overall_total_cross_section
+        overall_total_cross_section * part_feedback.density_factor
     }
 
     /// Sums each of the part's material composition vectors and ensures the fractions add up to 1. If not, it throws an error.
     /// This is probably a very error-prone operation, if we specify the parts manually.
     /// If we move to configuration files later, we can move this to integration tests.
+    /// Parts defined through atom or weight fractions are skipped, as their fractions are normalized and converted when the ```Components``` are created.
     pub fn check_material_fractions_sum(&self) {
         for part in &self.parts_vector {
-            let material_composition_vector = match part {
-                PartTypes::Sphere(sphere) => &sphere.material_composition_vector,
-                PartTypes::Cylinder(cylinder) => &cylinder.material_composition_vector,
-                PartTypes::Cuboid(cuboid) => &cuboid.material_composition_vector,
-            };
+            let material_composition_vector = part.material_composition_vector();
+
+            if material_composition_vector
+                .iter()
+                .any(|material_composition| {
+                    material_composition.fraction_type != FractionType::Legacy
+                })
+            {
+                continue;
+            }
 
             let mut material_sum = 0.0;
 
@@ -172,6 +595,7 @@ impl Components {
         &self,
         rng: &mut rand::rngs::SmallRng,
         part_composition_vector: &Vec<PartComposition>,
+        part_feedback: &PartFeedback,
         composition_total_cross_section: f64,
     ) -> usize {
         let material_selection_criterion = rng.gen::<f64>();
@@ -182,11 +606,12 @@ impl Components {
         // debug!("Criterion: {}", material_selection_criterion);
 
         for part_composition in part_composition_vector.iter() {
-            let material_name = part_composition.material_name;
-            let material_index = map_enum_to_indices(&material_name);
+            let material_index = part_composition.material_index;
             let material_composition = &self.cached_material_properties[material_index];
-            let normalized_cross_section = material_composition.total_cross_section()
+            let normalized_cross_section = material_composition
+                .total_cross_section_with_feedback(part_feedback.doppler_factor)
                 * part_composition.material_fraction
+                * part_feedback.density_factor
                 / composition_total_cross_section;
 
             // debug!(
@@ -206,14 +631,12 @@ impl Components {
             if material_selection_criterion >= cumulative_probability
                 && material_selection_criterion < cumulative_probability + normalized_cross_section
             {
-                let cached_material_vector_index = map_enum_to_indices(&material_name);
-
                 // debug!(
                 //     "Material: {:?}",
-                //     self.cached_material_properties[cached_material_vector_index].name
+                //     self.cached_material_properties[material_index].name
                 // );
 
-                return cached_material_vector_index;
+                return material_index;
             }
 
             cumulative_probability += normalized_cross_section;
@@ -226,74 +649,100 @@ impl Components {
         0
     }
 
+    /// Sets the temperature of each part and updates the density and Doppler feedback factors accordingly.
+    /// The temperatures are expected in the same order as ```parts_vector```.
+    pub fn set_part_temperatures(
+        &mut self,
+        part_temperatures: &[f64],
+        coupling_parameters: &CouplingParametersTOML,
+    ) {
+        debug_assert_eq!(part_temperatures.len(), self.parts_vector.len());
+
+        self.part_feedback_vector = part_temperatures
+            .iter()
+            .map(|&temperature| PartFeedback::from_temperature(temperature, coupling_parameters))
+            .collect();
+
+        // The cached part cross-sections include the feedback, so they have to be recomputed.
+        self.part_cache_stamps.fill(0);
+    }
+
+    /// Checks whether any of the materials in the part's composition is fissionable, i.e. whether it is fuel.
+    pub fn is_part_fissionable(&self, part_index: usize) -> bool {
+        self.parts_vector[part_index]
+            .material_composition_vector()
+            .iter()
+            .any(|part_composition| {
+                self.material_data_vector[part_composition.material_index].fissionable
+            })
+    }
+
+    /// Gets the index of the part at the neutron's current position by checking each individual part and their order.
+    /// The part with the highest order is selected, through constructive solid geometry. Returns None outside all parts.
+    /// Inside lattices, only the parts of the universes in the cells containing the position are checked.
+    pub fn get_part_index(&self, neutron_position: &Vec3D) -> Option<usize> {
+        self.lattice_index
+            .get_part_index(&self.parts_vector, neutron_position)
+    }
+
+    /// Gets the material index based on the neutron's current position, using the highest-order part.
+    /// The actual isotope that is interacted with is then selected from a composite material and returned, together with the total cross-section and the part's feedback.
+    pub fn get_material_selection(
+        &mut self,
+        rng: &mut rand::rngs::SmallRng,
+        neutron_position: &Vec3D,
+    ) -> (usize, f64, PartFeedback) {
+        let part_index = self.get_part_index(neutron_position);
+        let composition_total_cross_section = self.get_part_total_cross_section(part_index);
+        let max_material_index =
+            self.select_collision_material(rng, part_index, composition_total_cross_section);
+
+        (
+            max_material_index,
+            composition_total_cross_section,
+            self.get_part_feedback(part_index),
+        )
+    }
+
     /// Gets the material index based on the neutron's current position by checking each individual part and their order.
     /// The part with the highest order is selected, through constructive solid geometry.
     /// The actual isotope that is interacted with is then selected from a composite material and returned, together with the total cross-section.
     pub fn get_material_index(
-        &self,
+        &mut self,
         rng: &mut rand::rngs::SmallRng,
         neutron_position: &Vec3D,
     ) -> (usize, f64) {
-        let mut maximum_order = i32::MIN;
-
-        let empty_reference_vector: Vec<PartComposition> = Vec::default();
-        let mut max_part_composition_vector: &Vec<PartComposition> = &empty_reference_vector;
-
-        // Iterating over all the different parts.
-        for part in &self.parts_vector {
-            // Checks each option in the enum and returns the matches.
-            let (is_inside, order, material_composition_vector) = match part {
-                PartTypes::Sphere(sphere) => (
-                    sphere.is_inside(neutron_position),
-                    sphere.order,
-                    &sphere.material_composition_vector,
-                ),
-                PartTypes::Cylinder(cylinder) => (
-                    cylinder.is_inside(neutron_position),
-                    cylinder.order,
-                    &cylinder.material_composition_vector,
-                ),
-                PartTypes::Cuboid(cuboid) => (
-                    cuboid.is_inside(neutron_position),
-                    cuboid.order,
-                    &cuboid.material_composition_vector,
-                ),
-            };
-
-            // Keeping track of the highest-order part for Constructive Solid Geometry.
-            if order > maximum_order && is_inside {
-                maximum_order = order;
-                max_part_composition_vector = material_composition_vector;
-            }
-        }
+        let (material_index, composition_total_cross_section, _) =
+            self.get_material_selection(rng, neutron_position);
 
-        let composition_total_cross_section =
-            self.get_composition_total_cross_section(max_part_composition_vector);
-
-        // debug!("{}", composition_total_cross_section);
+        (material_index, composition_total_cross_section)
+    }
 
-        let max_material_index = self.select_material_from_composition(
+    /// Estimates the volumes and masses of the parts and materials by sampling positions in the box, with the part selection of the simulation.
+    pub fn calculate_volumes(
+        &self,
+        sampling_box: &BoundingBox,
+        sample_count: usize,
+        rng: &mut rand::rngs::SmallRng,
+    ) -> VolumeCalculation {
+        calculate_volumes(
+            &self.parts_vector,
+            &self.lattice_index,
+            &self.material_data_vector,
+            sampling_box,
+            sample_count,
             rng,
-            max_part_composition_vector,
-            composition_total_cross_section,
-        );
-
-        // debug!("Max material index: {}", max_material_index);
-
-        // let material_name = map_indices_to_enum(max_material_index);
-        // info!("Material name: {:?}", max_material_index);
-
-        (max_material_index, composition_total_cross_section)
+        )
     }
 
-    /// Gets the material properties and total cross-section based on the neutron's current position.
+    /// Gets the material properties, total cross-section and the part's Doppler factor based on the neutron's current position.
     /// This requires the cache to have been updated beforehand.
     /// The function will throw an exception if this has not been done.
     pub fn get_material_properties(
-        &self,
+        &mut self,
         rng: &mut rand::rngs::SmallRng,
         neutron_position: &Vec3D,
-    ) -> (&MaterialProperties, f64) {
+    ) -> (&MaterialProperties, f64, f64) {
         // Ensuring everything is correctly initialized.
         debug_assert!(self.cache_initialized, "Cache was not initialized!");
         debug_assert!(
@@ -301,10 +750,49 @@ impl Components {
             "Simulation range is not set correctly."
         );
 
-        let (material_index, composition_total_cross_section) =
-            self.get_material_index(rng, neutron_position);
+        let (material_index, composition_total_cross_section, part_feedback) =
+            self.get_material_selection(rng, neutron_position);
         let material_properties = &self.cached_material_properties[material_index];
 
-        (material_properties, composition_total_cross_section)
+        (
+            material_properties,
+            composition_total_cross_section,
+            part_feedback.doppler_factor,
+        )
+    }
+}
+
+/// Squared radius of the spherical simulation range around the origin, beyond which neutrons are discarded if there is no outer boundary.
+/// This is taken from the corners of the bounding boxes, which are in the global frame, whereas the part centers of transformed parts are in their local frame.
+pub fn get_simulation_range_squared(parts_vector: &[PartTypes]) -> f64 {
+    let mut maximum_radius = 0.0;
+
+    for part in parts_vector {
+        let bounding_box = part.bounding_box();
+
+        // Unbounded cells and planes, such as the outside of a sphere, would make the simulation range infinite.
+        if !bounding_box.is_bounded() {
+            continue;
+        }
+
+        // To allow a large background using for example a cube (computationally efficient because no squaring for the radius), without having it included in the simulation range, if the order is specified as -1 or less, it will be skipped.
+        if part.order() <= -1 {
+            continue;
+        }
+
+        // The corner farthest from the origin takes the larger bound in magnitude along every axis.
+        let farthest_corner_radius: f64 = bounding_box
+            .min
+            .to_array()
+            .iter()
+            .zip(bounding_box.max.to_array())
+            .map(|(min, max)| min.abs().max(max.abs()).powi(2))
+            .sum();
+
+        if farthest_corner_radius > maximum_radius {
+            maximum_radius = farthest_corner_radius
+        }
     }
+
+    maximum_radius
 }
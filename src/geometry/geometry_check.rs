@@ -0,0 +1,442 @@
+use rand::rngs::SmallRng;
+use rand::Rng;
+use std::collections::HashMap;
+
+use crate::geometry::boundary::Boundary;
+use crate::geometry::components::{get_simulation_range_squared, BoundingBox};
+use crate::geometry::lattice::LatticeIndex;
+use crate::geometry::parts::parts::PartTypes;
+use crate::utils::vectors::Vec3D;
+
+/// Problem found in a geometry by sampling it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum GeometryIssueKind {
+    /// Two parts of the same order overlap without a higher-order part covering them, so only their position in the parts vector decides which one is selected.
+    EqualOrderOverlap {
+        part_indices: [usize; 2],
+        order: i32,
+    },
+    /// The part is never selected, as higher-order or earlier parts cover all sampled positions inside it.
+    HiddenPart {
+        part_index: usize,
+        covering_part_index: usize,
+    },
+    /// The part extends beyond the simulation range or the outer boundary, where neutrons are discarded.
+    OutsideSimulationRange { part_index: usize },
+    /// Positions inside the simulation range that no part contains, although parts enclose them along all axis directions, or the outer boundary encloses the geometry.
+    /// Neutrons stream through these regions without colliding. The parts are those found on both sides along the sampled ray, where None stands for the outer boundary.
+    UnexpectedVoid {
+        enclosing_part_indices: [Option<usize>; 2],
+    },
+}
+
+/// Issue with the first position at which it was found, and the number of sampled positions showing it.
+#[derive(Debug, Clone)]
+pub struct GeometryIssue {
+    pub kind: GeometryIssueKind,
+    pub position: Vec3D,
+    pub sample_count: usize,
+}
+
+impl GeometryIssue {
+    /// Readable description of the issue, naming the parts and the position.
+    pub fn describe(&self, parts_vector: &[PartTypes]) -> String {
+        let position = format!(
+            "({:.4}, {:.4}, {:.4}) m",
+            self.position.x, self.position.y, self.position.z
+        );
+        let part = |part_index: usize| {
+            format!(
+                "part {} ({}, {})",
+                part_index,
+                parts_vector[part_index].name(),
+                parts_vector[part_index].material_name()
+            )
+        };
+
+        match self.kind {
+            GeometryIssueKind::EqualOrderOverlap {
+                part_indices,
+                order,
+            } => format!(
+                "Equal-order overlap: {} and {} both have order {} at {}, in {} samples. The first one is selected.",
+                part(part_indices[0]),
+                part(part_indices[1]),
+                order,
+                position,
+                self.sample_count
+            ),
+            GeometryIssueKind::HiddenPart {
+                part_index,
+                covering_part_index,
+            } => format!(
+                "Hidden part: {} is covered at all {} sampled positions inside it, e.g. by {} at {}.",
+                part(part_index),
+                self.sample_count,
+                part(covering_part_index),
+                position
+            ),
+            GeometryIssueKind::OutsideSimulationRange { part_index } => format!(
+                "Outside the simulation range: {} extends to {}, where neutrons are discarded, in {} samples.",
+                part(part_index),
+                position,
+                self.sample_count
+            ),
+            GeometryIssueKind::UnexpectedVoid {
+                enclosing_part_indices,
+            } => {
+                let enclosing = enclosing_part_indices.map(|part_index| {
+                    part_index.map_or("the outer boundary".to_string(), part)
+                });
+                format!(
+                    "Unexpected void: no part between {} and {} at {}, in {} samples.",
+                    enclosing[0], enclosing[1], position, self.sample_count
+                )
+            }
+        }
+    }
+}
+
+/// Number of samples for the geometry check.
+#[derive(Debug, Clone)]
+pub struct GeometryCheckSettings {
+    /// Rays through the sampling box, along which positions are sampled at each step length.
+    pub ray_count: usize,
+    /// Distance between the positions along a ray, by default the distance step of the neutrons.
+    pub step_length: f64,
+    /// Positions sampled in the bounding box of every part, to find hidden parts and parts outside the simulation range.
+    pub part_point_count: usize,
+}
+
+impl Default for GeometryCheckSettings {
+    fn default() -> Self {
+        Self {
+            ray_count: 1000,
+            step_length: 0.001,
+            part_point_count: 2000,
+        }
+    }
+}
+
+/// Samples positions along random rays and inside every part, and reports equal-order overlaps, hidden parts, parts outside the simulation range and unexpected void, sorted by kind.
+/// Without an outer boundary, the spherical simulation range of the parts is used, and void is only reported where parts enclose it along all six axis directions, as void around and between separate parts is expected.
+/// Since the check is based on sampling, small issues can be missed, but every reported issue is real at the given position.
+pub fn check_geometry(
+    parts_vector: &[PartTypes],
+    lattice_index: &LatticeIndex,
+    boundary: Option<&Boundary>,
+    settings: &GeometryCheckSettings,
+    rng: &mut SmallRng,
+) -> Vec<GeometryIssue> {
+    // Rays are sampled in the outer boundary, or in the box around all bounded parts.
+    let sampling_box = boundary.map(|boundary| boundary.bounding_box).or_else(|| {
+        parts_vector
+            .iter()
+            .map(|part| *part.bounding_box())
+            .filter(BoundingBox::is_bounded)
+            .reduce(|first, second| first.union(&second))
+    });
+
+    let mut geometry_check = GeometryCheck {
+        parts_vector,
+        lattice_index,
+        boundary,
+        simulation_range_squared: get_simulation_range_squared(parts_vector),
+        sampling_box,
+        step_length: settings.step_length,
+        issues: HashMap::new(),
+    };
+
+    if sampling_box.is_some() {
+        for _ in 0..settings.ray_count {
+            geometry_check.check_ray(rng);
+        }
+    }
+
+    for part_index in 0..parts_vector.len() {
+        geometry_check.check_part(part_index, settings.part_point_count, rng);
+    }
+
+    let mut issues: Vec<GeometryIssue> = geometry_check.issues.into_values().collect();
+    issues.sort_by_key(|issue| issue.kind);
+
+    issues
+}
+
+struct GeometryCheck<'a> {
+    parts_vector: &'a [PartTypes],
+    lattice_index: &'a LatticeIndex,
+    boundary: Option<&'a Boundary>,
+    simulation_range_squared: f64,
+    sampling_box: Option<BoundingBox>,
+    step_length: f64,
+    issues: HashMap<GeometryIssueKind, GeometryIssue>,
+}
+
+impl GeometryCheck<'_> {
+    /// Counts a sample of the issue, keeping the first position at which it was found.
+    fn record(&mut self, kind: GeometryIssueKind, position: Vec3D, sample_count: usize) {
+        self.issues
+            .entry(kind)
+            .or_insert(GeometryIssue {
+                kind,
+                position,
+                sample_count: 0,
+            })
+            .sample_count += sample_count;
+    }
+
+    fn is_in_simulation_range(&self, position: &Vec3D) -> bool {
+        match self.boundary {
+            Some(boundary) => boundary.bounding_box.is_inside(position),
+            None => position.norm_squared() < self.simulation_range_squared,
+        }
+    }
+
+    /// Gets the selected part at the position, and records an overlap if several parts share the highest order.
+    fn inspect_position(&mut self, position: &Vec3D) -> Option<usize> {
+        let containing_part_indices = self
+            .lattice_index
+            .get_containing_part_indices(self.parts_vector, position);
+        let maximum_order = containing_part_indices
+            .iter()
+            .map(|&part_index| self.parts_vector[part_index].order())
+            .max()?;
+
+        // The containing parts are sorted by index, so the first ones of the highest order are the selected part and the part it overlaps.
+        let mut maximum_order_part_indices = containing_part_indices
+            .into_iter()
+            .filter(|&part_index| self.parts_vector[part_index].order() == maximum_order);
+        let selected_part_index = maximum_order_part_indices.next();
+
+        if let (Some(first_part_index), Some(second_part_index)) =
+            (selected_part_index, maximum_order_part_indices.next())
+        {
+            self.record(
+                GeometryIssueKind::EqualOrderOverlap {
+                    part_indices: [first_part_index, second_part_index],
+                    order: maximum_order,
+                },
+                *position,
+                1,
+            );
+        }
+
+        selected_part_index
+    }
+
+    /// Steps along a random ray through the whole sampling box, checking for overlaps and for void between the parts.
+    fn check_ray(&mut self, rng: &mut SmallRng) {
+        let Some(sampling_box) = self.sampling_box else {
+            return;
+        };
+        let start_position = sample_position(&sampling_box, rng);
+        let direction = Vec3D::random_unit_vector(rng);
+
+        // Distances along the ray to the faces of the box, in both directions.
+        let (mut minimum_distance, mut maximum_distance) = (f64::NEG_INFINITY, f64::INFINITY);
+        let (start, direction_array, min, max) = (
+            start_position.to_array(),
+            direction.to_array(),
+            sampling_box.min.to_array(),
+            sampling_box.max.to_array(),
+        );
+        for axis in 0..3 {
+            if direction_array[axis] == 0.0 {
+                continue;
+            }
+            let first_distance = (min[axis] - start[axis]) / direction_array[axis];
+            let second_distance = (max[axis] - start[axis]) / direction_array[axis];
+            minimum_distance = minimum_distance.max(first_distance.min(second_distance));
+            maximum_distance = maximum_distance.min(first_distance.max(second_distance));
+        }
+
+        let step_count = ((maximum_distance - minimum_distance) / self.step_length).ceil() as usize;
+
+        // Void is tracked as a run of positions without a part, from its first position and with the part selected before it.
+        let mut previous_part_index = None;
+        let mut void_run: Option<(Vec3D, usize, Option<usize>)> = None;
+        let step_length = self.step_length;
+        let get_middle_position = |first_position: Vec3D, void_count: usize| {
+            first_position.add(direction.scalar_dot((void_count - 1) as f64 * step_length / 2.0))
+        };
+
+        for step in 0..step_count {
+            let distance = minimum_distance + (step as f64 + 0.5) * self.step_length;
+            let position = start_position.add(direction.scalar_dot(distance));
+
+            // Void that reaches the spherical simulation range is outside the geometry.
+            if !self.is_in_simulation_range(&position) {
+                previous_part_index = None;
+                void_run = None;
+                continue;
+            }
+
+            match self.inspect_position(&position) {
+                Some(part_index) => {
+                    if let Some((first_position, void_count, before_part_index)) = void_run.take() {
+                        self.record_void(
+                            [before_part_index, Some(part_index)],
+                            get_middle_position(first_position, void_count),
+                            void_count,
+                        );
+                    }
+                    previous_part_index = Some(part_index);
+                }
+                None => match &mut void_run {
+                    Some((_, void_count, _)) => *void_count += 1,
+                    None => void_run = Some((position, 1, previous_part_index)),
+                },
+            }
+        }
+
+        if let Some((first_position, void_count, before_part_index)) = void_run {
+            self.record_void(
+                [before_part_index, None],
+                get_middle_position(first_position, void_count),
+                void_count,
+            );
+        }
+    }
+
+    /// Records a run of void along a ray, if the outer boundary or parts in all axis directions from its middle enclose it.
+    fn record_void(
+        &mut self,
+        mut enclosing_part_indices: [Option<usize>; 2],
+        position: Vec3D,
+        sample_count: usize,
+    ) {
+        if self.boundary.is_none()
+            && (enclosing_part_indices.contains(&None) || !self.is_enclosed(&position))
+        {
+            return;
+        }
+
+        enclosing_part_indices.sort();
+        self.record(
+            GeometryIssueKind::UnexpectedVoid {
+                enclosing_part_indices,
+            },
+            position,
+            sample_count,
+        );
+    }
+
+    /// Checks whether a part is found along each axis direction from the position, before leaving the sampling box or the simulation range.
+    fn is_enclosed(&self, position: &Vec3D) -> bool {
+        let Some(sampling_box) = self.sampling_box else {
+            return false;
+        };
+
+        (0..3).all(|axis| {
+            [-1.0, 1.0].into_iter().all(|sign| {
+                let mut direction = [0.0; 3];
+                direction[axis] = sign * self.step_length;
+                let step = Vec3D::from_array(direction);
+
+                let mut current_position = position.add(step);
+                while sampling_box.is_inside(&current_position)
+                    && self.is_in_simulation_range(&current_position)
+                {
+                    if self
+                        .lattice_index
+                        .get_part_index(self.parts_vector, &current_position)
+                        .is_some()
+                    {
+                        return true;
+                    }
+                    current_position = current_position.add(step);
+                }
+
+                false
+            })
+        })
+    }
+
+    /// Samples positions inside the part, to find whether it is ever selected and whether it reaches beyond the simulation range.
+    fn check_part(&mut self, part_index: usize, part_point_count: usize, rng: &mut SmallRng) {
+        let part = &self.parts_vector[part_index];
+        let part_box = *part.bounding_box();
+        let is_bounded = part_box.is_bounded();
+
+        // Unbounded parts are only sampled where the other parts are, and they are expected to reach beyond the simulation range.
+        // Without an outer boundary, this also holds for the background parts with an order of -1 or less, which the simulation range excludes.
+        let checks_range = is_bounded && (self.boundary.is_some() || part.order() > -1);
+        let part_box = match (is_bounded, self.sampling_box) {
+            (true, _) => part_box,
+            (false, Some(sampling_box)) => part_box.intersection(&sampling_box),
+            (false, None) => return,
+        };
+        if !part_box.is_bounded()
+            || part_box.min.x > part_box.max.x
+            || part_box.min.y > part_box.max.y
+            || part_box.min.z > part_box.max.z
+        {
+            return;
+        }
+
+        let mut inside_count = 0;
+        let mut is_selected = false;
+        let mut covered_position = None;
+        let mut outside_range: Option<(Vec3D, usize)> = None;
+
+        for _ in 0..part_point_count {
+            let position = sample_position(&part_box, rng);
+
+            // Parts in lattice cells only count inside their cell.
+            if !self.parts_vector[part_index].is_inside(&position)
+                || !self
+                    .lattice_index
+                    .get_containing_part_indices(self.parts_vector, &position)
+                    .contains(&part_index)
+            {
+                continue;
+            }
+
+            if !self.is_in_simulation_range(&position) {
+                if checks_range {
+                    outside_range.get_or_insert((position, 0)).1 += 1;
+                }
+                continue;
+            }
+
+            inside_count += 1;
+            match self.inspect_position(&position) {
+                Some(selected_part_index) if selected_part_index == part_index => {
+                    is_selected = true
+                }
+                Some(selected_part_index) => {
+                    covered_position.get_or_insert((position, selected_part_index));
+                }
+                None => {}
+            }
+        }
+
+        if let Some((position, sample_count)) = outside_range {
+            self.record(
+                GeometryIssueKind::OutsideSimulationRange { part_index },
+                position,
+                sample_count,
+            );
+        }
+
+        if let (false, Some((position, covering_part_index))) = (is_selected, covered_position) {
+            self.record(
+                GeometryIssueKind::HiddenPart {
+                    part_index,
+                    covering_part_index,
+                },
+                position,
+                inside_count,
+            );
+        }
+    }
+}
+
+fn sample_position(sampling_box: &BoundingBox, rng: &mut SmallRng) -> Vec3D {
+    Vec3D {
+        x: rng.gen_range(sampling_box.min.x..=sampling_box.max.x),
+        y: rng.gen_range(sampling_box.min.y..=sampling_box.max.y),
+        z: rng.gen_range(sampling_box.min.z..=sampling_box.max.z),
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+
+use crate::geometry::part_grid::{PartGrid, MINIMUM_GRID_PART_COUNT};
+use crate::geometry::parts::parts::PartTypes;
+use crate::utils::vectors::Vec3D;
+
+/// Layout of the cells of a lattice in the x-y plane. Lattices extend infinitely along z; the parts of the universes bound them.
+#[derive(Debug, Clone)]
+pub enum LatticeGrid {
+    /// Cells of ```pitch[0]``` by ```pitch[1]```, stored row by row from the top row (largest y) down, as in the TOML map.
+    Rectangular {
+        pitch: [f64; 2],
+        row_count: usize,
+        column_count: usize,
+        top_left_corner: Vec3D,
+    },
+    /// Hexagonal cells with their flats facing x, so that neighbouring cells in a row are one pitch apart.
+    /// The cells are found through their axial coordinates relative to the first cell.
+    Hexagonal {
+        pitch: f64,
+        first_center: Vec3D,
+        cell_indices: HashMap<(i64, i64), usize>,
+    },
+}
+
+/// Lattice placing universes on a pitch. Each cell refers to its own instance of a universe, or to none if the cell is empty.
+#[derive(Debug, Clone)]
+pub struct Lattice {
+    pub grid: LatticeGrid,
+    pub cell_centers: Vec<Vec3D>,
+    pub universe_instances: Vec<Option<usize>>,
+}
+
+impl Lattice {
+    /// Rectangular lattice around its center, with all rows of the same length.
+    pub fn rectangular(
+        center: Vec3D,
+        pitch: [f64; 2],
+        row_count: usize,
+        column_count: usize,
+    ) -> Self {
+        assert!(
+            pitch[0] > 0.0 && pitch[1] > 0.0,
+            "The pitch of a lattice has to be positive."
+        );
+
+        let top_left_corner = Vec3D {
+            x: center.x - pitch[0] * column_count as f64 / 2.0,
+            y: center.y + pitch[1] * row_count as f64 / 2.0,
+            z: center.z,
+        };
+
+        let cell_centers = (0..row_count)
+            .flat_map(|row| {
+                (0..column_count).map(move |column| Vec3D {
+                    x: top_left_corner.x + pitch[0] * (column as f64 + 0.5),
+                    y: top_left_corner.y - pitch[1] * (row as f64 + 0.5),
+                    z: center.z,
+                })
+            })
+            .collect();
+
+        Self {
+            grid: LatticeGrid::Rectangular {
+                pitch,
+                row_count,
+                column_count,
+                top_left_corner,
+            },
+            cell_centers,
+            universe_instances: Vec::new(),
+        }
+    }
+
+    /// Hexagonal lattice around its center from rows of cells, which are centered on the lattice and staggered by half a pitch.
+    /// A hexagonal assembly with three rings has rows of 3, 4, 5, 4 and 3 cells.
+    pub fn hexagonal(center: Vec3D, pitch: f64, row_lengths: &[usize]) -> Self {
+        assert!(pitch > 0.0, "The pitch of a lattice has to be positive.");
+        assert!(
+            row_lengths
+                .windows(2)
+                .all(|lengths| (lengths[0] + lengths[1]) % 2 == 1),
+            "Adjacent rows of a hexagonal lattice have to differ in length by an odd number, so that they are staggered."
+        );
+
+        let row_spacing = pitch * 3.0_f64.sqrt() / 2.0;
+        let middle_row = (row_lengths.len() as f64 - 1.0) / 2.0;
+
+        let cell_centers: Vec<Vec3D> = row_lengths
+            .iter()
+            .enumerate()
+            .flat_map(|(row, &row_length)| {
+                let middle_column = (row_length as f64 - 1.0) / 2.0;
+                (0..row_length).map(move |column| Vec3D {
+                    x: center.x + pitch * (column as f64 - middle_column),
+                    y: center.y + row_spacing * (middle_row - row as f64),
+                    z: center.z,
+                })
+            })
+            .collect();
+
+        let first_center = cell_centers.first().copied().unwrap_or(center);
+        let cell_indices = cell_centers
+            .iter()
+            .enumerate()
+            .map(|(cell_index, cell_center)| {
+                (
+                    get_axial_coordinates(&cell_center.subtract(first_center), pitch),
+                    cell_index,
+                )
+            })
+            .collect();
+
+        Self {
+            grid: LatticeGrid::Hexagonal {
+                pitch,
+                first_center,
+                cell_indices,
+            },
+            cell_centers,
+            universe_instances: Vec::new(),
+        }
+    }
+
+    /// Index of the cell containing the position, or None outside the lattice.
+    pub fn get_cell_index(&self, position: &Vec3D) -> Option<usize> {
+        match &self.grid {
+            LatticeGrid::Rectangular {
+                pitch,
+                row_count,
+                column_count,
+                top_left_corner,
+            } => {
+                let column = ((position.x - top_left_corner.x) / pitch[0]).floor();
+                let row = ((top_left_corner.y - position.y) / pitch[1]).floor();
+
+                if column < 0.0
+                    || row < 0.0
+                    || column >= *column_count as f64
+                    || row >= *row_count as f64
+                {
+                    return None;
+                }
+
+                Some(row as usize * column_count + column as usize)
+            }
+            LatticeGrid::Hexagonal {
+                pitch,
+                first_center,
+                cell_indices,
+            } => cell_indices
+                .get(&get_axial_coordinates(
+                    &position.subtract(*first_center),
+                    *pitch,
+                ))
+                .copied(),
+        }
+    }
+
+    /// Universe instance in the cell containing the position.
+    pub fn get_universe_instance(&self, position: &Vec3D) -> Option<usize> {
+        self.get_cell_index(position)
+            .and_then(|cell_index| self.universe_instances[cell_index])
+    }
+}
+
+/// Axial coordinates of the hexagonal cell containing a position relative to a cell center, found by rounding the cube coordinates.
+/// The lattice vectors are (pitch, 0) and (pitch / 2, pitch √3 / 2).
+fn get_axial_coordinates(relative_position: &Vec3D, pitch: f64) -> (i64, i64) {
+    let r = relative_position.y / (pitch * 3.0_f64.sqrt() / 2.0);
+    let q = relative_position.x / pitch - r / 2.0;
+    let s = -q - r;
+
+    let (mut rounded_q, mut rounded_r, rounded_s) = (q.round(), r.round(), s.round());
+    let (q_difference, r_difference, s_difference) = (
+        (rounded_q - q).abs(),
+        (rounded_r - r).abs(),
+        (rounded_s - s).abs(),
+    );
+
+    // The coordinate with the largest rounding error is recomputed from the other two.
+    if q_difference > r_difference && q_difference > s_difference {
+        rounded_q = -rounded_r - rounded_s;
+    } else if r_difference > s_difference {
+        rounded_r = -rounded_q - rounded_s;
+    }
+
+    (rounded_q as i64, rounded_r as i64)
+}
+
+/// Parts and nested lattices of one placed universe. The top level of the geometry is a universe as well.
+/// Universes with many parts get a grid, so that only the parts near the position are checked.
+#[derive(Debug, Clone, Default)]
+pub struct UniverseInstance {
+    pub part_indices: Vec<usize>,
+    pub lattice_indices: Vec<usize>,
+    pub part_grid: Option<PartGrid>,
+}
+
+/// Index of the placed universes and lattices, which limits the point lookup to the parts in the lattice cells containing the position.
+/// The parts of all universe instances are stored in the flat parts vector, so that each copy has its own feedback and tallies.
+#[derive(Debug, Clone)]
+pub struct LatticeIndex {
+    pub root: UniverseInstance,
+    pub universe_instances: Vec<UniverseInstance>,
+    pub lattices: Vec<Lattice>,
+}
+
+impl LatticeIndex {
+    /// Index without lattices, in which all parts are checked.
+    pub fn new(part_count: usize) -> Self {
+        Self {
+            root: UniverseInstance {
+                part_indices: (0..part_count).collect(),
+                lattice_indices: Vec::new(),
+                part_grid: None,
+            },
+            universe_instances: Vec::new(),
+            lattices: Vec::new(),
+        }
+    }
+
+    /// Builds the grids of the universes with enough parts to benefit from one. This has to be repeated if the parts are changed.
+    pub fn build_part_grids(&mut self, parts_vector: &[PartTypes]) {
+        for universe_instance in
+            std::iter::once(&mut self.root).chain(self.universe_instances.iter_mut())
+        {
+            universe_instance.part_grid = (universe_instance.part_indices.len()
+                >= MINIMUM_GRID_PART_COUNT)
+                .then(|| PartGrid::new(parts_vector, &universe_instance.part_indices));
+        }
+    }
+
+    /// Gets the index of the highest-order part containing the position. For equal orders, the part that comes first in the parts vector is selected, as in a plain scan.
+    pub fn get_part_index(&self, parts_vector: &[PartTypes], position: &Vec3D) -> Option<usize> {
+        let mut maximum_part: Option<(i32, usize)> = None;
+        self.visit_candidate_parts(&self.root, position, &mut |part_index| {
+            let part = &parts_vector[part_index];
+            let order = part.order();
+
+            let is_higher = maximum_part.is_none_or(|(maximum_order, maximum_part_index)| {
+                order > maximum_order || (order == maximum_order && part_index < maximum_part_index)
+            });
+            if is_higher && part.is_inside(position) {
+                maximum_part = Some((order, part_index));
+            }
+        });
+
+        maximum_part.map(|(_, part_index)| part_index)
+    }
+
+    /// Gets the indices of all parts containing the position, including those hidden by higher orders, sorted by part index.
+    pub fn get_containing_part_indices(
+        &self,
+        parts_vector: &[PartTypes],
+        position: &Vec3D,
+    ) -> Vec<usize> {
+        let mut containing_part_indices = Vec::new();
+        self.visit_candidate_parts(&self.root, position, &mut |part_index| {
+            if parts_vector[part_index].is_inside(position) {
+                containing_part_indices.push(part_index);
+            }
+        });
+        containing_part_indices.sort_unstable();
+
+        containing_part_indices
+    }
+
+    /// Calls the visitor for every part that can contain the position, in the universe and the lattice cells containing the position.
+    fn visit_candidate_parts(
+        &self,
+        universe_instance: &UniverseInstance,
+        position: &Vec3D,
+        visitor: &mut impl FnMut(usize),
+    ) {
+        let candidate_part_indices = match &universe_instance.part_grid {
+            Some(part_grid) => {
+                let (cell_part_indices, unbounded_part_indices) =
+                    part_grid.get_candidates(position);
+                [cell_part_indices, unbounded_part_indices]
+            }
+            None => [&universe_instance.part_indices[..], &[]],
+        };
+
+        for &part_index in candidate_part_indices.into_iter().flatten() {
+            visitor(part_index);
+        }
+
+        for &lattice_index in &universe_instance.lattice_indices {
+            if let Some(instance_index) =
+                self.lattices[lattice_index].get_universe_instance(position)
+            {
+                self.visit_candidate_parts(
+                    &self.universe_instances[instance_index],
+                    position,
+                    visitor,
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use crate::geometry::components::BoundingBox;
+use crate::geometry::parts::parts::PartTypes;
+use crate::utils::vectors::Vec3D;
+
+/// Number of grid cells per part that the grid resolution aims for.
+const GRID_CELLS_PER_PART: f64 = 8.0;
+
+/// Maximum number of grid cells along each axis, which limits the memory for large or flat geometries.
+const MAXIMUM_AXIS_CELL_COUNT: usize = 128;
+
+/// Below this number of parts, checking every part is about as fast as the grid lookup, so no grid is built.
+pub const MINIMUM_GRID_PART_COUNT: usize = 8;
+
+/// Uniform grid over the bounding boxes of a set of parts, which returns the candidate parts for a position.
+/// Every grid cell lists the parts whose bounding box overlaps it, in the order of the parts vector. Parts without a finite bounding box are candidates everywhere.
+/// The order-based selection is unchanged, as it only ever skips parts that cannot contain the position.
+#[derive(Debug, Clone)]
+pub struct PartGrid {
+    pub bounding_box: BoundingBox,
+    pub cell_counts: [usize; 3],
+    cell_size: [f64; 3],
+    cells: Vec<Vec<usize>>,
+    unbounded_part_indices: Vec<usize>,
+}
+
+impl PartGrid {
+    pub fn new(parts_vector: &[PartTypes], part_indices: &[usize]) -> Self {
+        let (bounded_part_indices, unbounded_part_indices): (Vec<usize>, Vec<usize>) = part_indices
+            .iter()
+            .partition(|&&part_index| parts_vector[part_index].bounding_box().is_bounded());
+
+        let bounding_box = bounded_part_indices
+            .iter()
+            .map(|&part_index| *parts_vector[part_index].bounding_box())
+            .reduce(|first, second| first.union(&second))
+            .unwrap_or(BoundingBox {
+                min: Vec3D::default(),
+                max: Vec3D::default(),
+            });
+
+        // Flat extents are widened a little, so that the cells stay close to cubes.
+        let extent = bounding_box.max.subtract(bounding_box.min).to_array();
+        let maximum_extent = extent.iter().cloned().fold(f64::MIN_POSITIVE, f64::max);
+        let extent = extent.map(|axis_extent| axis_extent.max(maximum_extent * 1e-3));
+
+        let target_cell_count = bounded_part_indices.len() as f64 * GRID_CELLS_PER_PART;
+        let cell_edge = (extent.iter().product::<f64>() / target_cell_count).cbrt();
+        let cell_counts = if bounded_part_indices.is_empty() {
+            [1; 3]
+        } else {
+            extent.map(|axis_extent| {
+                ((axis_extent / cell_edge).ceil() as usize).clamp(1, MAXIMUM_AXIS_CELL_COUNT)
+            })
+        };
+        let cell_size = [0, 1, 2].map(|axis| extent[axis] / cell_counts[axis] as f64);
+
+        let mut part_grid = Self {
+            bounding_box,
+            cell_counts,
+            cell_size,
+            cells: vec![Vec::new(); cell_counts.iter().product()],
+            unbounded_part_indices,
+        };
+
+        for &part_index in &bounded_part_indices {
+            let part_bounding_box = parts_vector[part_index].bounding_box();
+            let minimum_cell = part_grid.get_cell_coordinates(&part_bounding_box.min);
+            let maximum_cell = part_grid.get_cell_coordinates(&part_bounding_box.max);
+
+            for x in minimum_cell[0]..=maximum_cell[0] {
+                for y in minimum_cell[1]..=maximum_cell[1] {
+                    for z in minimum_cell[2]..=maximum_cell[2] {
+                        let cell_index = part_grid.get_cell_index([x, y, z]);
+                        part_grid.cells[cell_index].push(part_index);
+                    }
+                }
+            }
+        }
+
+        part_grid
+    }
+
+    /// Coordinates of the grid cell containing the position, clamped to the grid.
+    fn get_cell_coordinates(&self, position: &Vec3D) -> [usize; 3] {
+        let relative_position = position.subtract(self.bounding_box.min).to_array();
+
+        [0, 1, 2].map(|axis| {
+            ((relative_position[axis] / self.cell_size[axis])
+                .floor()
+                .max(0.0) as usize)
+                .min(self.cell_counts[axis] - 1)
+        })
+    }
+
+    fn get_cell_index(&self, cell_coordinates: [usize; 3]) -> usize {
+        (cell_coordinates[0] * self.cell_counts[1] + cell_coordinates[1]) * self.cell_counts[2]
+            + cell_coordinates[2]
+    }
+
+    /// Parts that can contain the position: those overlapping its grid cell, and those without a finite bounding box.
+    pub fn get_candidates(&self, position: &Vec3D) -> (&[usize], &[usize]) {
+        if !self.bounding_box.is_inside(position) {
+            return (&[], &self.unbounded_part_indices);
+        }
+
+        let cell_index = self.get_cell_index(self.get_cell_coordinates(position));
+
+        (&self.cells[cell_index], &self.unbounded_part_indices)
+    }
+}
@@ -1,4 +1,14 @@
+pub mod annular_cylinder;
+pub mod cell;
+pub mod cone;
 pub mod cuboid;
 pub mod cylinder;
+pub mod ellipsoid;
+pub mod hexagonal_prism;
+pub mod mesh;
 pub mod parts;
+pub mod plane;
+pub mod primitives;
+pub mod solid;
 pub mod sphere;
+pub mod torus;
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+use crate::geometry::components::BoundingBox;
+use crate::utils::vectors::Vec3D;
+
+/// Hollow cylinder, such as a cladding tube, between an inner and outer radius around an axis through the center.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnularCylinder {
+    pub center: Vec3D,
+    pub direction: Vec3D,
+    pub length: f64,
+    pub inner_radius: f64,
+    pub outer_radius: f64,
+}
+
+impl AnnularCylinder {
+    pub fn normalized(mut self) -> Self {
+        assert!(
+            self.direction.norm() > 0.0,
+            "The direction of an annular cylinder cannot be zero."
+        );
+        assert!(
+            self.length > 0.0 && 0.0 <= self.inner_radius && self.inner_radius < self.outer_radius,
+            "An annular cylinder needs a positive length and 0 <= inner radius < outer radius."
+        );
+        self.direction.to_unit_vec();
+
+        self
+    }
+
+    pub fn is_inside(&self, position: &Vec3D) -> bool {
+        let relative_position = position.subtract(self.center);
+        let parallel_component = relative_position.dot(self.direction);
+        let perpendicular_component_squared =
+            relative_position.norm_squared() - parallel_component.powi(2);
+
+        parallel_component.abs() <= self.length / 2.0
+            && perpendicular_component_squared <= self.outer_radius.powi(2)
+            && perpendicular_component_squared >= self.inner_radius.powi(2)
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox {
+        let end1 = self
+            .center
+            .add(self.direction.scalar_dot(self.length / 2.0));
+        let end2 = self
+            .center
+            .add(self.direction.scalar_dot(-self.length / 2.0));
+
+        BoundingBox {
+            min: end1.min(end2).scalar_add(-self.outer_radius),
+            max: end1.max(end2).scalar_add(self.outer_radius),
+        }
+    }
+}
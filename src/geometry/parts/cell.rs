@@ -0,0 +1,237 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::geometry::components::BoundingBox;
+use crate::geometry::components::PartComposition;
+use crate::geometry::parts::primitives::Primitive;
+use crate::geometry::transform::Transform;
+use crate::utils::vectors::Vec3D;
+
+/// Boolean combination of primitives. Union and intersection can have any number of operands.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Region {
+    Primitive(Primitive),
+    Union(Vec<Region>),
+    Intersection(Vec<Region>),
+    Complement(Box<Region>),
+    /// Region defined in a local frame, placed with a rotation and translation.
+    Transformed(Box<Region>, Transform),
+}
+
+impl Region {
+    pub fn is_inside(&self, position: &Vec3D) -> bool {
+        match self {
+            Region::Primitive(primitive) => primitive.is_inside(position),
+            Region::Union(regions) => regions.iter().any(|region| region.is_inside(position)),
+            Region::Intersection(regions) => {
+                regions.iter().all(|region| region.is_inside(position))
+            }
+            Region::Complement(region) => !region.is_inside(position),
+            Region::Transformed(region, transform) => {
+                region.is_inside(&transform.to_local(position))
+            }
+        }
+    }
+
+    /// Conservative bounding box of the region. A complement is always unbounded, as the box of its operand says nothing about what lies outside of it.
+    pub fn bounding_box(&self) -> BoundingBox {
+        match self {
+            Region::Primitive(primitive) => primitive.bounding_box(),
+            Region::Union(regions) => regions
+                .iter()
+                .map(Region::bounding_box)
+                .reduce(|first, second| first.union(&second))
+                .unwrap_or_else(BoundingBox::unbounded),
+            Region::Intersection(regions) => regions
+                .iter()
+                .map(Region::bounding_box)
+                .fold(BoundingBox::unbounded(), |first, second| {
+                    first.intersection(&second)
+                }),
+            Region::Complement(_) => BoundingBox::unbounded(),
+            Region::Transformed(region, transform) => {
+                transform.transform_bounding_box(&region.bounding_box())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut characters = expression.chars().peekable();
+
+    while let Some(&character) = characters.peek() {
+        match character {
+            '&' => tokens.push(Token::And),
+            '|' => tokens.push(Token::Or),
+            '~' => tokens.push(Token::Not),
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            character if character.is_whitespace() => {}
+            character if character.is_alphanumeric() || character == '_' || character == '-' => {
+                let mut name = String::new();
+                while let Some(&character) = characters.peek() {
+                    if !(character.is_alphanumeric() || character == '_' || character == '-') {
+                        break;
+                    }
+                    name.push(character);
+                    characters.next();
+                }
+                tokens.push(Token::Name(name));
+                continue;
+            }
+            character => return Err(format!("unexpected character '{}'", character)),
+        }
+        characters.next();
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent parser over the tokens. Complement binds strongest, then intersection, then union.
+struct RegionParser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    primitives: &'a HashMap<String, Region>,
+}
+
+impl RegionParser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn parse_union(&mut self) -> Result<Region, String> {
+        let mut regions = vec![self.parse_intersection()?];
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            regions.push(self.parse_intersection()?);
+        }
+
+        Ok(match regions.len() {
+            1 => regions.remove(0),
+            _ => Region::Union(regions),
+        })
+    }
+
+    fn parse_intersection(&mut self) -> Result<Region, String> {
+        let mut regions = vec![self.parse_complement()?];
+        while self.peek() == Some(&Token::And) {
+            self.position += 1;
+            regions.push(self.parse_complement()?);
+        }
+
+        Ok(match regions.len() {
+            1 => regions.remove(0),
+            _ => Region::Intersection(regions),
+        })
+    }
+
+    fn parse_complement(&mut self) -> Result<Region, String> {
+        if self.peek() == Some(&Token::Not) {
+            self.position += 1;
+            return Ok(Region::Complement(Box::new(self.parse_complement()?)));
+        }
+
+        self.parse_operand()
+    }
+
+    fn parse_operand(&mut self) -> Result<Region, String> {
+        let token = self.peek().cloned();
+        self.position += 1;
+
+        match token {
+            Some(Token::Open) => {
+                let region = self.parse_union()?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err("missing closing parenthesis".to_string());
+                }
+                self.position += 1;
+                Ok(region)
+            }
+            Some(Token::Name(name)) => self
+                .primitives
+                .get(&name)
+                .cloned()
+                .ok_or_else(|| format!("primitive {} is not defined", name)),
+            Some(token) => Err(format!("unexpected {:?}", token)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+/// Parses a boolean expression over named primitives into a region, e.g. ```outer & ~inner & ~(hole | slot)```.
+/// ```&``` is the intersection, ```|``` the union and ```~``` the complement. The named primitives can already be transformed regions.
+pub fn parse_region(expression: &str, primitives: &HashMap<String, Region>) -> Region {
+    let parse = || -> Result<Region, String> {
+        let mut parser = RegionParser {
+            tokens: tokenize(expression)?,
+            position: 0,
+            primitives,
+        };
+
+        let region = parser.parse_union()?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("unexpected {:?}", token));
+        }
+
+        Ok(region)
+    };
+
+    parse().unwrap_or_else(|error| panic!("Invalid region expression '{}': {}", expression, error))
+}
+
+/// Cell defined by a boolean region over primitives, filled with a material.
+/// Cells take part in the order-based selection like any other part, so they can be combined with the existing spheres, cuboids and cylinders.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cell {
+    pub center: Vec3D,
+    pub region: Region,
+    pub bounding_box: BoundingBox,
+    pub name: String,
+    pub material_name: String,
+    pub material_composition_vector: Vec<PartComposition>,
+    pub order: i32,
+}
+
+impl Cell {
+    pub fn new(
+        name: String,
+        region: Region,
+        material_name: String,
+        material_composition_vector: Vec<PartComposition>,
+        order: i32,
+    ) -> Self {
+        let bounding_box = region.bounding_box();
+
+        // Unbounded cells have no meaningful center, and are left out of the simulation range.
+        let center = if bounding_box.is_bounded() {
+            bounding_box.center()
+        } else {
+            Vec3D::default()
+        };
+
+        Self {
+            center,
+            region,
+            bounding_box,
+            name,
+            material_name,
+            material_composition_vector,
+            order,
+        }
+    }
+
+    pub fn is_inside(&self, neutron_position: &Vec3D) -> bool {
+        self.bounding_box.is_inside(neutron_position) && self.region.is_inside(neutron_position)
+    }
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+use crate::geometry::components::BoundingBox;
+use crate::utils::vectors::Vec3D;
+
+/// Truncated cone, running from the base along the direction over its length. The radius changes linearly from the base radius to the top radius.
+/// A top radius of zero gives a full cone.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cone {
+    pub base_center: Vec3D,
+    pub direction: Vec3D,
+    pub length: f64,
+    pub base_radius: f64,
+    pub top_radius: f64,
+}
+
+impl Cone {
+    pub fn normalized(mut self) -> Self {
+        assert!(
+            self.direction.norm() > 0.0,
+            "The direction of a cone cannot be zero."
+        );
+        assert!(
+            self.length > 0.0 && self.base_radius >= 0.0 && self.top_radius >= 0.0,
+            "A cone needs a positive length and non-negative radii."
+        );
+        self.direction.to_unit_vec();
+
+        self
+    }
+
+    pub fn is_inside(&self, position: &Vec3D) -> bool {
+        let relative_position = position.subtract(self.base_center);
+        let parallel_component = relative_position.dot(self.direction);
+
+        if parallel_component < 0.0 || parallel_component > self.length {
+            return false;
+        }
+
+        let radius = self.base_radius
+            + (self.top_radius - self.base_radius) * parallel_component / self.length;
+        let perpendicular_component_squared =
+            relative_position.norm_squared() - parallel_component.powi(2);
+
+        perpendicular_component_squared <= radius.powi(2)
+    }
+
+    /// Bounding box of a cylinder with the larger of both radii around the axis.
+    pub fn bounding_box(&self) -> BoundingBox {
+        let top_center = self.base_center.add(self.direction.scalar_dot(self.length));
+        let radius = self.base_radius.max(self.top_radius);
+
+        BoundingBox {
+            min: self.base_center.min(top_center).scalar_add(-radius),
+            max: self.base_center.max(top_center).scalar_add(radius),
+        }
+    }
+}
@@ -2,17 +2,19 @@ use serde::{Deserialize, Serialize};
 
 use crate::geometry::components::BoundingBox;
 use crate::geometry::components::PartComposition;
-use crate::materials::material_properties::MaterialNames;
+use crate::geometry::transform::Transform;
 use crate::utils::vectors::Vec3D;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Cuboid {
     pub center: Vec3D,
     pub bounding_box: BoundingBox,
     pub name: String,
-    pub material_name: MaterialNames,
+    pub material_name: String,
     pub material_composition_vector: Vec<PartComposition>,
     pub order: i32,
+    pub transform: Option<Transform>,
+    half_vector: Vec3D,
 }
 
 impl Cuboid {
@@ -21,7 +23,7 @@ impl Cuboid {
         width: f64,
         depth: f64,
         height: f64,
-        material_name: MaterialNames,
+        material_name: String,
         material_composition_vector: Vec<PartComposition>,
         order: i32,
     ) -> Self {
@@ -50,9 +52,21 @@ impl Cuboid {
             material_name,
             material_composition_vector,
             order,
+            transform: None,
+            half_vector,
         }
     }
 
+    /// Places the part with a rotation and translation. The dimensions stay those in the part's local frame, and the bounding box is enlarged to contain the rotated part.
+    pub fn with_transform(mut self, transform: Option<Transform>) -> Self {
+        if let Some(transform) = &transform {
+            self.bounding_box = transform.transform_bounding_box(&self.bounding_box);
+        }
+        self.transform = transform;
+
+        self
+    }
+
     pub fn is_inside_bounding_box(&self, neutron_position: &Vec3D) -> bool {
         if neutron_position.x < self.bounding_box.min.x
             || neutron_position.x > self.bounding_box.max.x
@@ -72,11 +86,20 @@ impl Cuboid {
         true
     }
 
+    /// Without a transform, the cuboid is its own bounding box. Otherwise, the position is checked against the half-dimensions in the cuboid's local frame.
     pub fn is_inside(&self, neutron_position: &Vec3D) -> bool {
         if !self.is_inside_bounding_box(neutron_position) {
             return false;
-        } else {
-            return true;
         }
+
+        let Some(transform) = &self.transform else {
+            return true;
+        };
+
+        let relative_position = transform.to_local(neutron_position).subtract(self.center);
+
+        relative_position.x.abs() <= self.half_vector.x
+            && relative_position.y.abs() <= self.half_vector.y
+            && relative_position.z.abs() <= self.half_vector.z
     }
 }
@@ -3,10 +3,10 @@ use serde::Serialize;
 
 use crate::geometry::components::BoundingBox;
 use crate::geometry::components::PartComposition;
-use crate::materials::material_properties::MaterialNames;
+use crate::geometry::transform::{to_local_frame, Transform};
 use crate::utils::vectors::Vec3D;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Cylinder {
     pub center: Vec3D,
     pub direction: Vec3D,
@@ -14,9 +14,10 @@ pub struct Cylinder {
     pub radius: f64,
     pub bounding_box: BoundingBox,
     pub name: String,
-    pub material_name: MaterialNames,
+    pub material_name: String,
     pub material_composition_vector: Vec<PartComposition>,
     pub order: i32,
+    pub transform: Option<Transform>,
     squared_radius: f64,
     half_length: f64,
 }
@@ -27,7 +28,7 @@ impl Cylinder {
         direction: Vec3D,
         length: f64,
         radius: f64,
-        material_name: MaterialNames,
+        material_name: String,
         material_composition_vector: Vec<PartComposition>,
         order: i32,
     ) -> Self {
@@ -56,11 +57,22 @@ impl Cylinder {
             material_name,
             material_composition_vector,
             order,
+            transform: None,
             squared_radius,
             half_length,
         }
     }
 
+    /// Places the part with a rotation and translation. The dimensions stay those in the part's local frame, and the bounding box is enlarged to contain the rotated part.
+    pub fn with_transform(mut self, transform: Option<Transform>) -> Self {
+        if let Some(transform) = &transform {
+            self.bounding_box = transform.transform_bounding_box(&self.bounding_box);
+        }
+        self.transform = transform;
+
+        self
+    }
+
     pub fn is_inside_bounding_box(&self, neutron_position: &Vec3D) -> bool {
         if neutron_position.x < self.bounding_box.min.x
             || neutron_position.x > self.bounding_box.max.x
@@ -85,13 +97,15 @@ impl Cylinder {
     /// Then, we calculate the parallel component to the unit vector defined for the cylinder's angle, with a dot product.
     /// From that, we use Pythagoras to determine the perpendicular component, which amounts to the radius.
     /// To speed up computation, we pre-compute the squared radius and halved length.
+    /// For a rotated or translated cylinder, the position is first moved into the cylinder's local frame.
     pub fn is_inside(&self, neutron_position: &Vec3D) -> bool {
         if !self.is_inside_bounding_box(neutron_position) {
             // debug!("Outside bounding box!");
             return false;
         }
 
-        let relative_position = neutron_position.subtract(self.center);
+        let local_position = to_local_frame(self.transform.as_ref(), neutron_position);
+        let relative_position = local_position.subtract(self.center);
         let parallel_component = relative_position.dot(self.direction);
         let perpendicular_component_squared =
             relative_position.norm_squared() - parallel_component.powi(2);
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+use crate::geometry::components::BoundingBox;
+use crate::utils::vectors::Vec3D;
+
+/// Ellipsoid with its semi-axes along the x-, y- and z-axis of its local frame. Other orientations are given through a rotation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ellipsoid {
+    pub center: Vec3D,
+    pub semi_axes: Vec3D,
+}
+
+impl Ellipsoid {
+    pub fn normalized(self) -> Self {
+        assert!(
+            self.semi_axes.x > 0.0 && self.semi_axes.y > 0.0 && self.semi_axes.z > 0.0,
+            "An ellipsoid needs positive semi-axes."
+        );
+
+        self
+    }
+
+    pub fn is_inside(&self, position: &Vec3D) -> bool {
+        let relative_position = position.subtract(self.center);
+
+        (relative_position.x / self.semi_axes.x).powi(2)
+            + (relative_position.y / self.semi_axes.y).powi(2)
+            + (relative_position.z / self.semi_axes.z).powi(2)
+            <= 1.0
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox {
+        BoundingBox {
+            min: self.center.subtract(self.semi_axes),
+            max: self.center.add(self.semi_axes),
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+use crate::geometry::components::BoundingBox;
+use crate::utils::vectors::Vec3D;
+
+/// Regular hexagonal prism along the z-axis of its local frame, with two of its flats facing the x-direction.
+/// Assemblies with their flats facing the y-direction are rotated by 30 degrees around the z-axis.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HexagonalPrism {
+    pub center: Vec3D,
+    pub flat_to_flat: f64,
+    pub height: f64,
+}
+
+impl HexagonalPrism {
+    pub fn normalized(self) -> Self {
+        assert!(
+            self.flat_to_flat > 0.0 && self.height > 0.0,
+            "A hexagonal prism needs a positive flat-to-flat distance and height."
+        );
+
+        self
+    }
+
+    /// The position has to be between each of the three pairs of flats, whose normals are 60 degrees apart.
+    pub fn is_inside(&self, position: &Vec3D) -> bool {
+        let relative_position = position.subtract(self.center);
+        let half_flat_to_flat = self.flat_to_flat / 2.0;
+        let sin_60 = 3.0_f64.sqrt() / 2.0;
+
+        relative_position.z.abs() <= self.height / 2.0
+            && relative_position.x.abs() <= half_flat_to_flat
+            && (0.5 * relative_position.x + sin_60 * relative_position.y).abs() <= half_flat_to_flat
+            && (0.5 * relative_position.x - sin_60 * relative_position.y).abs() <= half_flat_to_flat
+    }
+
+    /// The corners lie on the y-axis, at the flat-to-flat distance divided by √3.
+    pub fn bounding_box(&self) -> BoundingBox {
+        let half_vector = Vec3D {
+            x: self.flat_to_flat / 2.0,
+            y: self.flat_to_flat / 3.0_f64.sqrt(),
+            z: self.height / 2.0,
+        };
+
+        BoundingBox {
+            min: self.center.subtract(half_vector),
+            max: self.center.add(half_vector),
+        }
+    }
+}
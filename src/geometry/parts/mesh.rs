@@ -0,0 +1,431 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::geometry::components::BoundingBox;
+use crate::utils::vectors::Vec3D;
+
+/// Maximum number of grid cells along each axis of the triangle grid.
+const MAXIMUM_AXIS_CELL_COUNT: usize = 256;
+
+/// Loading in a mesh from a TOML: an STL (ASCII or binary) or OBJ file, with a scale for files that are not in m, e.g. 0.001 for CAD exports in mm.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeshTOML {
+    pub path: String,
+    #[serde(default = "get_default_scale")]
+    pub scale: f64,
+}
+
+fn get_default_scale() -> f64 {
+    1.0
+}
+
+/// Closed triangulated surface, as exported from CAD. The inside is found by counting the crossings of a ray along +z through the surface: an odd count is inside.
+/// Crossings are found through a grid over the triangles projected onto the x-y plane, so only the triangles above or below the position are checked.
+/// The mesh has to be watertight, with every edge shared by exactly two triangles. Rays through edges and vertices are counted exactly once, through a consistent tie-break.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "MeshTOML", into = "MeshTOML")]
+pub struct Mesh {
+    pub path: String,
+    pub scale: f64,
+    data: Arc<MeshData>,
+}
+
+/// Triangles and grid of a mesh, shared between the copies of a part in a lattice.
+#[derive(Debug)]
+struct MeshData {
+    triangle_count: usize,
+    bounding_box: BoundingBox,
+    projected_triangles: Vec<ProjectedTriangle>,
+    grid: TriangleGrid,
+}
+
+/// Triangle with its vertices ordered counterclockwise in the x-y plane, leaving out those that are vertical.
+#[derive(Debug)]
+struct ProjectedTriangle {
+    vertices: [Vec3D; 3],
+}
+
+/// Uniform grid in the x-y plane, with the triangles whose projected bounding box overlaps each cell.
+#[derive(Debug)]
+struct TriangleGrid {
+    min: [f64; 2],
+    cell_size: [f64; 2],
+    cell_counts: [usize; 2],
+    cells: Vec<Vec<usize>>,
+}
+
+impl TryFrom<MeshTOML> for Mesh {
+    type Error = String;
+
+    fn try_from(mesh_toml: MeshTOML) -> Result<Self, Self::Error> {
+        Mesh::load(Path::new(&mesh_toml.path), mesh_toml.scale)
+    }
+}
+
+impl From<Mesh> for MeshTOML {
+    fn from(mesh: Mesh) -> Self {
+        MeshTOML {
+            path: mesh.path,
+            scale: mesh.scale,
+        }
+    }
+}
+
+impl Mesh {
+    /// Loads a mesh from an STL or OBJ file, depending on its extension, and scales it into m.
+    pub fn load(path: &Path, scale: f64) -> Result<Self, String> {
+        if scale <= 0.0 {
+            return Err(format!(
+                "The scale of mesh {} has to be positive.",
+                path.display()
+            ));
+        }
+
+        let bytes = fs::read(path)
+            .map_err(|error| format!("Failed to read mesh {}: {}", path.display(), error))?;
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+
+        let triangles = match extension.as_deref() {
+            Some("stl") => read_stl(&bytes),
+            Some("obj") => read_obj(&String::from_utf8_lossy(&bytes)),
+            _ => Err("only STL and OBJ files are supported".to_string()),
+        }
+        .map_err(|error| format!("Failed to load mesh {}: {}", path.display(), error))?;
+
+        let triangles = triangles
+            .into_iter()
+            .map(|triangle| triangle.map(|vertex| vertex.scalar_dot(scale)))
+            .collect();
+
+        let mut mesh = Self::new(triangles)
+            .map_err(|error| format!("Invalid mesh {}: {}", path.display(), error))?;
+        mesh.path = path.to_string_lossy().to_string();
+        mesh.scale = scale;
+
+        Ok(mesh)
+    }
+
+    /// Creates a mesh from its triangles in m, after checking that it is watertight.
+    pub fn new(triangles: Vec<[Vec3D; 3]>) -> Result<Self, String> {
+        if triangles.is_empty() {
+            return Err("the mesh has no triangles".to_string());
+        }
+        check_watertight(&triangles)?;
+
+        let bounding_box = triangles
+            .iter()
+            .flatten()
+            .map(|vertex| BoundingBox {
+                min: *vertex,
+                max: *vertex,
+            })
+            .reduce(|first, second| first.union(&second))
+            .expect("The mesh has triangles.");
+
+        let projected_triangles: Vec<ProjectedTriangle> = triangles
+            .iter()
+            .filter_map(|&[first, second, third]| {
+                match get_edge_function(&first, &second, &third) {
+                    area if area > 0.0 => Some([first, second, third]),
+                    area if area < 0.0 => Some([first, third, second]),
+                    _ => None,
+                }
+            })
+            .map(|vertices| ProjectedTriangle { vertices })
+            .collect();
+
+        let grid = TriangleGrid::new(&bounding_box, &projected_triangles);
+
+        Ok(Self {
+            path: String::new(),
+            scale: 1.0,
+            data: Arc::new(MeshData {
+                triangle_count: triangles.len(),
+                bounding_box,
+                projected_triangles,
+                grid,
+            }),
+        })
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.data.triangle_count
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox {
+        self.data.bounding_box
+    }
+
+    /// Counts the triangles crossed by the ray from the position along +z.
+    pub fn is_inside(&self, position: &Vec3D) -> bool {
+        if !self.data.bounding_box.is_inside(position) {
+            return false;
+        }
+
+        let crossing_count = self
+            .data
+            .grid
+            .get_candidates(position)
+            .iter()
+            .filter(|&&triangle_index| {
+                self.data.projected_triangles[triangle_index].is_crossed_above(position)
+            })
+            .count();
+
+        crossing_count % 2 == 1
+    }
+}
+
+impl ProjectedTriangle {
+    /// Whether the vertical line through the position crosses the triangle above the position.
+    /// A position on an edge or vertex in the x-y plane only counts for the triangles for which that edge is a top or left edge, so that it is counted once for a closed surface.
+    fn is_crossed_above(&self, position: &Vec3D) -> bool {
+        let mut weights = [0.0; 3];
+
+        for (vertex, weight) in weights.iter_mut().enumerate() {
+            // The weight of each vertex is the edge function of the opposite edge.
+            let start = &self.vertices[(vertex + 1) % 3];
+            let end = &self.vertices[(vertex + 2) % 3];
+            let edge_function = get_edge_function(start, end, position);
+
+            if edge_function < 0.0 || (edge_function == 0.0 && !is_top_left_edge(start, end)) {
+                return false;
+            }
+            *weight = edge_function;
+        }
+
+        // The height of the crossing, interpolated with the barycentric weights.
+        let weight_sum: f64 = weights.iter().sum();
+        let crossing_height = (0..3)
+            .map(|vertex| weights[vertex] * self.vertices[vertex].z)
+            .sum::<f64>()
+            / weight_sum;
+
+        crossing_height > position.z
+    }
+}
+
+/// Twice the signed area of the triangle from the edge to the position in the x-y plane, positive if the position is to the left of the edge.
+/// The edge is evaluated from its lexicographically smallest end, so that both triangles sharing an edge get exactly opposite values.
+fn get_edge_function(start: &Vec3D, end: &Vec3D, position: &Vec3D) -> f64 {
+    let (first, second, sign) = if (start.x, start.y) <= (end.x, end.y) {
+        (start, end, 1.0)
+    } else {
+        (end, start, -1.0)
+    };
+
+    sign * ((second.x - first.x) * (position.y - first.y)
+        - (second.y - first.y) * (position.x - first.x))
+}
+
+/// For counterclockwise triangles, left edges point down and top edges point in the negative x direction.
+fn is_top_left_edge(start: &Vec3D, end: &Vec3D) -> bool {
+    end.y < start.y || (end.y == start.y && end.x < start.x)
+}
+
+impl TriangleGrid {
+    fn new(bounding_box: &BoundingBox, projected_triangles: &[ProjectedTriangle]) -> Self {
+        let min = [bounding_box.min.x, bounding_box.min.y];
+        let extent = [
+            (bounding_box.max.x - bounding_box.min.x).max(f64::MIN_POSITIVE),
+            (bounding_box.max.y - bounding_box.min.y).max(f64::MIN_POSITIVE),
+        ];
+
+        // About one triangle per cell for a surface spread over the plane.
+        let axis_cell_count = ((projected_triangles.len() as f64).sqrt().ceil() as usize)
+            .clamp(1, MAXIMUM_AXIS_CELL_COUNT);
+        let cell_counts = [axis_cell_count; 2];
+        let cell_size = [0, 1].map(|axis| extent[axis] / cell_counts[axis] as f64);
+
+        let mut grid = Self {
+            min,
+            cell_size,
+            cell_counts,
+            cells: vec![Vec::new(); cell_counts[0] * cell_counts[1]],
+        };
+
+        for (triangle_index, projected_triangle) in projected_triangles.iter().enumerate() {
+            let vertices = &projected_triangle.vertices;
+            let minimum_cell = grid.get_cell_coordinates(
+                vertices[0].x.min(vertices[1].x).min(vertices[2].x),
+                vertices[0].y.min(vertices[1].y).min(vertices[2].y),
+            );
+            let maximum_cell = grid.get_cell_coordinates(
+                vertices[0].x.max(vertices[1].x).max(vertices[2].x),
+                vertices[0].y.max(vertices[1].y).max(vertices[2].y),
+            );
+
+            for x in minimum_cell[0]..=maximum_cell[0] {
+                for y in minimum_cell[1]..=maximum_cell[1] {
+                    grid.cells[x * cell_counts[1] + y].push(triangle_index);
+                }
+            }
+        }
+
+        grid
+    }
+
+    /// Coordinates of the cell containing the position in the x-y plane, clamped to the grid.
+    fn get_cell_coordinates(&self, x: f64, y: f64) -> [usize; 2] {
+        let relative_position = [x - self.min[0], y - self.min[1]];
+
+        [0, 1].map(|axis| {
+            ((relative_position[axis] / self.cell_size[axis])
+                .floor()
+                .max(0.0) as usize)
+                .min(self.cell_counts[axis] - 1)
+        })
+    }
+
+    fn get_candidates(&self, position: &Vec3D) -> &[usize] {
+        let [x, y] = self.get_cell_coordinates(position.x, position.y);
+
+        &self.cells[x * self.cell_counts[1] + y]
+    }
+}
+
+/// Checks that every edge is shared by exactly two triangles, with the vertices matched by their exact coordinates.
+fn check_watertight(triangles: &[[Vec3D; 3]]) -> Result<(), String> {
+    // Adding zero turns -0.0 into 0.0, so that both match.
+    let get_key = |vertex: &Vec3D| {
+        [vertex.x, vertex.y, vertex.z].map(|coordinate| (coordinate + 0.0).to_bits())
+    };
+
+    let mut edge_counts: HashMap<([u64; 3], [u64; 3]), usize> = HashMap::new();
+    for triangle in triangles {
+        for edge in 0..3 {
+            let (start, end) = (get_key(&triangle[edge]), get_key(&triangle[(edge + 1) % 3]));
+            *edge_counts
+                .entry((start.min(end), start.max(end)))
+                .or_default() += 1;
+        }
+    }
+
+    let open_edge_count = edge_counts.values().filter(|&&count| count == 1).count();
+    let shared_edge_count = edge_counts.values().filter(|&&count| count > 2).count();
+    if open_edge_count > 0 || shared_edge_count > 0 {
+        return Err(format!(
+            "the mesh is not watertight, with {} open edges and {} edges shared by more than two triangles",
+            open_edge_count, shared_edge_count
+        ));
+    }
+
+    Ok(())
+}
+
+/// Reads a binary STL, recognized by its size, or an ASCII STL.
+fn read_stl(bytes: &[u8]) -> Result<Vec<[Vec3D; 3]>, String> {
+    // Binary STLs have an 80-byte header, the triangle count, and 50 bytes per triangle: the normal, the vertices and an attribute.
+    if bytes.len() >= 84 {
+        let triangle_count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]);
+        if bytes.len() == 84 + 50 * triangle_count as usize {
+            let read_float = |offset: usize| {
+                f32::from_le_bytes([
+                    bytes[offset],
+                    bytes[offset + 1],
+                    bytes[offset + 2],
+                    bytes[offset + 3],
+                ]) as f64
+            };
+
+            return Ok((0..triangle_count as usize)
+                .map(|triangle_index| {
+                    let offset = 84 + 50 * triangle_index + 12;
+                    [0, 1, 2].map(|vertex| Vec3D {
+                        x: read_float(offset + 12 * vertex),
+                        y: read_float(offset + 12 * vertex + 4),
+                        z: read_float(offset + 12 * vertex + 8),
+                    })
+                })
+                .collect());
+        }
+    }
+
+    let text = std::str::from_utf8(bytes)
+        .map_err(|_| "the STL is neither a valid binary nor an ASCII STL".to_string())?;
+    let vertices = text
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("vertex"))
+        .map(parse_vertex)
+        .collect::<Result<Vec<Vec3D>, String>>()?;
+
+    if vertices.len() % 3 != 0 {
+        return Err("the number of vertices is not a multiple of three".to_string());
+    }
+
+    Ok(vertices
+        .chunks(3)
+        .map(|vertices| [vertices[0], vertices[1], vertices[2]])
+        .collect())
+}
+
+/// Reads the vertices and faces of an OBJ. Faces with more than three vertices are split into a fan of triangles.
+fn read_obj(text: &str) -> Result<Vec<[Vec3D; 3]>, String> {
+    let mut vertices = Vec::new();
+    let mut triangles = Vec::new();
+
+    for line in text.lines() {
+        let line = line.trim();
+        if let Some(coordinates) = line.strip_prefix("v ") {
+            vertices.push(parse_vertex(coordinates)?);
+        } else if let Some(face) = line.strip_prefix("f ") {
+            // Vertex references can carry texture and normal indices, as in 1/2/3, and count back from the last vertex if negative.
+            let face_vertices =
+                face.split_whitespace()
+                    .map(|reference| {
+                        let index: i64 = reference
+                            .split('/')
+                            .next()
+                            .and_then(|index| index.parse().ok())
+                            .ok_or_else(|| format!("invalid face vertex {}", reference))?;
+                        let index = if index < 0 {
+                            vertices.len() as i64 + index
+                        } else {
+                            index - 1
+                        };
+
+                        vertices
+                            .get(usize::try_from(index).map_err(|_| {
+                                format!("face vertex {} is out of range", reference)
+                            })?)
+                            .copied()
+                            .ok_or_else(|| format!("face vertex {} is out of range", reference))
+                    })
+                    .collect::<Result<Vec<Vec3D>, String>>()?;
+
+            if face_vertices.len() < 3 {
+                return Err(format!("face with fewer than three vertices: {}", face));
+            }
+            for fan_index in 1..face_vertices.len() - 1 {
+                triangles.push([
+                    face_vertices[0],
+                    face_vertices[fan_index],
+                    face_vertices[fan_index + 1],
+                ]);
+            }
+        }
+    }
+
+    Ok(triangles)
+}
+
+fn parse_vertex(coordinates: &str) -> Result<Vec3D, String> {
+    let coordinates = coordinates
+        .split_whitespace()
+        .map(|coordinate| {
+            coordinate
+                .parse::<f64>()
+                .map_err(|_| format!("invalid coordinate {}", coordinate))
+        })
+        .collect::<Result<Vec<f64>, String>>()?;
+
+    match coordinates[..] {
+        [x, y, z, ..] => Ok(Vec3D { x, y, z }),
+        _ => Err("a vertex needs three coordinates".to_string()),
+    }
+}
@@ -1,12 +1,134 @@
 use serde::{Deserialize, Serialize};
 
+use crate::geometry::components::{BoundingBox, PartComposition};
+use crate::geometry::parts::cell::{Cell, Region};
 use crate::geometry::parts::cuboid::Cuboid;
 use crate::geometry::parts::cylinder::Cylinder;
+use crate::geometry::parts::solid::Solid;
 use crate::geometry::parts::sphere::Sphere;
+use crate::geometry::transform::{add_translation, Transform};
+use crate::utils::vectors::Vec3D;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PartTypes {
     Cylinder(Cylinder),
     Sphere(Sphere),
     Cuboid(Cuboid),
+    Cell(Cell),
+    Solid(Solid),
+}
+
+impl PartTypes {
+    /// Checks whether the position is inside the underlying part.
+    pub fn is_inside(&self, neutron_position: &Vec3D) -> bool {
+        match self {
+            PartTypes::Sphere(sphere) => sphere.is_inside(neutron_position),
+            PartTypes::Cylinder(cylinder) => cylinder.is_inside(neutron_position),
+            PartTypes::Cuboid(cuboid) => cuboid.is_inside(neutron_position),
+            PartTypes::Cell(cell) => cell.is_inside(neutron_position),
+            PartTypes::Solid(solid) => solid.is_inside(neutron_position),
+        }
+    }
+
+    /// Name of the part type, e.g. ```Cylinder```.
+    pub fn name(&self) -> &str {
+        match self {
+            PartTypes::Sphere(sphere) => &sphere.name,
+            PartTypes::Cylinder(cylinder) => &cylinder.name,
+            PartTypes::Cuboid(cuboid) => &cuboid.name,
+            PartTypes::Cell(cell) => &cell.name,
+            PartTypes::Solid(solid) => &solid.name,
+        }
+    }
+
+    /// Name of the material or compound the part is made of.
+    pub fn material_name(&self) -> &str {
+        match self {
+            PartTypes::Sphere(sphere) => &sphere.material_name,
+            PartTypes::Cylinder(cylinder) => &cylinder.material_name,
+            PartTypes::Cuboid(cuboid) => &cuboid.material_name,
+            PartTypes::Cell(cell) => &cell.material_name,
+            PartTypes::Solid(solid) => &solid.material_name,
+        }
+    }
+
+    /// Order of the part for constructive solid geometry.
+    pub fn order(&self) -> i32 {
+        match self {
+            PartTypes::Sphere(sphere) => sphere.order,
+            PartTypes::Cylinder(cylinder) => cylinder.order,
+            PartTypes::Cuboid(cuboid) => cuboid.order,
+            PartTypes::Cell(cell) => cell.order,
+            PartTypes::Solid(solid) => solid.order,
+        }
+    }
+
+    /// Bounding box of the part in the global frame, which is unbounded for half-spaces and unbounded cells.
+    pub fn bounding_box(&self) -> &BoundingBox {
+        match self {
+            PartTypes::Sphere(sphere) => &sphere.bounding_box,
+            PartTypes::Cylinder(cylinder) => &cylinder.bounding_box,
+            PartTypes::Cuboid(cuboid) => &cuboid.bounding_box,
+            PartTypes::Cell(cell) => &cell.bounding_box,
+            PartTypes::Solid(solid) => &solid.bounding_box,
+        }
+    }
+
+    /// Material composition of the part.
+    pub fn material_composition_vector(&self) -> &Vec<PartComposition> {
+        match self {
+            PartTypes::Sphere(sphere) => &sphere.material_composition_vector,
+            PartTypes::Cylinder(cylinder) => &cylinder.material_composition_vector,
+            PartTypes::Cuboid(cuboid) => &cuboid.material_composition_vector,
+            PartTypes::Cell(cell) => &cell.material_composition_vector,
+            PartTypes::Solid(solid) => &solid.material_composition_vector,
+        }
+    }
+
+    /// Mutable material composition of the part, used to resolve the material indices.
+    pub fn material_composition_vector_mut(&mut self) -> &mut Vec<PartComposition> {
+        match self {
+            PartTypes::Sphere(sphere) => &mut sphere.material_composition_vector,
+            PartTypes::Cylinder(cylinder) => &mut cylinder.material_composition_vector,
+            PartTypes::Cuboid(cuboid) => &mut cuboid.material_composition_vector,
+            PartTypes::Cell(cell) => &mut cell.material_composition_vector,
+            PartTypes::Solid(solid) => &mut solid.material_composition_vector,
+        }
+    }
+
+    /// Moves the part by a translation, used to place copies of a universe in the cells of a lattice.
+    pub fn translated(self, translation: Vec3D) -> Self {
+        match self {
+            PartTypes::Sphere(mut sphere) => {
+                sphere.bounding_box = sphere.bounding_box.translated(translation);
+                sphere.transform = Some(add_translation(sphere.transform, translation));
+                PartTypes::Sphere(sphere)
+            }
+            PartTypes::Cylinder(mut cylinder) => {
+                cylinder.bounding_box = cylinder.bounding_box.translated(translation);
+                cylinder.transform = Some(add_translation(cylinder.transform, translation));
+                PartTypes::Cylinder(cylinder)
+            }
+            PartTypes::Cuboid(mut cuboid) => {
+                cuboid.bounding_box = cuboid.bounding_box.translated(translation);
+                cuboid.transform = Some(add_translation(cuboid.transform, translation));
+                PartTypes::Cuboid(cuboid)
+            }
+            PartTypes::Solid(mut solid) => {
+                solid.bounding_box = solid.bounding_box.translated(translation);
+                solid.transform = Some(add_translation(solid.transform, translation));
+                PartTypes::Solid(solid)
+            }
+            // Cells have no transform of their own, so the region is wrapped instead.
+            PartTypes::Cell(mut cell) => {
+                cell.region = Region::Transformed(
+                    Box::new(cell.region),
+                    Transform::from_translation(translation),
+                );
+                cell.bounding_box = cell.bounding_box.translated(translation);
+                cell.center = cell.center.add(translation);
+                PartTypes::Cell(cell)
+            }
+        }
+    }
 }
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+use crate::geometry::components::BoundingBox;
+use crate::utils::vectors::Vec3D;
+
+/// Half-space of all positions with ```normal · position <= offset```, i.e. behind the plane as seen along the normal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Plane {
+    pub normal: Vec3D,
+    pub offset: f64,
+}
+
+impl Plane {
+    /// Normalizes the normal, scaling the offset along with it so that the half-space stays the same.
+    pub fn normalized(mut self) -> Self {
+        let norm = self.normal.norm();
+        assert!(norm > 0.0, "The normal of a plane cannot be zero.");
+        self.normal = self.normal.scalar_dot(1.0 / norm);
+        self.offset /= norm;
+
+        self
+    }
+
+    pub fn is_inside(&self, position: &Vec3D) -> bool {
+        position.dot(self.normal) <= self.offset
+    }
+
+    /// A half-space is unbounded, except along the normal if the normal is aligned with an axis. Intersecting six axis-aligned planes then gives a bounded box.
+    pub fn bounding_box(&self) -> BoundingBox {
+        let normal = self.normal.to_array();
+        let nonzero_axes: Vec<usize> = (0..3).filter(|&axis| normal[axis] != 0.0).collect();
+        let [axis] = nonzero_axes[..] else {
+            return BoundingBox::unbounded();
+        };
+
+        let mut min = [f64::NEG_INFINITY; 3];
+        let mut max = [f64::INFINITY; 3];
+        if normal[axis] > 0.0 {
+            max[axis] = self.offset / normal[axis];
+        } else {
+            min[axis] = self.offset / normal[axis];
+        }
+
+        BoundingBox {
+            min: Vec3D::from_array(min),
+            max: Vec3D::from_array(max),
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+
+use crate::geometry::components::BoundingBox;
+use crate::geometry::parts::annular_cylinder::AnnularCylinder;
+use crate::geometry::parts::cone::Cone;
+use crate::geometry::parts::ellipsoid::Ellipsoid;
+use crate::geometry::parts::hexagonal_prism::HexagonalPrism;
+use crate::geometry::parts::mesh::Mesh;
+use crate::geometry::parts::plane::Plane;
+use crate::geometry::parts::torus::Torus;
+use crate::utils::vectors::Vec3D;
+
+/// Geometric primitives without a material, from which CSG cells are built. Half-spaces are unbounded, the solids are closed.
+/// Meshes are loaded from their file when the primitive is read from the TOML.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Primitive {
+    Plane(Plane),
+    Sphere {
+        center: Vec3D,
+        radius: f64,
+    },
+    /// Cylinder around an axis through the center. Without a length, the cylinder is infinite.
+    Cylinder {
+        center: Vec3D,
+        direction: Vec3D,
+        radius: f64,
+        length: Option<f64>,
+    },
+    /// Cuboid aligned with the axes of its local frame, with the same dimensions as the ```Cuboid``` part.
+    Cuboid {
+        center: Vec3D,
+        width: f64,
+        depth: f64,
+        height: f64,
+    },
+    Cone(Cone),
+    Torus(Torus),
+    Ellipsoid(Ellipsoid),
+    HexagonalPrism(HexagonalPrism),
+    AnnularCylinder(AnnularCylinder),
+    Mesh(Mesh),
+}
+
+impl Primitive {
+    /// Normalizes the direction vectors, so that the inside checks can use them directly, and checks the dimensions.
+    pub fn normalized(self) -> Self {
+        match self {
+            Primitive::Cylinder {
+                center,
+                mut direction,
+                radius,
+                length,
+            } => {
+                assert!(
+                    direction.norm() > 0.0,
+                    "The direction of a cylinder cannot be zero."
+                );
+                direction.to_unit_vec();
+
+                Primitive::Cylinder {
+                    center,
+                    direction,
+                    radius,
+                    length,
+                }
+            }
+            Primitive::Plane(plane) => Primitive::Plane(plane.normalized()),
+            Primitive::Cone(cone) => Primitive::Cone(cone.normalized()),
+            Primitive::Torus(torus) => Primitive::Torus(torus.normalized()),
+            Primitive::Ellipsoid(ellipsoid) => Primitive::Ellipsoid(ellipsoid.normalized()),
+            Primitive::HexagonalPrism(hexagonal_prism) => {
+                Primitive::HexagonalPrism(hexagonal_prism.normalized())
+            }
+            Primitive::AnnularCylinder(annular_cylinder) => {
+                Primitive::AnnularCylinder(annular_cylinder.normalized())
+            }
+            Primitive::Sphere { .. } | Primitive::Cuboid { .. } | Primitive::Mesh(_) => self,
+        }
+    }
+
+    pub fn is_inside(&self, position: &Vec3D) -> bool {
+        match self {
+            Primitive::Plane(plane) => plane.is_inside(position),
+            Primitive::Cone(cone) => cone.is_inside(position),
+            Primitive::Torus(torus) => torus.is_inside(position),
+            Primitive::Ellipsoid(ellipsoid) => ellipsoid.is_inside(position),
+            Primitive::HexagonalPrism(hexagonal_prism) => hexagonal_prism.is_inside(position),
+            Primitive::AnnularCylinder(annular_cylinder) => annular_cylinder.is_inside(position),
+            Primitive::Mesh(mesh) => mesh.is_inside(position),
+            Primitive::Sphere { center, radius } => {
+                position.subtract(*center).norm_squared() <= radius.powi(2)
+            }
+            Primitive::Cylinder {
+                center,
+                direction,
+                radius,
+                length,
+            } => {
+                let relative_position = position.subtract(*center);
+                let parallel_component = relative_position.dot(*direction);
+                let perpendicular_component_squared =
+                    relative_position.norm_squared() - parallel_component.powi(2);
+
+                perpendicular_component_squared <= radius.powi(2)
+                    && length.is_none_or(|length| parallel_component.abs() <= length / 2.0)
+            }
+            Primitive::Cuboid { .. } => self.bounding_box().is_inside(position),
+        }
+    }
+
+    /// Conservative bounding box of the primitive, which is unbounded for half-spaces and infinite cylinders.
+    pub fn bounding_box(&self) -> BoundingBox {
+        match self {
+            Primitive::Plane(plane) => plane.bounding_box(),
+            Primitive::Cone(cone) => cone.bounding_box(),
+            Primitive::Torus(torus) => torus.bounding_box(),
+            Primitive::Ellipsoid(ellipsoid) => ellipsoid.bounding_box(),
+            Primitive::HexagonalPrism(hexagonal_prism) => hexagonal_prism.bounding_box(),
+            Primitive::AnnularCylinder(annular_cylinder) => annular_cylinder.bounding_box(),
+            Primitive::Mesh(mesh) => mesh.bounding_box(),
+            Primitive::Sphere { center, radius } => BoundingBox {
+                min: center.scalar_add(-radius),
+                max: center.scalar_add(*radius),
+            },
+            Primitive::Cylinder {
+                center,
+                direction,
+                radius,
+                length,
+            } => match length {
+                Some(length) => {
+                    let end1 = center.add(direction.scalar_dot(length / 2.0));
+                    let end2 = center.add(direction.scalar_dot(-length / 2.0));
+
+                    BoundingBox {
+                        min: end1.min(end2).scalar_add(-radius),
+                        max: end1.max(end2).scalar_add(*radius),
+                    }
+                }
+                None => BoundingBox::unbounded(),
+            },
+            Primitive::Cuboid {
+                center,
+                width,
+                depth,
+                height,
+            } => {
+                let half_vector = Vec3D {
+                    x: width / 2.0,
+                    y: depth / 2.0,
+                    z: height / 2.0,
+                };
+
+                BoundingBox {
+                    min: center.subtract(half_vector),
+                    max: center.add(half_vector),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+use crate::geometry::components::BoundingBox;
+use crate::geometry::components::PartComposition;
+use crate::geometry::parts::primitives::Primitive;
+use crate::geometry::transform::{to_local_frame, Transform};
+use crate::utils::vectors::Vec3D;
+
+/// Part made of a single primitive, used for the shapes without a dedicated part type: planes, cones, tori, ellipsoids, hexagonal prisms, annular cylinders and meshes.
+/// Half-spaces are unbounded, and are left out of the simulation range like a background with order -1.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Solid {
+    pub center: Vec3D,
+    pub primitive: Primitive,
+    pub bounding_box: BoundingBox,
+    pub name: String,
+    pub material_name: String,
+    pub material_composition_vector: Vec<PartComposition>,
+    pub order: i32,
+    pub transform: Option<Transform>,
+}
+
+impl Solid {
+    pub fn new(
+        primitive: Primitive,
+        material_name: String,
+        material_composition_vector: Vec<PartComposition>,
+        order: i32,
+    ) -> Self {
+        let primitive = primitive.normalized();
+        let bounding_box = primitive.bounding_box();
+        let center = if bounding_box.is_bounded() {
+            bounding_box.center()
+        } else {
+            Vec3D::default()
+        };
+
+        let name = match &primitive {
+            Primitive::Plane(_) => "Plane",
+            Primitive::Sphere { .. } => "Sphere",
+            Primitive::Cylinder { .. } => "Cylinder",
+            Primitive::Cuboid { .. } => "Cuboid",
+            Primitive::Cone(_) => "Cone",
+            Primitive::Torus(_) => "Torus",
+            Primitive::Ellipsoid(_) => "Ellipsoid",
+            Primitive::HexagonalPrism(_) => "HexagonalPrism",
+            Primitive::AnnularCylinder(_) => "AnnularCylinder",
+            Primitive::Mesh(_) => "Mesh",
+        }
+        .to_string();
+
+        Self {
+            center,
+            primitive,
+            bounding_box,
+            name,
+            material_name,
+            material_composition_vector,
+            order,
+            transform: None,
+        }
+    }
+
+    /// Places the part with a rotation and translation. The dimensions stay those in the part's local frame, and the bounding box is enlarged to contain the rotated part.
+    pub fn with_transform(mut self, transform: Option<Transform>) -> Self {
+        if let Some(transform) = &transform {
+            self.bounding_box = transform.transform_bounding_box(&self.bounding_box);
+        }
+        self.transform = transform;
+
+        self
+    }
+
+    pub fn is_inside(&self, neutron_position: &Vec3D) -> bool {
+        if !self.bounding_box.is_inside(neutron_position) {
+            return false;
+        }
+
+        self.primitive
+            .is_inside(&to_local_frame(self.transform.as_ref(), neutron_position))
+    }
+}
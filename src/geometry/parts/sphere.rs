@@ -1,26 +1,27 @@
 use crate::geometry::components::BoundingBox;
 use crate::geometry::components::PartComposition;
-use crate::materials::material_properties::MaterialNames;
+use crate::geometry::transform::{to_local_frame, Transform};
 use crate::utils::vectors::Vec3D;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Sphere {
     pub center: Vec3D,
     pub radius: f64,
     pub bounding_box: BoundingBox,
     pub name: String,
     pub squared_radius: f64,
-    pub material_name: MaterialNames,
+    pub material_name: String,
     pub material_composition_vector: Vec<PartComposition>,
     pub order: i32,
+    pub transform: Option<Transform>,
 }
 
 impl Sphere {
     pub fn new(
         center: Vec3D,
         radius: f64,
-        material_name: MaterialNames,
+        material_name: String,
         material_composition_vector: Vec<PartComposition>,
         order: i32,
     ) -> Self {
@@ -55,9 +56,20 @@ impl Sphere {
             material_name,
             material_composition_vector,
             order,
+            transform: None,
         }
     }
 
+    /// Places the part with a rotation and translation. The dimensions stay those in the part's local frame, and the bounding box is enlarged to contain the rotated part.
+    pub fn with_transform(mut self, transform: Option<Transform>) -> Self {
+        if let Some(transform) = &transform {
+            self.bounding_box = transform.transform_bounding_box(&self.bounding_box);
+        }
+        self.transform = transform;
+
+        self
+    }
+
     pub fn is_inside_bounding_box(&self, neutron_position: &Vec3D) -> bool {
         if neutron_position.x < self.bounding_box.min.x
             || neutron_position.x > self.bounding_box.max.x
@@ -82,7 +94,8 @@ impl Sphere {
             return false;
         }
 
-        let relative_radius_squared = neutron_position.subtract(self.center).norm_squared();
+        let local_position = to_local_frame(self.transform.as_ref(), neutron_position);
+        let relative_radius_squared = local_position.subtract(self.center).norm_squared();
 
         // debug!(
         //     "Relative position norm: {}",
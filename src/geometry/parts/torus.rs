@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+use crate::geometry::components::BoundingBox;
+use crate::utils::vectors::Vec3D;
+
+/// Torus around an axis through its center, with the major radius to the center of the tube and the minor radius of the tube itself.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Torus {
+    pub center: Vec3D,
+    pub direction: Vec3D,
+    pub major_radius: f64,
+    pub minor_radius: f64,
+}
+
+impl Torus {
+    pub fn normalized(mut self) -> Self {
+        assert!(
+            self.direction.norm() > 0.0,
+            "The direction of a torus cannot be zero."
+        );
+        assert!(
+            self.major_radius > 0.0 && self.minor_radius > 0.0,
+            "A torus needs positive radii."
+        );
+        self.direction.to_unit_vec();
+
+        self
+    }
+
+    /// The position is inside if its distance to the circle through the center of the tube is at most the minor radius.
+    pub fn is_inside(&self, position: &Vec3D) -> bool {
+        let relative_position = position.subtract(self.center);
+        let parallel_component = relative_position.dot(self.direction);
+        let perpendicular_component = (relative_position.norm_squared()
+            - parallel_component.powi(2))
+        .max(0.0)
+        .sqrt();
+
+        (perpendicular_component - self.major_radius).powi(2) + parallel_component.powi(2)
+            <= self.minor_radius.powi(2)
+    }
+
+    /// Cube around the sphere that contains the torus in any orientation.
+    pub fn bounding_box(&self) -> BoundingBox {
+        let outer_radius = self.major_radius + self.minor_radius;
+
+        BoundingBox {
+            min: self.center.scalar_add(-outer_radius),
+            max: self.center.scalar_add(outer_radius),
+        }
+    }
+}
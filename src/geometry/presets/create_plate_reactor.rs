@@ -1,32 +1,19 @@
 use crate::geometry::parts::cuboid::Cuboid;
 use crate::geometry::parts::parts::PartTypes;
-use crate::materials::material_properties::MaterialNames;
 use crate::utils::vectors::Vec3D;
 use log::debug;
 
 use crate::geometry::components::PartComposition;
 
 pub fn create_plate_reactor(plate_thickness: f64) -> Vec<PartTypes> {
-    let u235_composition = PartComposition {
-        material_name: MaterialNames::U235,
-        material_fraction: 0.94,
-    };
-    let u238_composition = PartComposition {
-        material_name: MaterialNames::U238,
-        material_fraction: 0.06,
-    };
+    let u235_composition = PartComposition::new("U235", 0.94);
+    let u238_composition = PartComposition::new("U238", 0.06);
 
     let uranium_fuel = vec![u238_composition, u235_composition];
 
-    let hydrogen_in_water = PartComposition {
-        material_name: MaterialNames::H1,
-        material_fraction: 2. / 3.,
-    };
+    let hydrogen_in_water = PartComposition::new("H1", 2. / 3.);
 
-    let oxygen_in_water = PartComposition {
-        material_name: MaterialNames::O16,
-        material_fraction: 1. / 3.,
-    };
+    let oxygen_in_water = PartComposition::new("O16", 1. / 3.);
 
     let water = vec![hydrogen_in_water, oxygen_in_water];
 
@@ -52,7 +39,7 @@ pub fn create_plate_reactor(plate_thickness: f64) -> Vec<PartTypes> {
         background_water_size,
         background_water_size,
         background_water_size,
-        MaterialNames::H1,
+        "H1".to_string(),
         water,
         -1,
     );
@@ -74,7 +61,7 @@ pub fn create_plate_reactor(plate_thickness: f64) -> Vec<PartTypes> {
             plate_thickness,
             plate_width,
             plate_height,
-            MaterialNames::U235,
+            "U235".to_string(),
             uranium_fuel.clone(),
             1,
         );
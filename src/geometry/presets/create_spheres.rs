@@ -1,9 +1,8 @@
 use crate::geometry::parts::parts::PartTypes;
 use crate::geometry::parts::sphere::Sphere;
-use crate::materials::material_properties::MaterialNames;
 use crate::utils::vectors::Vec3D;
 
-use crate::geometry::components::PartComposition;
+use crate::geometry::components::{FractionType, PartComposition};
 
 pub fn create_sphere(
     center: Vec3D,
@@ -14,7 +13,7 @@ pub fn create_sphere(
     let sphere = Sphere::new(
         center,
         radius,
-        MaterialNames::U235,
+        "U235".to_string(),
         material_composition_vector,
         order,
     );
@@ -29,14 +28,8 @@ pub fn create_default_sphere(radius: f64) -> Vec<PartTypes> {
     };
     let order: i32 = 1;
 
-    let u235_composition = PartComposition {
-        material_name: MaterialNames::U235,
-        material_fraction: 1.0,
-    };
-    // let u238_composition = PartComposition {
-    //     material_name: MaterialNames::U238,
-    //     material_fraction: 0.06,
-    // };
+    let u235_composition = PartComposition::new("U235", 1.0);
+    // let u238_composition = PartComposition::new("U238", 0.06);
 
     let material_composition_vector = vec![u235_composition];
 
@@ -55,20 +48,32 @@ pub fn create_reference_sphere() -> Vec<PartTypes> {
     let radius: f64 = 0.087037;
     let order: i32 = 1;
 
-    let u235_composition = PartComposition {
-        material_name: MaterialNames::U235,
-        material_fraction: 0.94,
-    };
-    let u238_composition = PartComposition {
-        material_name: MaterialNames::U238,
-        material_fraction: 0.06,
-    };
+    let u235_composition = PartComposition::new("U235", 0.94);
+    let u238_composition = PartComposition::new("U238", 0.06);
 
     let material_composition_vector = vec![u238_composition, u235_composition];
 
     return create_sphere(center, radius, material_composition_vector, order);
 }
 
+/// Equivalent to the Jezebel bare plutonium sphere.
+/// Based on the ICSBEP benchmark PU-MET-FAST-001: delta-phase plutonium at 15.61 g/cm3, with k = 1 at r = 0.063849.
+/// The 4.5% Pu-240, 0.3% Pu-241 and 1 wt-% gallium are approximated as Pu-239.
+pub fn create_jezebel_sphere() -> Vec<PartTypes> {
+    let center = Vec3D {
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+    };
+    let radius: f64 = 0.063849;
+    let order: i32 = 1;
+
+    let material_composition_vector =
+        PartComposition::from_fractions(15610.0, &[("Pu239", 1.0)], FractionType::Weight);
+
+    create_sphere(center, radius, material_composition_vector, order)
+}
+
 pub fn create_water_body() -> Vec<PartTypes> {
     let center = Vec3D {
         x: 0.0,
@@ -79,15 +84,9 @@ pub fn create_water_body() -> Vec<PartTypes> {
     let radius = 100.0;
     let order = 1;
 
-    let hydrogen_in_water = PartComposition {
-        material_name: MaterialNames::H1,
-        material_fraction: 2. / 3.,
-    };
+    let hydrogen_in_water = PartComposition::new("H1", 2. / 3.);
 
-    let oxygen_in_water = PartComposition {
-        material_name: MaterialNames::O16,
-        material_fraction: 1. / 3.,
-    };
+    let oxygen_in_water = PartComposition::new("O16", 1. / 3.);
 
     let material_composition_vector = vec![hydrogen_in_water, oxygen_in_water];
 
@@ -0,0 +1,209 @@
+use serde::{Deserialize, Serialize};
+
+use crate::geometry::components::BoundingBox;
+use crate::utils::vectors::Vec3D;
+
+/// Tolerance for checking that a rotation matrix is orthonormal.
+const ROTATION_TOLERANCE: f64 = 1e-6;
+
+/// Rotation of a part or primitive as given in the TOML, in one of three forms.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RotationTOML {
+    /// Rotations about the x-, y- and z-axis in degrees, applied in that order around the fixed axes.
+    EulerAngles { euler_angles: Vec3D },
+    /// Right-handed rotation by an angle in degrees around an axis, which does not have to be normalized.
+    AxisAngle { axis: Vec3D, angle: f64 },
+    /// Rotation matrix, row by row. It has to be orthonormal with a determinant of 1.
+    Matrix { matrix: [[f64; 3]; 3] },
+}
+
+impl RotationTOML {
+    pub fn get_rotation_matrix(&self) -> [[f64; 3]; 3] {
+        match self {
+            RotationTOML::EulerAngles { euler_angles } => {
+                let (sin_x, cos_x) = euler_angles.x.to_radians().sin_cos();
+                let (sin_y, cos_y) = euler_angles.y.to_radians().sin_cos();
+                let (sin_z, cos_z) = euler_angles.z.to_radians().sin_cos();
+
+                let rotation_x = [[1.0, 0.0, 0.0], [0.0, cos_x, -sin_x], [0.0, sin_x, cos_x]];
+                let rotation_y = [[cos_y, 0.0, sin_y], [0.0, 1.0, 0.0], [-sin_y, 0.0, cos_y]];
+                let rotation_z = [[cos_z, -sin_z, 0.0], [sin_z, cos_z, 0.0], [0.0, 0.0, 1.0]];
+
+                multiply_matrices(&rotation_z, &multiply_matrices(&rotation_y, &rotation_x))
+            }
+            RotationTOML::AxisAngle { axis, angle } => {
+                let norm = axis.norm();
+                assert!(norm > 0.0, "The rotation axis cannot be zero.");
+                let axis = axis.scalar_dot(1.0 / norm);
+                let (sin, cos) = angle.to_radians().sin_cos();
+                let one_minus_cos = 1.0 - cos;
+
+                // Rodrigues' rotation formula.
+                [
+                    [
+                        cos + axis.x * axis.x * one_minus_cos,
+                        axis.x * axis.y * one_minus_cos - axis.z * sin,
+                        axis.x * axis.z * one_minus_cos + axis.y * sin,
+                    ],
+                    [
+                        axis.y * axis.x * one_minus_cos + axis.z * sin,
+                        cos + axis.y * axis.y * one_minus_cos,
+                        axis.y * axis.z * one_minus_cos - axis.x * sin,
+                    ],
+                    [
+                        axis.z * axis.x * one_minus_cos - axis.y * sin,
+                        axis.z * axis.y * one_minus_cos + axis.x * sin,
+                        cos + axis.z * axis.z * one_minus_cos,
+                    ],
+                ]
+            }
+            RotationTOML::Matrix { matrix } => {
+                let product = multiply_matrices(&transpose(matrix), matrix);
+                let is_orthonormal = (0..3).all(|row| {
+                    (0..3).all(|column| {
+                        let identity = if row == column { 1.0 } else { 0.0 };
+                        (product[row][column] - identity).abs() < ROTATION_TOLERANCE
+                    })
+                });
+
+                assert!(
+                    is_orthonormal && (determinant(matrix) - 1.0).abs() < ROTATION_TOLERANCE,
+                    "The rotation matrix {:?} is not a proper rotation.",
+                    matrix
+                );
+
+                *matrix
+            }
+        }
+    }
+}
+
+fn multiply_matrices(first: &[[f64; 3]; 3], second: &[[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let mut product = [[0.0; 3]; 3];
+    for row in 0..3 {
+        for column in 0..3 {
+            product[row][column] = (0..3)
+                .map(|index| first[row][index] * second[index][column])
+                .sum();
+        }
+    }
+
+    product
+}
+
+fn transpose(matrix: &[[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let mut transposed = [[0.0; 3]; 3];
+    for row in 0..3 {
+        for column in 0..3 {
+            transposed[column][row] = matrix[row][column];
+        }
+    }
+
+    transposed
+}
+
+fn determinant(matrix: &[[f64; 3]; 3]) -> f64 {
+    matrix[0][0] * (matrix[1][1] * matrix[2][2] - matrix[1][2] * matrix[2][1])
+        - matrix[0][1] * (matrix[1][0] * matrix[2][2] - matrix[1][2] * matrix[2][0])
+        + matrix[0][2] * (matrix[1][0] * matrix[2][1] - matrix[1][1] * matrix[2][0])
+}
+
+fn apply_matrix(matrix: &[[f64; 3]; 3], vector: &Vec3D) -> Vec3D {
+    Vec3D {
+        x: matrix[0][0] * vector.x + matrix[0][1] * vector.y + matrix[0][2] * vector.z,
+        y: matrix[1][0] * vector.x + matrix[1][1] * vector.y + matrix[1][2] * vector.z,
+        z: matrix[2][0] * vector.x + matrix[2][1] * vector.y + matrix[2][2] * vector.z,
+    }
+}
+
+/// Rigid transform from the local frame a shape is defined in to the global frame: the shape is first rotated around the origin, then translated.
+/// Shapes are checked by moving the position back into the local frame, so their own checks stay unchanged.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Transform {
+    pub rotation: [[f64; 3]; 3],
+    pub translation: Vec3D,
+}
+
+impl Transform {
+    /// Creates the transform from the optional TOML entries, or returns None if neither is given, so that untransformed shapes skip the transform entirely.
+    pub fn from_toml(rotation: Option<&RotationTOML>, translation: Option<Vec3D>) -> Option<Self> {
+        if rotation.is_none() && translation.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            rotation: rotation.map_or(
+                [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+                RotationTOML::get_rotation_matrix,
+            ),
+            translation: translation.unwrap_or_default(),
+        })
+    }
+
+    /// Pure translation, e.g. of a universe to the center of a lattice cell.
+    pub fn from_translation(translation: Vec3D) -> Self {
+        Self {
+            rotation: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            translation,
+        }
+    }
+
+    pub fn to_global(&self, local_position: &Vec3D) -> Vec3D {
+        apply_matrix(&self.rotation, local_position).add(self.translation)
+    }
+
+    /// The inverse of a rotation is its transpose.
+    pub fn to_local(&self, global_position: &Vec3D) -> Vec3D {
+        apply_matrix(
+            &transpose(&self.rotation),
+            &global_position.subtract(self.translation),
+        )
+    }
+
+    /// Axis-aligned box in the global frame around the transformed corners of a local box. This is conservative, as it contains the whole rotated box.
+    pub fn transform_bounding_box(&self, local_bounding_box: &BoundingBox) -> BoundingBox {
+        if !local_bounding_box.is_bounded() {
+            return BoundingBox::unbounded();
+        }
+
+        let (min, max) = (local_bounding_box.min, local_bounding_box.max);
+        let global_corners: Vec<Vec3D> = (0..8)
+            .map(|corner_index| {
+                self.to_global(&Vec3D {
+                    x: if corner_index & 1 == 0 { min.x } else { max.x },
+                    y: if corner_index & 2 == 0 { min.y } else { max.y },
+                    z: if corner_index & 4 == 0 { min.z } else { max.z },
+                })
+            })
+            .collect();
+
+        BoundingBox {
+            min: global_corners
+                .iter()
+                .fold(global_corners[0], |min, corner| min.min(*corner)),
+            max: global_corners
+                .iter()
+                .fold(global_corners[0], |max, corner| max.max(*corner)),
+        }
+    }
+}
+
+/// Adds a translation after the optional transform of a shape, which places the already transformed shape elsewhere.
+pub fn add_translation(transform: Option<Transform>, translation: Vec3D) -> Transform {
+    match transform {
+        Some(transform) => Transform {
+            translation: transform.translation.add(translation),
+            ..transform
+        },
+        None => Transform::from_translation(translation),
+    }
+}
+
+/// Moves a position into the local frame of a shape, if the shape has a transform.
+pub fn to_local_frame(transform: Option<&Transform>, global_position: &Vec3D) -> Vec3D {
+    match transform {
+        Some(transform) => transform.to_local(global_position),
+        None => *global_position,
+    }
+}
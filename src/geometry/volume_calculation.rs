@@ -0,0 +1,176 @@
+use rand::rngs::SmallRng;
+use rand::Rng;
+
+use crate::geometry::components::{BoundingBox, AVOGADRO_CONSTANT};
+use crate::geometry::lattice::LatticeIndex;
+use crate::geometry::parts::parts::PartTypes;
+use crate::materials::material_data::MaterialData;
+use crate::utils::vectors::Vec3D;
+
+/// Volume in m3 and mass in kg, with their statistical uncertainties (one standard deviation).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VolumeEstimate {
+    pub volume: f64,
+    pub volume_uncertainty: f64,
+    pub mass: f64,
+    pub mass_uncertainty: f64,
+}
+
+/// Volumes and masses of the parts and materials as they are used in the simulation, i.e. after the order-based overlay.
+#[derive(Debug, Clone)]
+pub struct VolumeCalculation {
+    pub sampling_box: BoundingBox,
+    pub sample_count: usize,
+    /// Estimates per part, in the order of the parts vector.
+    pub part_estimates: Vec<VolumeEstimate>,
+    /// Estimates per material of the material library, by material index. The volume of a material is that of the parts containing it.
+    /// Void at index 0 gets the volume in the sampling box outside all parts.
+    pub material_estimates: Vec<VolumeEstimate>,
+}
+
+/// Mass density in kg/m3 of a material in a part, or of the whole part without a material index, from the resolved fractions of the library number densities.
+pub fn get_mass_density(
+    part: &PartTypes,
+    material_index: Option<usize>,
+    material_data_vector: &[MaterialData],
+) -> f64 {
+    // Number densities in 1/m3 and molar masses in g/mol.
+    part.material_composition_vector()
+        .iter()
+        .filter(|part_composition| {
+            material_index
+                .is_none_or(|material_index| part_composition.material_index == material_index)
+        })
+        .map(|part_composition| {
+            let material_data = &material_data_vector[part_composition.material_index];
+            part_composition.material_fraction
+                * material_data.number_density
+                * material_data.atomic_mass
+                / AVOGADRO_CONSTANT
+                * 1e-3
+        })
+        .sum()
+}
+
+/// Estimates the volume and mass of every part and material by sampling positions uniformly in the box, and selecting the part at each position as in the simulation.
+/// The compositions of the parts have to be resolved against the material library, as done when creating the ```Components```. Thermal expansion is not included.
+/// Parts that reach beyond the box are only counted inside it.
+pub fn calculate_volumes(
+    parts_vector: &[PartTypes],
+    lattice_index: &LatticeIndex,
+    material_data_vector: &[MaterialData],
+    sampling_box: &BoundingBox,
+    sample_count: usize,
+    rng: &mut SmallRng,
+) -> VolumeCalculation {
+    assert!(
+        sampling_box.is_bounded()
+            && sampling_box.min.x < sampling_box.max.x
+            && sampling_box.min.y < sampling_box.max.y
+            && sampling_box.min.z < sampling_box.max.z,
+        "The volume calculation needs a finite box with a positive volume."
+    );
+    assert!(sample_count > 0, "The volume calculation needs samples.");
+
+    let mut part_sample_counts = vec![0_usize; parts_vector.len()];
+    let mut void_sample_count = 0;
+
+    for _ in 0..sample_count {
+        let position = Vec3D {
+            x: rng.gen_range(sampling_box.min.x..sampling_box.max.x),
+            y: rng.gen_range(sampling_box.min.y..sampling_box.max.y),
+            z: rng.gen_range(sampling_box.min.z..sampling_box.max.z),
+        };
+
+        match lattice_index.get_part_index(parts_vector, &position) {
+            Some(part_index) => part_sample_counts[part_index] += 1,
+            None => void_sample_count += 1,
+        }
+    }
+
+    let extent = sampling_box.max.subtract(sampling_box.min);
+    let box_volume = extent.x * extent.y * extent.z;
+
+    // Every sample scores a value depending on the part it is in, e.g. one for the part whose volume is estimated, or the density of a material.
+    // The estimate is the box volume times the mean score, and its uncertainty follows from the variance of the scores.
+    let estimate = |part_scores: &dyn Fn(usize) -> f64, void_score: f64| {
+        let (mut score_sum, mut squared_score_sum) = (
+            void_sample_count as f64 * void_score,
+            void_sample_count as f64 * void_score.powi(2),
+        );
+        for (part_index, &part_sample_count) in part_sample_counts.iter().enumerate() {
+            let score = part_scores(part_index);
+            score_sum += part_sample_count as f64 * score;
+            squared_score_sum += part_sample_count as f64 * score.powi(2);
+        }
+
+        let mean = score_sum / sample_count as f64;
+        let variance = (squared_score_sum / sample_count as f64 - mean.powi(2)).max(0.0);
+
+        (
+            box_volume * mean,
+            box_volume * (variance / sample_count as f64).sqrt(),
+        )
+    };
+
+    let part_mass_densities: Vec<f64> = parts_vector
+        .iter()
+        .map(|part| get_mass_density(part, None, material_data_vector))
+        .collect();
+
+    let part_estimates = (0..parts_vector.len())
+        .map(|part_index| {
+            let (volume, volume_uncertainty) = estimate(
+                &|other_part_index| f64::from(u8::from(other_part_index == part_index)),
+                0.0,
+            );
+
+            VolumeEstimate {
+                volume,
+                volume_uncertainty,
+                mass: volume * part_mass_densities[part_index],
+                mass_uncertainty: volume_uncertainty * part_mass_densities[part_index],
+            }
+        })
+        .collect();
+
+    let material_estimates = (0..material_data_vector.len())
+        .map(|material_index| {
+            let contains_material = |part_index: usize| {
+                parts_vector[part_index]
+                    .material_composition_vector()
+                    .iter()
+                    .any(|part_composition| part_composition.material_index == material_index)
+            };
+
+            let (volume, volume_uncertainty) = estimate(
+                &|part_index| f64::from(u8::from(contains_material(part_index))),
+                f64::from(u8::from(material_index == 0)),
+            );
+            let (mass, mass_uncertainty) = estimate(
+                &|part_index| {
+                    get_mass_density(
+                        &parts_vector[part_index],
+                        Some(material_index),
+                        material_data_vector,
+                    )
+                },
+                0.0,
+            );
+
+            VolumeEstimate {
+                volume,
+                volume_uncertainty,
+                mass,
+                mass_uncertainty,
+            }
+        })
+        .collect();
+
+    VolumeCalculation {
+        sampling_box: *sampling_box,
+        sample_count,
+        part_estimates,
+        material_estimates,
+    }
+}
@@ -15,6 +15,14 @@ struct TemperatureData {
     maximum_temperature: f64,
 }
 
+/// Maximum temperature on the grid of a material with a melting point.
+#[derive(Debug, Clone)]
+pub struct MeltingMargin {
+    pub material_name: String,
+    pub melting_point: f64,
+    pub maximum_temperature: f64,
+}
+
 pub struct HeatDiffusion {
     simulation: Simulation,
     geometry: GeometryDiagnostics,
@@ -34,6 +42,7 @@ pub struct HeatDiffusion {
     relevant_tuples: Vec<(usize, usize, usize)>,
 
     material_index_array: Vec<usize>,
+    part_index_array: Vec<Option<usize>>,
     temperature_array: Vec<f64>,
     temperature_array_new: Vec<f64>,
 
@@ -1,7 +1,9 @@
 use crate::diagnostics::geometry_diagnostics::GeometryDiagnostics;
 use crate::heat_diffusion::SimulationDirections;
 use crate::simulation::aggregate_runs::standard_simulation::create_simulation;
+use crate::simulation::Simulation;
 use crate::utils::config_loading::load_config;
+use crate::utils::config_loading::Config;
 use crate::utils::config_loading::GridBinParametersTOML;
 use crate::utils::data_loading::load_fission_vector;
 use crate::utils::vectors::Vec3D;
@@ -22,6 +24,7 @@ use std::process::Command;
 use std::thread;
 
 use crate::heat_diffusion::HeatDiffusion;
+use crate::heat_diffusion::MeltingMargin;
 use crate::heat_diffusion::TemperatureData;
 
 impl SimulationDirections {
@@ -50,11 +53,18 @@ impl TemperatureData {
 impl HeatDiffusion {
     pub fn new() -> HeatDiffusion {
         let config = load_config(Path::new("config/simulation/default.toml"));
+        let simulation = create_simulation();
+
+        HeatDiffusion::from_simulation(config, simulation)
+    }
 
+    /// Creates the heat diffusion model for an existing simulation, whose geometry and material data are used for the grid.
+    pub fn from_simulation(config: Config, simulation: Simulation) -> HeatDiffusion {
         let heat_diffusion_bins: GridBinParametersTOML = config.heat_diffusion_bins;
         let geometry = GeometryDiagnostics::new(heat_diffusion_bins);
 
         let material_index_array = Vec::<usize>::new();
+        let part_index_array = Vec::<Option<usize>>::new();
 
         let fission_source_array = Vec::<f64>::new();
 
@@ -82,7 +92,6 @@ impl HeatDiffusion {
             .minimum_relevant_property_index;
 
         let simulation_directions = SimulationDirections::all_directions();
-        let simulation = create_simulation();
 
         // We have the source term here as J/bin over the entire simulation.
         // We need to convert this to J/m3/s to act as a source term in FVM, so we convert it to that, and apply a multiplier that scales the distribution.
@@ -101,6 +110,7 @@ impl HeatDiffusion {
             minimum_relevant_property_index,
             cell_volume,
             material_index_array,
+            part_index_array,
             temperature_array,
             temperature_array_new,
             source_term_constant,
@@ -135,6 +145,8 @@ impl HeatDiffusion {
                         .get_material_index(&mut self.simulation.rng, &neutron_position);
 
                     self.material_index_array[center_index] = current_material_index;
+                    self.part_index_array[center_index] =
+                        self.simulation.components.get_part_index(&neutron_position);
                 }
             }
         }
@@ -193,8 +205,13 @@ impl HeatDiffusion {
 
         // Checking whether we have an internal node or a boundary condition.
         if property_index_adjacent >= self.minimum_relevant_property_index {
-            let averaged_thermal_conductivity = (material_center.thermal_conductivity
-                + material_adjacent.thermal_conductivity)
+            // The conductivity of each cell is evaluated at its own temperature.
+            let averaged_thermal_conductivity = (material_center
+                .thermal_conductivity
+                .evaluate(temperature_center)
+                + material_adjacent
+                    .thermal_conductivity
+                    .evaluate(temperature_adjacent))
                 / 2.0;
 
             let heat_flux = averaged_thermal_conductivity
@@ -270,6 +287,11 @@ impl HeatDiffusion {
 
     pub fn load_fission_source(&self, fission_source_file_path: &Path) -> Vec<f64> {
         let fission_event_vector = load_fission_vector(fission_source_file_path);
+        self.bin_fission_source(&fission_event_vector)
+    }
+
+    /// Bins the fission events onto the heat diffusion grid, giving the energy released per bin (J).
+    pub fn bin_fission_source(&self, fission_event_vector: &[Vec3D]) -> Vec<f64> {
         let mut fission_source_array: Vec<f64> = self.create_grid_array();
 
         const ENERGY_PER_FISSION: f64 = 1.9341e+8; // eV
@@ -285,6 +307,24 @@ impl HeatDiffusion {
         fission_source_array
     }
 
+    /// Sets the source term from a binned fission source, normalized so that the total heat generation equals the specified power (W).
+    pub fn set_normalized_fission_source(
+        &mut self,
+        fission_source_array: Vec<f64>,
+        total_power: f64,
+    ) {
+        let total_source: f64 = fission_source_array.iter().sum();
+
+        self.source_term_constant = if total_source > 0.0 {
+            total_power / (total_source * self.cell_volume)
+        } else {
+            warn!("No fissions on the heat diffusion grid - the source term is set to zero.");
+            0.0
+        };
+
+        self.fission_source_array = fission_source_array;
+    }
+
     pub fn write_temperature_history(&self) {
         let dir_path = Path::new("results/heat_diffusion");
         let file_path = dir_path.join("temperature_data.csv");
@@ -322,9 +362,15 @@ impl HeatDiffusion {
             _ => (),
         };
 
+        self.initialize_grid();
+    }
+
+    /// Determines the materials and parts on the grid and sets the initial temperatures.
+    pub fn initialize_grid(&mut self) {
         self.simulation.components.update_cache_properties(1e6);
 
         self.material_index_array = self.create_grid_array();
+        self.part_index_array = self.create_grid_array();
         self.create_property_array();
 
         self.temperature_array = self.create_grid_array();
@@ -344,6 +390,7 @@ impl HeatDiffusion {
             .collect();
         self.temperature_array_new = self.temperature_array.clone();
 
+        self.relevant_tuples.clear();
         for x_bin in 1..self.geometry.length_count - 1 {
             for y_bin in 1..self.geometry.depth_count - 1 {
                 for z_bin in 1..self.geometry.height_count - 1 {
@@ -358,74 +405,203 @@ impl HeatDiffusion {
         }
     }
 
-    pub fn solve_fvm(&mut self) -> () {
-        self.initialize_fvm();
+    /// Calculates the new temperatures for a single explicit time step into ```temperature_array_new```.
+    /// Returns the mean and maximum temperature, and the maximum rate of temperature change (K/s).
+    fn advance_time_step(&mut self, time: f64, time_index: i64) -> (f64, f64, f64) {
+        let mut mean_temperature = 0.0;
+        let mut maximum_temperature = f64::MIN;
+        let mut maximum_temperature_rate: f64 = 0.0;
+
+        for (x_bin, y_bin, z_bin) in &self.relevant_tuples {
+            let center_index = self.geometry.bins_to_index(x_bin, y_bin, z_bin);
+            let center_material_index = self.material_index_array[center_index];
+
+            if center_material_index < self.minimum_relevant_property_index {
+                continue;
+            }
+
+            let center_material_data =
+                &self.simulation.components.material_data_vector[center_material_index];
+            let temperature_center = self.temperature_array[center_index];
+
+            // Some diagnostics.
+            mean_temperature += temperature_center;
+            if temperature_center > maximum_temperature {
+                maximum_temperature = temperature_center;
+            }
 
+            // It's a bit complicated to calculate CFL: we have prescribed heat fluxes, so it's possible that cells get a negative temperature.
+            // For now, this is an easy way to stop the code from running, rather than waiting and seeing it failed from the result files.
+            if temperature_center.is_nan() {
+                panic!(
+                    "Encountered NaN in temperature_center at t={} ({}/{}), halting simulation.",
+                    time, time_index, self.time_steps
+                );
+            }
+
+            let center_heat_flux: f64 = self
+                .simulation_directions
+                .iter()
+                .map(|direction: &SimulationDirections| {
+                    self.calculate_interface_flux(&direction, (x_bin, y_bin, z_bin))
+                })
+                .sum();
+
+            let source_term = self.source_term_constant * self.fission_source_array[center_index];
+
+            let inertial_term = center_material_data.density.evaluate(temperature_center)
+                * center_material_data
+                    .heat_capacity
+                    .evaluate(temperature_center)
+                * self.cell_volume;
+
+            #[allow(non_snake_case)]
+            let dT_dt = (center_heat_flux + source_term * self.cell_volume) / inertial_term;
+
+            maximum_temperature_rate = maximum_temperature_rate.max(dT_dt.abs());
+
+            self.temperature_array_new[center_index] = temperature_center + dT_dt * self.t_delta;
+        }
+
+        mean_temperature /= self.relevant_tuples.len() as f64;
+
+        (
+            mean_temperature,
+            maximum_temperature,
+            maximum_temperature_rate,
+        )
+    }
+
+    /// Advances the temperatures in time until the maximum rate of temperature change drops below the tolerance (K/s), or until ```t_end``` is reached.
+    /// Continues from the current temperature field, so repeated calls with a slightly changed source converge quickly.
+    /// Returns whether the steady state was reached.
+    pub fn solve_steady_state(&mut self, steady_state_tolerance: f64) -> bool {
         let mut time = 0.0;
         let mut time_index = 0;
 
-        let mut file_writing_handles: Vec<std::thread::JoinHandle<()>> = vec![];
+        while time < self.t_end {
+            let (_, _, maximum_temperature_rate) = self.advance_time_step(time, time_index);
+            std::mem::swap(&mut self.temperature_array, &mut self.temperature_array_new);
 
-        let pb = ProgressBar::new(self.time_steps);
-        pb.set_style(ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {pos}/{len} ({eta}) {percent}%")
-            .expect("Error in setting progress bar template")
-            );
+            if maximum_temperature_rate < steady_state_tolerance {
+                info!(
+                    "Heat diffusion reached steady state after {:.3} s ({} steps).",
+                    time, time_index
+                );
+                self.check_melting_points();
+                return true;
+            }
 
-        pb.set_message("Processing...");
+            time += self.t_delta;
+            time_index += 1;
+        }
 
-        while time < self.t_end {
-            let mut mean_temperature = 0.0;
-            let mut maximum_temperature = f64::MIN;
+        warn!(
+            "Heat diffusion did not reach steady state within {} s - using the final temperatures.",
+            self.t_end
+        );
+        self.check_melting_points();
+        false
+    }
 
-            for (x_bin, y_bin, z_bin) in &self.relevant_tuples {
-                let center_index = self.geometry.bins_to_index(x_bin, y_bin, z_bin);
-                let center_material_index = self.material_index_array[center_index];
+    /// Averages the temperature over the relevant cells of each part. Parts that are not resolved on the grid return None.
+    pub fn get_part_temperatures(&self) -> Vec<Option<f64>> {
+        let part_count = self.simulation.components.parts_vector.len();
+        let mut temperature_sums = vec![(0.0, 0_usize); part_count];
 
-                if center_material_index < self.minimum_relevant_property_index {
-                    continue;
-                }
+        for (x_bin, y_bin, z_bin) in &self.relevant_tuples {
+            let center_index = self.geometry.bins_to_index(x_bin, y_bin, z_bin);
 
-                let center_material_data =
-                    &self.simulation.components.material_data_vector[center_material_index];
-                let temperature_center = self.temperature_array[center_index];
+            if let Some(part_index) = self.part_index_array[center_index] {
+                temperature_sums[part_index].0 += self.temperature_array[center_index];
+                temperature_sums[part_index].1 += 1;
+            }
+        }
 
-                // Some diagnostics.
-                mean_temperature += temperature_center;
-                if temperature_center > maximum_temperature {
-                    maximum_temperature = temperature_center;
-                }
+        temperature_sums
+            .iter()
+            .map(|&(temperature_sum, cell_count)| {
+                (cell_count > 0).then(|| temperature_sum / cell_count as f64)
+            })
+            .collect()
+    }
 
-                // It's a bit complicated to calculate CFL: we have prescribed heat fluxes, so it's possible that cells get a negative temperature.
-                // For now, this is an easy way to stop the code from running, rather than waiting and seeing it failed from the result files.
-                if temperature_center.is_nan() {
-                    panic!(
-                        "Encountered NaN in temperature_center at t={} ({}/{}), halting simulation.",
-                        time, time_index, self.time_steps
-                    );
-                }
+    /// Returns the maximum temperature on the grid of every material that has a melting point in the library.
+    pub fn get_melting_margins(&self) -> Vec<MeltingMargin> {
+        let material_data_vector = &self.simulation.components.material_data_vector;
+        let mut maximum_temperatures = vec![f64::MIN; material_data_vector.len()];
 
-                let center_heat_flux: f64 = self
-                    .simulation_directions
-                    .iter()
-                    .map(|direction: &SimulationDirections| {
-                        self.calculate_interface_flux(&direction, (x_bin, y_bin, z_bin))
+        for (x_bin, y_bin, z_bin) in &self.relevant_tuples {
+            let center_index = self.geometry.bins_to_index(x_bin, y_bin, z_bin);
+            let material_index = self.material_index_array[center_index];
+            maximum_temperatures[material_index] =
+                maximum_temperatures[material_index].max(self.temperature_array[center_index]);
+        }
+
+        material_data_vector
+            .iter()
+            .zip(maximum_temperatures)
+            .filter(|&(_, maximum_temperature)| maximum_temperature > f64::MIN)
+            .filter_map(|(material_data, maximum_temperature)| {
+                material_data
+                    .melting_point
+                    .map(|melting_point| MeltingMargin {
+                        material_name: material_data.name.clone(),
+                        melting_point,
+                        maximum_temperature,
                     })
-                    .sum();
+            })
+            .collect()
+    }
 
-                let source_term =
-                    self.source_term_constant * self.fission_source_array[center_index];
+    /// Warns for every material whose maximum temperature exceeds its melting point.
+    fn check_melting_points(&self) {
+        for melting_margin in self.get_melting_margins() {
+            if melting_margin.maximum_temperature >= melting_margin.melting_point {
+                warn!(
+                    "{} reaches {:.1} K, above its melting point of {:.1} K.",
+                    melting_margin.material_name,
+                    melting_margin.maximum_temperature,
+                    melting_margin.melting_point
+                );
+            }
+        }
+    }
 
-                let inertial_term = center_material_data.density
-                    * center_material_data.heat_capacity
-                    * self.cell_volume;
+    /// Returns the peak temperature over all cells belonging to parts with fissionable materials.
+    pub fn get_peak_fuel_temperature(&self) -> f64 {
+        let components = &self.simulation.components;
 
-                #[allow(non_snake_case)]
-                let dT_dt = (center_heat_flux + source_term * self.cell_volume) / inertial_term;
+        self.relevant_tuples
+            .iter()
+            .map(|(x_bin, y_bin, z_bin)| self.geometry.bins_to_index(x_bin, y_bin, z_bin))
+            .filter(|&center_index| match self.part_index_array[center_index] {
+                Some(part_index) => components.is_part_fissionable(part_index),
+                None => false,
+            })
+            .map(|center_index| self.temperature_array[center_index])
+            .fold(f64::MIN, f64::max)
+    }
 
-                self.temperature_array_new[center_index] =
-                    temperature_center + dT_dt * self.t_delta;
-            }
+    pub fn solve_fvm(&mut self) -> () {
+        self.initialize_fvm();
+
+        let mut time = 0.0;
+        let mut time_index = 0;
+
+        let mut file_writing_handles: Vec<std::thread::JoinHandle<()>> = vec![];
+
+        let pb = ProgressBar::new(self.time_steps);
+        pb.set_style(ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {pos}/{len} ({eta}) {percent}%")
+            .expect("Error in setting progress bar template")
+            );
+
+        pb.set_message("Processing...");
+
+        while time < self.t_end {
+            let (mean_temperature, maximum_temperature, _) =
+                self.advance_time_step(time, time_index);
 
             if time_index % self.config.heat_diffusion_parameters.write_interval == 0
                 && self.config.heat_diffusion_parameters.save_files
@@ -455,8 +631,6 @@ impl HeatDiffusion {
             // Pure magic: it shaves 20s of the previously 60s simulation!
             std::mem::swap(&mut self.temperature_array, &mut self.temperature_array_new);
 
-            mean_temperature = mean_temperature / self.relevant_tuples.len() as f64;
-
             let temperature_data =
                 TemperatureData::new(time, mean_temperature, maximum_temperature);
 
@@ -468,6 +642,7 @@ impl HeatDiffusion {
         }
 
         self.write_temperature_history();
+        self.check_melting_points();
 
         for handle in file_writing_handles {
             handle.join().expect("Thread panicked");
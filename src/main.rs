@@ -1,5 +1,5 @@
 use env_logger::{Builder, Env};
-use log::info;
+use log::{error, info};
 use nuclear::simulation::aggregate_runs::standard_simulation::create_simulation;
 use std::path::Path;
 
@@ -8,6 +8,7 @@ use nuclear::diagnostics::plotting::plot_geometry;
 
 use nuclear::heat_diffusion::HeatDiffusion;
 use nuclear::simulation::aggregate_runs::aggregate_runs::parallel_runs;
+use nuclear::simulation::thermal_coupling::coupled_runs;
 use nuclear::utils::config_loading::load_config;
 
 fn main() {
@@ -16,7 +17,13 @@ fn main() {
 
     let config = load_config(Path::new("config/simulation/default.toml"));
 
-    if config.simulation_parameters.model_heat_diffusion {
+    if config.simulation_parameters.model_thermal_coupling {
+        info!("Running coupled neutronics-thermal simulation.");
+        if let Err(coupling_error) = coupled_runs(Path::new("config/simulation/default.toml")) {
+            error!("Coupled simulation failed: {}", coupling_error);
+            std::process::exit(1);
+        }
+    } else if config.simulation_parameters.model_heat_diffusion {
         info!("Simulating heat diffusion.");
         let mut heat_diffusion = HeatDiffusion::new();
         heat_diffusion.solve_fvm();
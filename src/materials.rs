@@ -1,2 +1,8 @@
+pub mod compounds;
+pub mod data_cache;
+pub mod data_inspection;
+pub mod data_validation;
+pub mod energy_grid;
 pub mod material_data;
+pub mod material_library;
 pub mod material_properties;
@@ -0,0 +1,233 @@
+use crate::geometry::components::{FractionType, PartComposition};
+use crate::materials::material_data::MaterialData;
+use crate::materials::material_library::get_material_index_by_name;
+
+use log::debug;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// Default location of the compound library.
+pub const DEFAULT_COMPOUND_LIBRARY_PATH: &str = "config/materials/compounds.toml";
+
+/// A named compound, defined once by its mass density in kg/m3 and composition, and referenced from the parts by name.
+/// Uranium can be added through ```uranium_fraction``` and ```enrichment``` (weight percent U-235), in which case it is split into U-235 and U-238.
+/// The uranium fraction is of the same type as the other fractions: atoms of uranium for atom fractions, mass of uranium for weight fractions.
+#[derive(Deserialize, Debug, Clone)]
+pub struct CompoundTOML {
+    pub name: String,
+    pub density: f64,
+    pub atom_fractions: Option<Vec<PartComposition>>,
+    pub weight_fractions: Option<Vec<PartComposition>>,
+    pub enrichment: Option<f64>,
+    pub uranium_fraction: Option<f64>,
+}
+
+/// All compounds defined in a compound TOML.
+#[derive(Deserialize, Debug)]
+struct CompoundLibraryTOML {
+    compounds: Vec<CompoundTOML>,
+}
+
+/// Uranium isotope of a compound entry whose fraction is given for all uranium atoms together, with the enrichment in weight percent U-235 to split them by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UraniumIsotope {
+    U235(f64),
+    U238(f64),
+}
+
+impl UraniumIsotope {
+    /// Name of the isotope in the material library.
+    pub fn material_name(&self) -> &'static str {
+        match self {
+            UraniumIsotope::U235(_) => "U235",
+            UraniumIsotope::U238(_) => "U238",
+        }
+    }
+
+    /// Share of the uranium atoms that belongs to this isotope, using the atomic masses in the material library.
+    pub fn atom_fraction(&self, material_data_vector: &[MaterialData]) -> f64 {
+        match *self {
+            UraniumIsotope::U235(enrichment) => {
+                get_uranium_atom_fractions(enrichment, material_data_vector).0
+            }
+            UraniumIsotope::U238(enrichment) => {
+                get_uranium_atom_fractions(enrichment, material_data_vector).1
+            }
+        }
+    }
+}
+
+/// Returns the atom fractions of U-235 and U-238 for uranium with the given enrichment in weight percent U-235, using the atomic masses in the material library.
+pub fn get_uranium_atom_fractions(
+    enrichment: f64,
+    material_data_vector: &[MaterialData],
+) -> (f64, f64) {
+    assert!(
+        (0.0..=100.0).contains(&enrichment),
+        "Enrichment has to be between 0 and 100 weight percent, not {}.",
+        enrichment
+    );
+
+    let atomic_mass = |material_name: &str| {
+        get_material_index_by_name(material_data_vector, material_name)
+            .map(|material_index| material_data_vector[material_index].atomic_mass)
+            .unwrap_or_else(|| {
+                panic!(
+                    "Material {} is not defined in the material library.",
+                    material_name
+                )
+            })
+    };
+    let u235_moles = enrichment / 100.0 / atomic_mass("U235");
+    let u238_moles = (1.0 - enrichment / 100.0) / atomic_mass("U238");
+
+    (
+        u235_moles / (u235_moles + u238_moles),
+        u238_moles / (u235_moles + u238_moles),
+    )
+}
+
+/// Creates the composition of uranium metal with the given mass density in kg/m3 and enrichment in weight percent U-235.
+pub fn get_enriched_uranium(density: f64, enrichment: f64) -> Vec<PartComposition> {
+    PartComposition::from_fractions(
+        density,
+        &[
+            ("U235", enrichment / 100.0),
+            ("U238", 1.0 - enrichment / 100.0),
+        ],
+        FractionType::Weight,
+    )
+}
+
+impl CompoundTOML {
+    /// Converts the compound into a part's composition vector, optionally overriding the density.
+    pub fn get_material_composition_vector(&self, density: Option<f64>) -> Vec<PartComposition> {
+        let (fractions, fraction_type) = match (&self.atom_fractions, &self.weight_fractions) {
+            (Some(atom_fractions), None) => (atom_fractions.clone(), FractionType::Atom),
+            (None, Some(weight_fractions)) => (weight_fractions.clone(), FractionType::Weight),
+            (None, None) => (Vec::new(), FractionType::Atom),
+            (Some(_), Some(_)) => panic!(
+                "Compound {} specifies both atom and weight fractions.",
+                self.name
+            ),
+        };
+
+        let mut material_fractions: Vec<(&str, f64)> = fractions
+            .iter()
+            .map(|part_composition| {
+                (
+                    part_composition.material_name.as_str(),
+                    part_composition.material_fraction,
+                )
+            })
+            .collect();
+
+        let uranium = match (self.uranium_fraction, self.enrichment) {
+            (Some(uranium_fraction), Some(enrichment)) => Some((uranium_fraction, enrichment)),
+            (None, None) => None,
+            _ => panic!(
+                "Compound {} needs both uranium_fraction and enrichment to add uranium.",
+                self.name
+            ),
+        };
+
+        if let (Some((uranium_fraction, enrichment)), FractionType::Weight) =
+            (uranium, fraction_type)
+        {
+            material_fractions.push(("U235", uranium_fraction * enrichment / 100.0));
+            material_fractions.push(("U238", uranium_fraction * (1.0 - enrichment / 100.0)));
+        }
+
+        let mut part_composition_vector = PartComposition::from_fractions(
+            density.unwrap_or(self.density),
+            &material_fractions,
+            fraction_type,
+        );
+
+        // For atom fractions, each isotope gets the full uranium fraction, which is only split once the atomic masses in the material library are known.
+        if let (Some((uranium_fraction, enrichment)), FractionType::Atom) = (uranium, fraction_type)
+        {
+            for uranium_isotope in [
+                UraniumIsotope::U235(enrichment),
+                UraniumIsotope::U238(enrichment),
+            ] {
+                part_composition_vector.push(PartComposition {
+                    fraction_type,
+                    mass_density: density.unwrap_or(self.density),
+                    uranium_isotope: Some(uranium_isotope),
+                    ..PartComposition::new(uranium_isotope.material_name(), uranium_fraction)
+                });
+            }
+        }
+
+        assert!(
+            !part_composition_vector.is_empty(),
+            "Compound {} does not contain any materials.",
+            self.name
+        );
+
+        part_composition_vector
+    }
+}
+
+/// Reads the compounds from a single TOML.
+fn read_compound_library_file(toml_path: &Path) -> Vec<CompoundTOML> {
+    let toml_str = fs::read_to_string(toml_path)
+        .unwrap_or_else(|_| panic!("Failed to read compound library: {:?}", toml_path));
+    let compound_library: CompoundLibraryTOML = toml::from_str(&toml_str).unwrap_or_else(|error| {
+        panic!(
+            "Failed to parse compound library {:?}: {}",
+            toml_path, error
+        )
+    });
+
+    compound_library.compounds
+}
+
+/// Loads the compound library from either a single TOML, or a directory of TOMLs (read in alphabetical order).
+/// The isotopes are only resolved against the material library once a part uses the compound.
+pub fn load_compound_library(library_path: &Path) -> Vec<CompoundTOML> {
+    let compounds: Vec<CompoundTOML> = if library_path.is_dir() {
+        let mut toml_paths: Vec<_> = fs::read_dir(library_path)
+            .expect("Failed to read compound library directory.")
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "toml")
+            })
+            .collect();
+        toml_paths.sort();
+
+        toml_paths
+            .iter()
+            .flat_map(|toml_path| read_compound_library_file(toml_path))
+            .collect()
+    } else {
+        read_compound_library_file(library_path)
+    };
+
+    for (index, compound) in compounds.iter().enumerate() {
+        assert!(
+            compounds[..index]
+                .iter()
+                .all(|previous_compound| previous_compound.name != compound.name),
+            "Compound {} is defined more than once in the compound library.",
+            compound.name
+        );
+    }
+
+    debug!("Loaded {} compounds.", compounds.len());
+
+    compounds
+}
+
+/// Returns the compound with the given name, or None if it is not defined.
+pub fn get_compound_by_name<'a>(
+    compounds: &'a [CompoundTOML],
+    compound_name: &str,
+) -> Option<&'a CompoundTOML> {
+    compounds
+        .iter()
+        .find(|compound| compound.name == compound_name)
+}
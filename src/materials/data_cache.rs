@@ -0,0 +1,182 @@
+use crate::materials::material_data::MaterialData;
+use crate::materials::material_library::{get_library_source_paths, load_material_library};
+
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::UNIX_EPOCH;
+
+/// Version of the cache layout. Has to be increased whenever ```MaterialData``` changes, so that existing caches are rebuilt.
+const CACHE_FORMAT_VERSION: u32 = 4;
+
+/// Size and modification time of a source file, used to detect changes without re-reading the file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SourceStamp {
+    pub path: PathBuf,
+    pub length: u64,
+    pub modified_nanoseconds: u128,
+}
+
+/// Written in front of the material data, so that an outdated cache is detected without decoding the data.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct CacheHeader {
+    format_version: u32,
+    crate_version: String,
+    source_stamps: Vec<SourceStamp>,
+}
+
+/// A material library loaded in this process, with the stamps of the sources it was built from.
+struct SharedLibrary {
+    library_path: PathBuf,
+    source_stamps: Vec<SourceStamp>,
+    material_data_vector: Arc<Vec<MaterialData>>,
+}
+
+/// Material libraries already loaded in this process, shared read-only between all simulations and threads.
+static SHARED_LIBRARIES: OnceLock<Mutex<Vec<SharedLibrary>>> = OnceLock::new();
+
+/// Returns the stamps of all files the library is built from, or None if any of them cannot be read.
+pub fn get_source_stamps(library_path: &Path) -> Option<Vec<SourceStamp>> {
+    get_library_source_paths(library_path)
+        .into_iter()
+        .map(|path| {
+            let metadata = fs::metadata(&path).ok()?;
+            let modified_nanoseconds = metadata
+                .modified()
+                .ok()?
+                .duration_since(UNIX_EPOCH)
+                .ok()?
+                .as_nanos();
+
+            Some(SourceStamp {
+                path,
+                length: metadata.len(),
+                modified_nanoseconds,
+            })
+        })
+        .collect()
+}
+
+/// Returns the cache file of a library within the cache directory. Each library path has its own file.
+pub fn get_cache_file_path(library_path: &Path, cache_directory: &Path) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    library_path.hash(&mut hasher);
+
+    cache_directory.join(format!("material_library_{:016x}.bin", hasher.finish()))
+}
+
+/// Reads the material data from the cache file, or returns None if it is missing, outdated or corrupt.
+fn read_cache(cache_file_path: &Path, source_stamps: &[SourceStamp]) -> Option<Vec<MaterialData>> {
+    let mut reader = BufReader::new(File::open(cache_file_path).ok()?);
+
+    let cache_header: CacheHeader = bincode::deserialize_from(&mut reader).ok()?;
+    if cache_header.format_version != CACHE_FORMAT_VERSION
+        || cache_header.crate_version != env!("CARGO_PKG_VERSION")
+        || cache_header.source_stamps != source_stamps
+    {
+        debug!("Nuclear data cache {:?} is outdated.", cache_file_path);
+        return None;
+    }
+
+    bincode::deserialize_from(&mut reader).ok()
+}
+
+/// Writes the material data to the cache file. A temporary file is renamed at the end, so that other processes never read a partial cache.
+fn write_cache(
+    cache_file_path: &Path,
+    source_stamps: &[SourceStamp],
+    material_data_vector: &[MaterialData],
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(cache_directory) = cache_file_path.parent() {
+        fs::create_dir_all(cache_directory)?;
+    }
+
+    let temporary_path = cache_file_path.with_extension(format!("bin.{}", std::process::id()));
+    let mut writer = BufWriter::new(File::create(&temporary_path)?);
+
+    let cache_header = CacheHeader {
+        format_version: CACHE_FORMAT_VERSION,
+        crate_version: env!("CARGO_PKG_VERSION").to_string(),
+        source_stamps: source_stamps.to_vec(),
+    };
+    bincode::serialize_into(&mut writer, &cache_header)?;
+    bincode::serialize_into(&mut writer, material_data_vector)?;
+    writer.flush()?;
+    drop(writer);
+
+    fs::rename(&temporary_path, cache_file_path)?;
+
+    Ok(())
+}
+
+/// Loads the material library from the binary cache in the cache directory, if it is still valid.
+/// Otherwise, the library is loaded from its sources and the cache is rewritten. Failing to write the cache only gives a warning.
+pub fn load_cached_material_library(
+    library_path: &Path,
+    cache_directory: &Path,
+) -> Vec<MaterialData> {
+    // Missing sources are reported by the regular loading.
+    let Some(source_stamps) = get_source_stamps(library_path) else {
+        return load_material_library(library_path);
+    };
+
+    let cache_file_path = get_cache_file_path(library_path, cache_directory);
+    if let Some(material_data_vector) = read_cache(&cache_file_path, &source_stamps) {
+        debug!("Loaded material library from cache {:?}.", cache_file_path);
+        return material_data_vector;
+    }
+
+    info!("Building nuclear data cache {:?}.", cache_file_path);
+    let material_data_vector = load_material_library(library_path);
+
+    if let Err(error) = write_cache(&cache_file_path, &source_stamps, &material_data_vector) {
+        warn!(
+            "Failed to write nuclear data cache {:?}: {}",
+            cache_file_path, error
+        );
+    }
+
+    material_data_vector
+}
+
+/// Returns the material library shared by all simulations in this process, loading it only if it was not loaded yet or its sources changed since.
+/// With a cache directory, the library is loaded through the binary cache, otherwise directly from its sources.
+pub fn load_shared_material_library(
+    library_path: &Path,
+    cache_directory: Option<&Path>,
+) -> Arc<Vec<MaterialData>> {
+    // The lock is held while loading, so that concurrent threads wait for a single load instead of each loading their own copy.
+    let mut shared_libraries = SHARED_LIBRARIES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+
+    let source_stamps = get_source_stamps(library_path);
+    if let Some(shared_library) = shared_libraries.iter().find(|shared_library| {
+        shared_library.library_path == library_path
+            && Some(&shared_library.source_stamps) == source_stamps.as_ref()
+    }) {
+        return Arc::clone(&shared_library.material_data_vector);
+    }
+
+    let material_data_vector = Arc::new(match cache_directory {
+        Some(cache_directory) => load_cached_material_library(library_path, cache_directory),
+        None => load_material_library(library_path),
+    });
+
+    if let Some(source_stamps) = source_stamps {
+        shared_libraries.retain(|shared_library| shared_library.library_path != library_path);
+        shared_libraries.push(SharedLibrary {
+            library_path: library_path.to_path_buf(),
+            source_stamps,
+            material_data_vector: Arc::clone(&material_data_vector),
+        });
+    }
+
+    material_data_vector
+}
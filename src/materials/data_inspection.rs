@@ -0,0 +1,144 @@
+use crate::geometry::components::{resolve_part_composition, PartComposition};
+use crate::materials::material_data::MaterialData;
+use crate::utils::data_handling::interpolate_table;
+
+use csv::Writer;
+use serde::Serialize;
+use std::path::Path;
+
+/// Name used for the rows that sum all materials of a composition.
+pub const TOTAL_ROW_NAME: &str = "total";
+
+/// Interpolated cross-sections of a single material in a composition at one energy.
+/// Microscopic cross-sections are in barn, macroscopic ones in 1/m at the material's number density within the composition.
+/// The rows summing the composition only have macroscopic cross-sections.
+#[derive(Debug, Serialize)]
+pub struct CrossSectionSample {
+    pub energy: f64,
+    pub material_name: String,
+    pub number_density: f64,
+    pub microscopic_fission: Option<f64>,
+    pub microscopic_scattering: Option<f64>,
+    pub microscopic_absorption: Option<f64>,
+    pub microscopic_total: Option<f64>,
+    pub nu_bar: Option<f64>,
+    pub macroscopic_fission: f64,
+    pub macroscopic_scattering: f64,
+    pub macroscopic_absorption: f64,
+    pub macroscopic_total: f64,
+}
+
+/// Creates a grid of energies in eV, logarithmically spaced between the minimum and maximum energy.
+pub fn get_log_energy_grid(energy_min: f64, energy_max: f64, point_count: usize) -> Vec<f64> {
+    assert!(
+        energy_min > 0.0 && energy_max > energy_min && point_count >= 2,
+        "The energy grid needs 0 < minimum < maximum and at least two points."
+    );
+
+    let log_step = (energy_max / energy_min).ln() / (point_count - 1) as f64;
+    (0..point_count)
+        .map(|index| energy_min * (log_step * index as f64).exp())
+        .collect()
+}
+
+/// Interpolates the cross-sections of every material in the composition at each energy, followed by the sum over the composition.
+/// The composition is resolved the same way as for a part, so a single material at its library number density is ```PartComposition::new(name, 1.0)```.
+pub fn inspect_composition(
+    material_data_vector: &[MaterialData],
+    part_composition_vector: &[PartComposition],
+    energies: &[f64],
+) -> Vec<CrossSectionSample> {
+    let mut part_composition_vector = part_composition_vector.to_vec();
+    resolve_part_composition(&mut part_composition_vector, material_data_vector);
+
+    let mut cross_section_samples = Vec::new();
+
+    for &energy in energies {
+        let mut total_sample = CrossSectionSample {
+            energy,
+            material_name: TOTAL_ROW_NAME.to_string(),
+            number_density: 0.0,
+            microscopic_fission: None,
+            microscopic_scattering: None,
+            microscopic_absorption: None,
+            microscopic_total: None,
+            nu_bar: None,
+            macroscopic_fission: 0.0,
+            macroscopic_scattering: 0.0,
+            macroscopic_absorption: 0.0,
+            macroscopic_total: 0.0,
+        };
+
+        for part_composition in part_composition_vector.iter() {
+            let material_data = &material_data_vector[part_composition.material_index];
+            let table_schemes = &material_data.table_schemes;
+            let number_density = material_data.number_density * part_composition.material_fraction;
+
+            let fission = interpolate_table(
+                &material_data.energy_fission_cross_sections,
+                &material_data.fission_cross_sections,
+                &table_schemes.fission,
+                energy,
+            );
+            let scattering = interpolate_table(
+                &material_data.energy_scattering_cross_sections,
+                &material_data.elastic_cross_sections,
+                &table_schemes.scattering,
+                energy,
+            );
+            let absorption = interpolate_table(
+                &material_data.energy_absorption_cross_sections,
+                &material_data.absorption_cross_sections,
+                &table_schemes.absorption,
+                energy,
+            );
+            let nu_bar = interpolate_table(
+                &material_data.energy_nu_bar,
+                &material_data.nu_bar,
+                &table_schemes.nu_bar,
+                energy,
+            );
+
+            let sample = CrossSectionSample {
+                energy,
+                material_name: material_data.name.clone(),
+                number_density,
+                microscopic_fission: Some(fission),
+                microscopic_scattering: Some(scattering),
+                microscopic_absorption: Some(absorption),
+                microscopic_total: Some(fission + scattering + absorption),
+                nu_bar: Some(nu_bar),
+                macroscopic_fission: fission * 1e-28 * number_density,
+                macroscopic_scattering: scattering * 1e-28 * number_density,
+                macroscopic_absorption: absorption * 1e-28 * number_density,
+                macroscopic_total: (fission + scattering + absorption) * 1e-28 * number_density,
+            };
+
+            total_sample.number_density += sample.number_density;
+            total_sample.macroscopic_fission += sample.macroscopic_fission;
+            total_sample.macroscopic_scattering += sample.macroscopic_scattering;
+            total_sample.macroscopic_absorption += sample.macroscopic_absorption;
+            total_sample.macroscopic_total += sample.macroscopic_total;
+
+            cross_section_samples.push(sample);
+        }
+
+        cross_section_samples.push(total_sample);
+    }
+
+    cross_section_samples
+}
+
+/// Writes the cross-section samples to a CSV, with one row per material and energy.
+pub fn write_cross_section_samples(cross_section_samples: &[CrossSectionSample], file_path: &Path) {
+    let mut writer = Writer::from_path(file_path)
+        .unwrap_or_else(|_| panic!("Failed to create cross-section file {:?}.", file_path));
+
+    for cross_section_sample in cross_section_samples {
+        writer
+            .serialize(cross_section_sample)
+            .expect("Failed to write cross-sections to CSV.");
+    }
+
+    writer.flush().expect("Failed to flush writer.");
+}
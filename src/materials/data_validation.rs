@@ -0,0 +1,400 @@
+use crate::materials::material_data::{MaterialData, ThermalProperty};
+use crate::materials::material_library::read_material_tomls;
+use crate::utils::data_handling::{InterpolationLaw, OutOfRange, TableScheme};
+
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+/// Energy range in eV that the tables are expected to cover: from sub-thermal up to the highest fission neutron energies.
+pub const COVERAGE_ENERGY_MIN: f64 = 1e-5;
+pub const COVERAGE_ENERGY_MAX: f64 = 2e7;
+
+/// Cross-sections above this value in barn are most likely not in barn. The largest known thermal cross-section, Xe-135, is ~ 2.6e6 barn.
+const MAXIMUM_CROSS_SECTION: f64 = 1e7;
+
+/// Watt parameter energies are in MeV, all other tables are in eV.
+const WATT_ENERGY_SCALE: f64 = 1e6;
+
+/// Errors break the interpolation, while warnings point at data that is likely incorrect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueSeverity {
+    Warning,
+    Error,
+}
+
+/// A single problem found in the data of a material.
+#[derive(Debug, Clone)]
+pub struct DataIssue {
+    pub material_name: String,
+    pub table_name: String,
+    pub severity: IssueSeverity,
+    pub message: String,
+}
+
+impl fmt::Display for DataIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            IssueSeverity::Warning => "warning",
+            IssueSeverity::Error => "error",
+        };
+        write!(
+            f,
+            "[{}] {} ({}): {}",
+            severity, self.material_name, self.table_name, self.message
+        )
+    }
+}
+
+/// What the values of a table represent, which determines the unit checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TableKind {
+    CrossSection,
+    NuBar,
+    WattA,
+    WattB,
+}
+
+/// Name, energies, values, scheme and kind of a material's table.
+type TableDefinition<'a> = (&'a str, &'a [f64], &'a [f64], &'a TableScheme, TableKind);
+
+/// Checks a single table: matching lengths, finite values, increasing energies, non-negative values, units and coverage.
+/// Tables with a single point mean the reaction does not occur, and are not checked further.
+fn validate_table(
+    energies: &[f64],
+    values: &[f64],
+    table_scheme: &TableScheme,
+    table_kind: TableKind,
+) -> Vec<(IssueSeverity, String)> {
+    let mut issues = Vec::new();
+
+    if energies.is_empty() || energies.len() != values.len() {
+        issues.push((
+            IssueSeverity::Error,
+            format!(
+                "The table has {} energies and {} values.",
+                energies.len(),
+                values.len()
+            ),
+        ));
+        return issues;
+    }
+
+    if energies.len() == 1 {
+        return issues;
+    }
+
+    if let Some(index) = (0..energies.len())
+        .find(|&index| !energies[index].is_finite() || !values[index].is_finite())
+    {
+        issues.push((
+            IssueSeverity::Error,
+            format!(
+                "Non-finite point at index {}: ({}, {}).",
+                index, energies[index], values[index]
+            ),
+        ));
+        return issues;
+    }
+
+    if let Some(index) = (1..energies.len()).find(|&index| energies[index] < energies[index - 1]) {
+        issues.push((
+            IssueSeverity::Error,
+            format!(
+                "Energies decrease at index {}: {:e} after {:e}. The binary search requires non-decreasing energies.",
+                index,
+                energies[index],
+                energies[index - 1]
+            ),
+        ));
+    }
+
+    // Repeated energies are allowed, as they represent discontinuities. Logarithmic laws fall back to lin-lin for non-positive energies.
+    let logarithmic_energies = matches!(
+        table_scheme.interpolation,
+        InterpolationLaw::LinLog | InterpolationLaw::LogLog
+    );
+    if logarithmic_energies && energies[0] <= 0.0 {
+        issues.push((
+            IssueSeverity::Warning,
+            format!(
+                "Non-positive energy {:e} with a logarithmic interpolation law, which falls back to lin-lin there.",
+                energies[0]
+            ),
+        ));
+    }
+
+    if let Some(index) = values.iter().position(|&value| value < 0.0) {
+        issues.push((
+            IssueSeverity::Error,
+            format!("Negative value {:e} at index {}.", values[index], index),
+        ));
+    }
+
+    let maximum_value = values.iter().cloned().fold(f64::MIN, f64::max);
+    let minimum_value = values.iter().cloned().fold(f64::MAX, f64::min);
+    let energy_scale = match table_kind {
+        TableKind::WattA | TableKind::WattB => WATT_ENERGY_SCALE,
+        _ => 1.0,
+    };
+    let energy_min = energies[0] * energy_scale;
+    let energy_max = energies[energies.len() - 1] * energy_scale;
+
+    // Unit sanity: the values have to be in the expected range for their unit.
+    let unit_issue = match table_kind {
+        TableKind::CrossSection if maximum_value > MAXIMUM_CROSS_SECTION => Some(format!(
+            "Cross-sections up to {:e}, which is implausible for barn.",
+            maximum_value
+        )),
+        TableKind::CrossSection if energy_max < 1e3 => Some(format!(
+            "Energies only reach {:e}; these have to be in eV, not MeV.",
+            energy_max
+        )),
+        TableKind::NuBar if maximum_value > 10.0 => {
+            Some(format!("ν̄ up to {}, which is implausible.", maximum_value))
+        }
+        TableKind::WattA | TableKind::WattB if energy_max > 1e3 * WATT_ENERGY_SCALE => {
+            Some(format!(
+                "Energies reach {:e} MeV; Watt parameter energies have to be in MeV, not eV.",
+                energy_max / WATT_ENERGY_SCALE
+            ))
+        }
+        TableKind::WattA
+            if !(0.1..=10.0).contains(&minimum_value) || !(0.1..=10.0).contains(&maximum_value) =>
+        {
+            Some(format!(
+                "Watt a from {} to {}, outside the plausible 0.1 to 10 MeV.",
+                minimum_value, maximum_value
+            ))
+        }
+        TableKind::WattB if maximum_value > 20.0 => Some(format!(
+            "Watt b up to {}, outside the plausible 0 to 20 1/MeV.",
+            maximum_value
+        )),
+        _ => None,
+    };
+    if let Some(unit_issue) = unit_issue {
+        issues.push((IssueSeverity::Warning, unit_issue));
+    }
+
+    // Small relative tolerances, to allow for rounding in the conversions between eV and MeV.
+    if energy_min > COVERAGE_ENERGY_MIN * (1.0 + 1e-6)
+        || energy_max < COVERAGE_ENERGY_MAX * (1.0 - 1e-6)
+    {
+        let out_of_range = match table_scheme.out_of_range {
+            OutOfRange::Clamp => "clamped to the end values",
+            OutOfRange::Extrapolate => "extrapolated",
+            OutOfRange::Zero => "zero",
+            OutOfRange::Error => "an error",
+        };
+        issues.push((
+            IssueSeverity::Warning,
+            format!(
+                "Covers {:e} to {:e} eV instead of {:e} to {:e} eV; outside, the table is {}.",
+                energy_min, energy_max, COVERAGE_ENERGY_MIN, COVERAGE_ENERGY_MAX, out_of_range
+            ),
+        ));
+    }
+
+    issues
+}
+
+/// Checks all tables of a loaded material.
+pub fn validate_material_data(material_data: &MaterialData) -> Vec<DataIssue> {
+    let table_schemes = &material_data.table_schemes;
+    let tables: [TableDefinition; 7] = [
+        (
+            "fission",
+            &material_data.energy_fission_cross_sections,
+            &material_data.fission_cross_sections,
+            &table_schemes.fission,
+            TableKind::CrossSection,
+        ),
+        (
+            "scattering",
+            &material_data.energy_scattering_cross_sections,
+            &material_data.elastic_cross_sections,
+            &table_schemes.scattering,
+            TableKind::CrossSection,
+        ),
+        (
+            "inelastic",
+            &material_data.energy_inelastic_cross_sections,
+            &material_data.inelastic_cross_sections,
+            &table_schemes.inelastic,
+            TableKind::CrossSection,
+        ),
+        (
+            "absorption",
+            &material_data.energy_absorption_cross_sections,
+            &material_data.absorption_cross_sections,
+            &table_schemes.absorption,
+            TableKind::CrossSection,
+        ),
+        (
+            "nu_bar",
+            &material_data.energy_nu_bar,
+            &material_data.nu_bar,
+            &table_schemes.nu_bar,
+            TableKind::NuBar,
+        ),
+        (
+            "watt_a",
+            &material_data.energy_watt_parameters,
+            &material_data.watt_parameters_a,
+            &table_schemes.watt_parameters,
+            TableKind::WattA,
+        ),
+        (
+            "watt_b",
+            &material_data.energy_watt_parameters,
+            &material_data.watt_parameters_b,
+            &table_schemes.watt_parameters,
+            TableKind::WattB,
+        ),
+    ];
+
+    let mut issues: Vec<DataIssue> = tables
+        .into_iter()
+        .flat_map(|(table_name, energies, values, table_scheme, table_kind)| {
+            validate_table(energies, values, table_scheme, table_kind)
+                .into_iter()
+                .map(move |(severity, message)| DataIssue {
+                    material_name: material_data.name.clone(),
+                    table_name: table_name.to_string(),
+                    severity,
+                    message,
+                })
+        })
+        .collect();
+
+    if material_data.fissionable && material_data.fission_cross_sections.len() == 1 {
+        issues.push(DataIssue {
+            material_name: material_data.name.clone(),
+            table_name: "fission".to_string(),
+            severity: IssueSeverity::Warning,
+            message: "The material is fissionable, but has no fission cross-sections.".to_string(),
+        });
+    }
+
+    for (property_name, thermal_property) in [
+        ("thermal_conductivity", &material_data.thermal_conductivity),
+        ("density", &material_data.density),
+        ("heat_capacity", &material_data.heat_capacity),
+    ] {
+        issues.extend(validate_thermal_property(thermal_property).into_iter().map(
+            |(severity, message)| DataIssue {
+                material_name: material_data.name.clone(),
+                table_name: property_name.to_string(),
+                severity,
+                message,
+            },
+        ));
+    }
+
+    issues
+}
+
+/// Checks a thermophysical property: tables need increasing temperatures, and the values have to be non-negative.
+fn validate_thermal_property(thermal_property: &ThermalProperty) -> Vec<(IssueSeverity, String)> {
+    let (temperatures, values): (Vec<f64>, Vec<f64>) = match thermal_property {
+        ThermalProperty::Constant(value) => (vec![0.0], vec![*value]),
+        ThermalProperty::Table {
+            temperatures,
+            values,
+        } => {
+            if let Some(index) = (1..temperatures.len())
+                .find(|&index| temperatures[index] <= temperatures[index - 1])
+            {
+                return vec![(
+                    IssueSeverity::Error,
+                    format!(
+                        "Temperatures do not increase at index {}: {} after {}.",
+                        index,
+                        temperatures[index],
+                        temperatures[index - 1]
+                    ),
+                )];
+            }
+            (temperatures.clone(), values.clone())
+        }
+        // Correlations are checked at the ends of their range of validity, limited to 1 to 5000 K.
+        ThermalProperty::Polynomial {
+            minimum_temperature,
+            maximum_temperature,
+            ..
+        } => {
+            let temperatures = vec![
+                minimum_temperature.max(1.0),
+                maximum_temperature.min(5000.0),
+            ];
+            let values = temperatures
+                .iter()
+                .map(|&temperature| thermal_property.evaluate(temperature))
+                .collect();
+            (temperatures, values)
+        }
+    };
+
+    match values
+        .iter()
+        .position(|&value| !value.is_finite() || value < 0.0)
+    {
+        Some(index) => vec![(
+            IssueSeverity::Error,
+            format!(
+                "Invalid value {} at {} K.",
+                values[index], temperatures[index]
+            ),
+        )],
+        None => Vec::new(),
+    }
+}
+
+/// Returns the message of a caught panic.
+fn get_panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .unwrap_or_else(|| "Unknown error.".to_string()),
+    }
+}
+
+/// Checks every material in the library: all data files have to exist and be readable, after which their tables are checked.
+/// Materials that fail to load are reported as an error instead of aborting, so that all problems are found in a single pass.
+pub fn validate_material_library(library_path: &Path) -> Vec<DataIssue> {
+    let mut issues = Vec::new();
+
+    for material_toml in read_material_tomls(library_path) {
+        let material_name = material_toml.name.clone();
+        let missing_paths: Vec<_> = material_toml
+            .get_data_paths()
+            .into_iter()
+            .filter(|data_path| !data_path.is_file())
+            .collect();
+
+        if !missing_paths.is_empty() {
+            issues.extend(missing_paths.into_iter().map(|data_path| DataIssue {
+                material_name: material_name.clone(),
+                table_name: "files".to_string(),
+                severity: IssueSeverity::Error,
+                message: format!("Missing data file {:?}.", data_path),
+            }));
+            continue;
+        }
+
+        match panic::catch_unwind(AssertUnwindSafe(|| MaterialData::from_toml(material_toml))) {
+            Ok(material_data) => issues.extend(validate_material_data(&material_data)),
+            Err(payload) => issues.push(DataIssue {
+                material_name,
+                table_name: "files".to_string(),
+                severity: IssueSeverity::Error,
+                message: format!("Failed to load: {}", get_panic_message(payload)),
+            }),
+        }
+    }
+
+    issues
+}
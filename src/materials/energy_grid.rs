@@ -0,0 +1,197 @@
+use crate::materials::material_data::MaterialData;
+use crate::utils::data_handling::{evaluate_table, TableScheme};
+
+use log::debug;
+
+/// Number of logarithmically spaced bins spanning all tabulated energies.
+pub const DEFAULT_ENERGY_BIN_COUNT: usize = 8192;
+
+/// Hashed logarithmic energy grid, shared by all materials.
+/// The bin of an energy is computed once, after which every table only has to search the few points within that bin instead of running a full binary search.
+#[derive(Debug, Clone)]
+pub struct EnergyGrid {
+    pub log_energy_min: f64,
+    pub log_energy_max: f64,
+    pub bin_count: usize,
+    inverse_bin_width: f64,
+}
+
+/// Position of a neutron energy on the ```EnergyGrid```, computed once per energy change.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GridPosition {
+    pub energy: f64,
+    pub bin: usize,
+}
+
+/// For each bin, the index of the last table point at or below the bin's lower edge.
+/// Has ```bin_count + 1``` entries, with the last being the final table index.
+#[derive(Debug, Clone, Default)]
+pub struct HashedTableIndices {
+    bin_start_indices: Vec<u32>,
+}
+
+/// Hashed indices for all the tables of a single material.
+#[derive(Debug, Clone, Default)]
+pub struct MaterialGridIndices {
+    pub fission: HashedTableIndices,
+    pub scattering: HashedTableIndices,
+    pub inelastic: HashedTableIndices,
+    pub absorption: HashedTableIndices,
+    pub nu_bar: HashedTableIndices,
+    pub watt_parameters: HashedTableIndices,
+}
+
+/// Watt parameter energies are in MeV, all other tables are in eV.
+const WATT_ENERGY_SCALE: f64 = 1e6;
+
+impl EnergyGrid {
+    /// Creates a grid spanning the positive energies of all tables of all materials.
+    pub fn new(material_data_vector: &[MaterialData], bin_count: usize) -> Self {
+        let mut energy_min = f64::MAX;
+        let mut energy_max = f64::MIN;
+
+        for material_data in material_data_vector {
+            for (energies, scale) in material_tables(material_data) {
+                for &energy in energies.iter().filter(|&&energy| energy > 0.0) {
+                    energy_min = energy_min.min(energy * scale);
+                    energy_max = energy_max.max(energy * scale);
+                }
+            }
+        }
+
+        // Without any data (e.g. only Void), a nominal range is used.
+        if energy_min >= energy_max {
+            energy_min = 1e-5;
+            energy_max = 2e7;
+        }
+
+        let log_energy_min = energy_min.ln();
+        let log_energy_max = energy_max.ln();
+
+        debug!(
+            "Energy grid from {:.3e} to {:.3e} eV with {} bins.",
+            energy_min, energy_max, bin_count
+        );
+
+        EnergyGrid {
+            log_energy_min,
+            log_energy_max,
+            bin_count,
+            inverse_bin_width: bin_count as f64 / (log_energy_max - log_energy_min),
+        }
+    }
+
+    /// Returns the lower edge of a bin in eV.
+    fn bin_lower_energy(&self, bin: usize) -> f64 {
+        (self.log_energy_min + bin as f64 / self.inverse_bin_width).exp()
+    }
+
+    /// Returns the position of the energy on the grid. Energies outside the grid are placed in the first or last bin.
+    pub fn get_position(&self, energy: f64) -> GridPosition {
+        let bin = ((energy.ln() - self.log_energy_min) * self.inverse_bin_width).floor();
+        let bin = if bin.is_nan() || bin < 0.0 {
+            0
+        } else {
+            (bin as usize).min(self.bin_count - 1)
+        };
+
+        GridPosition { energy, bin }
+    }
+
+    /// Creates the hashed indices for a single table, whose energies are given in units of ```scale``` eV.
+    pub fn hash_table(&self, energies: &[f64], scale: f64) -> HashedTableIndices {
+        let last_index = energies.len().saturating_sub(1);
+
+        let mut bin_start_indices: Vec<u32> = (0..self.bin_count)
+            .map(|bin| {
+                let lower_energy = self.bin_lower_energy(bin) / scale;
+                energies
+                    .partition_point(|&energy| energy <= lower_energy)
+                    .saturating_sub(1) as u32
+            })
+            .collect();
+
+        // Energies below the grid end up in the first bin, and above it in the last, so these have to cover the full table.
+        if let Some(first_index) = bin_start_indices.first_mut() {
+            *first_index = 0;
+        }
+        bin_start_indices.push(last_index as u32);
+
+        HashedTableIndices { bin_start_indices }
+    }
+
+    /// Creates the hashed indices for all the tables of a material.
+    pub fn hash_material(&self, material_data: &MaterialData) -> MaterialGridIndices {
+        MaterialGridIndices {
+            fission: self.hash_table(&material_data.energy_fission_cross_sections, 1.0),
+            scattering: self.hash_table(&material_data.energy_scattering_cross_sections, 1.0),
+            inelastic: self.hash_table(&material_data.energy_inelastic_cross_sections, 1.0),
+            absorption: self.hash_table(&material_data.energy_absorption_cross_sections, 1.0),
+            nu_bar: self.hash_table(&material_data.energy_nu_bar, 1.0),
+            watt_parameters: self
+                .hash_table(&material_data.energy_watt_parameters, WATT_ENERGY_SCALE),
+        }
+    }
+}
+
+impl HashedTableIndices {
+    /// Returns the index of the table point at or below the energy, searching only within the energy's bin. Below the table, index 0 is returned.
+    fn find_index(&self, energies: &[f64], grid_position: &GridPosition, energy: f64) -> usize {
+        // Starting one point earlier guards against rounding in the bin of energies right at a bin edge.
+        let start_index = (self.bin_start_indices[grid_position.bin] as usize).saturating_sub(1);
+        let end_index = self.bin_start_indices[grid_position.bin + 1] as usize;
+
+        start_index
+            + energies[start_index..=end_index]
+                .partition_point(|&table_energy| table_energy <= energy)
+                .saturating_sub(1)
+    }
+
+    /// Evaluates the table at the grid position with its scheme, giving the same result as ```interpolate_table```.
+    pub fn interpolate(
+        &self,
+        energies: &[f64],
+        values: &[f64],
+        table_scheme: &TableScheme,
+        grid_position: &GridPosition,
+    ) -> f64 {
+        let index = self.find_index(energies, grid_position, grid_position.energy);
+
+        evaluate_table(energies, values, table_scheme, index, grid_position.energy)
+    }
+
+    /// Returns the Watt parameters at the grid position, giving the same result as ```get_watt_parameters_with_scheme```.
+    pub fn interpolate_watt_parameters(
+        &self,
+        energies: &[f64],
+        a_values: &[f64],
+        b_values: &[f64],
+        table_scheme: &TableScheme,
+        grid_position: &GridPosition,
+    ) -> (f64, f64) {
+        // Tables without data still give their b value, which is used as the placeholder.
+        if a_values.len() == 1 {
+            return (0.0, b_values[0]);
+        }
+
+        let mev_energy = grid_position.energy / WATT_ENERGY_SCALE;
+        let index = self.find_index(energies, grid_position, mev_energy);
+
+        (
+            evaluate_table(energies, a_values, table_scheme, index, mev_energy),
+            evaluate_table(energies, b_values, table_scheme, index, mev_energy),
+        )
+    }
+}
+
+/// All energy tables of a material, with the scale to convert their energies to eV.
+fn material_tables(material_data: &MaterialData) -> [(&Vec<f64>, f64); 6] {
+    [
+        (&material_data.energy_fission_cross_sections, 1.0),
+        (&material_data.energy_scattering_cross_sections, 1.0),
+        (&material_data.energy_inelastic_cross_sections, 1.0),
+        (&material_data.energy_absorption_cross_sections, 1.0),
+        (&material_data.energy_nu_bar, 1.0),
+        (&material_data.energy_watt_parameters, WATT_ENERGY_SCALE),
+    ]
+}
@@ -1,7 +1,9 @@
-use crate::materials::material_properties::MaterialNames;
+use crate::utils::data_handling::{interpolate_table, TableScheme};
+
+use serde::{Deserialize, Serialize};
 
 /// Includes all required material data.
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Serialize, Deserialize)]
 pub struct MaterialData {
     pub energy_fission_cross_sections: Vec<f64>,
     pub fission_cross_sections: Vec<f64>,
@@ -9,6 +11,10 @@ pub struct MaterialData {
     pub energy_scattering_cross_sections: Vec<f64>,
     pub elastic_cross_sections: Vec<f64>,
 
+    // Inelastic part of the scattering cross-sections above, which leaves the nucleus excited.
+    pub energy_inelastic_cross_sections: Vec<f64>,
+    pub inelastic_cross_sections: Vec<f64>,
+
     pub energy_absorption_cross_sections: Vec<f64>,
     pub absorption_cross_sections: Vec<f64>,
 
@@ -19,13 +25,91 @@ pub struct MaterialData {
     pub watt_parameters_a: Vec<f64>,
     pub watt_parameters_b: Vec<f64>,
 
+    pub table_schemes: MaterialTableSchemes,
+
     pub number_density: f64,
     pub atomic_mass: f64,
 
-    pub thermal_conductivity: f64,
-    pub density: f64,
-    pub heat_capacity: f64,
+    pub thermal_conductivity: ThermalProperty,
+    pub density: ThermalProperty,
+    pub heat_capacity: ThermalProperty,
+    pub melting_point: Option<f64>,
 
-    pub name: MaterialNames,
+    pub name: String,
     pub fissionable: bool,
 }
+
+impl MaterialData {
+    /// Lowest energy at which inelastic scattering is possible: the last tabulated zero before the inelastic cross-section becomes positive.
+    pub fn inelastic_threshold(&self) -> f64 {
+        match self
+            .inelastic_cross_sections
+            .iter()
+            .position(|&cross_section| cross_section > 0.0)
+        {
+            Some(index) if index > 0 => self.energy_inelastic_cross_sections[index - 1],
+            _ => self.energy_inelastic_cross_sections[0],
+        }
+    }
+}
+
+/// The interpolation law and out-of-range behaviour for each of a material's tables.
+#[derive(Default, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct MaterialTableSchemes {
+    pub fission: TableScheme,
+    pub scattering: TableScheme,
+    pub inelastic: TableScheme,
+    pub absorption: TableScheme,
+    pub nu_bar: TableScheme,
+    pub watt_parameters: TableScheme,
+}
+
+/// A thermophysical property as a function of temperature (K), either constant, tabulated or as a polynomial correlation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ThermalProperty {
+    Constant(f64),
+    /// Interpolated linearly, and clamped to the end values outside the table.
+    Table {
+        temperatures: Vec<f64>,
+        values: Vec<f64>,
+    },
+    /// Polynomial in the temperature, with the coefficients in increasing order. Outside its range of validity, the temperature is clamped.
+    Polynomial {
+        coefficients: Vec<f64>,
+        minimum_temperature: f64,
+        maximum_temperature: f64,
+    },
+}
+
+impl Default for ThermalProperty {
+    fn default() -> Self {
+        ThermalProperty::Constant(0.0)
+    }
+}
+
+impl ThermalProperty {
+    /// Evaluates the property at the given temperature in K.
+    pub fn evaluate(&self, temperature: f64) -> f64 {
+        match self {
+            ThermalProperty::Constant(value) => *value,
+            ThermalProperty::Table {
+                temperatures,
+                values,
+            } => match values.as_slice() {
+                [value] => *value,
+                _ => interpolate_table(temperatures, values, &TableScheme::default(), temperature),
+            },
+            ThermalProperty::Polynomial {
+                coefficients,
+                minimum_temperature,
+                maximum_temperature,
+            } => {
+                let temperature = temperature.clamp(*minimum_temperature, *maximum_temperature);
+                coefficients
+                    .iter()
+                    .rev()
+                    .fold(0.0, |value, coefficient| value * temperature + coefficient)
+            }
+        }
+    }
+}
@@ -0,0 +1,608 @@
+use crate::materials::data_validation::{validate_material_data, IssueSeverity};
+use crate::materials::material_data::{MaterialData, MaterialTableSchemes, ThermalProperty};
+use crate::utils::ace_loading::AceTable;
+use crate::utils::data_handling::{sum_on_union_grid, TableScheme};
+use crate::utils::data_loading::{
+    load_cross_sections, load_table_scheme, load_thermal_property, load_watt_parameters,
+};
+use crate::utils::endf_loading::EndfEvaluation;
+
+use log::{debug, warn};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the material that is used whenever a position is outside all parts. Always present at index 0.
+pub const VOID_MATERIAL_NAME: &str = "Void";
+
+/// Default location of the material library.
+pub const DEFAULT_MATERIAL_LIBRARY_PATH: &str = "config/materials/material_library.toml";
+
+/// Loading in a single material from the library TOML.
+/// Reactions without a data file are taken to not occur for this material.
+/// If an ENDF-6 evaluation or ACE table is specified, all reactions are read from it, with any explicitly specified CSVs taking precedence.
+/// Natural elements can instead list their ```isotopes```, whose evaluations are combined by abundance.
+/// The table schemes are read from the headers of the CSVs, and can be overridden per table through ```table_schemes```.
+#[derive(Deserialize, Debug)]
+pub struct MaterialTOML {
+    pub name: String,
+    pub atomic_mass: f64,
+    pub number_density: f64,
+    #[serde(default)]
+    pub fissionable: bool,
+    #[serde(default)]
+    pub thermal_conductivity: ThermalPropertyTOML,
+    #[serde(default)]
+    pub density: ThermalPropertyTOML,
+    #[serde(default)]
+    pub heat_capacity: ThermalPropertyTOML,
+    pub melting_point: Option<f64>,
+    pub fission_path: Option<String>,
+    pub scattering_path: Option<String>,
+    pub inelastic_path: Option<String>,
+    pub absorption_path: Option<String>,
+    pub nu_bar_path: Option<String>,
+    pub watt_parameters_path: Option<String>,
+    pub endf_path: Option<String>,
+    pub ace_path: Option<String>,
+    pub isotopes: Option<Vec<IsotopeTOML>>,
+    pub table_schemes: Option<MaterialTableSchemesTOML>,
+}
+
+/// A thermophysical property in the library: either a constant, a table against temperature (K), a CSV with ```temperature``` and ```value``` columns, or a polynomial correlation.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum ThermalPropertyTOML {
+    Constant(f64),
+    Table {
+        temperatures: Vec<f64>,
+        values: Vec<f64>,
+    },
+    File {
+        path: String,
+    },
+    Polynomial {
+        coefficients: Vec<f64>,
+        #[serde(default)]
+        minimum_temperature: Option<f64>,
+        #[serde(default)]
+        maximum_temperature: Option<f64>,
+    },
+}
+
+impl Default for ThermalPropertyTOML {
+    fn default() -> Self {
+        ThermalPropertyTOML::Constant(0.0)
+    }
+}
+
+impl ThermalPropertyTOML {
+    /// Converts the property, reading the table from its file if needed.
+    pub fn into_thermal_property(self, material_name: &str) -> ThermalProperty {
+        let (temperatures, values) = match self {
+            ThermalPropertyTOML::Constant(value) => return ThermalProperty::Constant(value),
+            ThermalPropertyTOML::Polynomial {
+                coefficients,
+                minimum_temperature,
+                maximum_temperature,
+            } => {
+                return ThermalProperty::Polynomial {
+                    coefficients,
+                    minimum_temperature: minimum_temperature.unwrap_or(0.0),
+                    maximum_temperature: maximum_temperature.unwrap_or(f64::INFINITY),
+                }
+            }
+            ThermalPropertyTOML::Table {
+                temperatures,
+                values,
+            } => (temperatures, values),
+            ThermalPropertyTOML::File { path } => load_thermal_property(Path::new(&path)),
+        };
+
+        assert!(
+            !values.is_empty() && temperatures.len() == values.len(),
+            "Thermal property table of material {} needs the same, non-zero number of temperatures and values.",
+            material_name
+        );
+
+        ThermalProperty::Table {
+            temperatures,
+            values,
+        }
+    }
+}
+
+/// Overrides for the interpolation law and out-of-range behaviour of a material's tables.
+#[derive(Deserialize, Debug, Default)]
+pub struct MaterialTableSchemesTOML {
+    pub fission: Option<TableScheme>,
+    pub scattering: Option<TableScheme>,
+    pub inelastic: Option<TableScheme>,
+    pub absorption: Option<TableScheme>,
+    pub nu_bar: Option<TableScheme>,
+    pub watt_parameters: Option<TableScheme>,
+}
+
+/// An isotope of a natural element, with its evaluation and its abundance as an atom fraction.
+#[derive(Deserialize, Debug)]
+pub struct IsotopeTOML {
+    pub endf_path: Option<String>,
+    pub ace_path: Option<String>,
+    pub abundance: f64,
+}
+
+/// All materials defined in a library TOML.
+#[derive(Deserialize, Debug)]
+struct MaterialLibraryTOML {
+    materials: Vec<MaterialTOML>,
+}
+
+/// Loads the cross-sections and their scheme from a path if specified, otherwise returns the fallback data.
+fn load_optional_cross_sections(
+    file_path: &Option<String>,
+    fallback: (Vec<f64>, Vec<f64>, TableScheme),
+) -> (Vec<f64>, Vec<f64>, TableScheme) {
+    match file_path {
+        Some(file_path) => {
+            let (energies, cross_sections) = load_cross_sections(Path::new(file_path));
+            (
+                energies,
+                cross_sections,
+                load_table_scheme(Path::new(file_path)),
+            )
+        }
+        None => fallback,
+    }
+}
+
+/// Loads the evaluated data from either an ENDF-6 evaluation or an ACE table.
+fn load_evaluated_data(
+    endf_path: &Option<String>,
+    ace_path: &Option<String>,
+    name: &str,
+) -> MaterialData {
+    match (endf_path, ace_path) {
+        (Some(_), Some(_)) => panic!("Material {} specifies both an ENDF and an ACE file.", name),
+        (Some(endf_path), None) => {
+            MaterialData::from_endf(&EndfEvaluation::from_file(Path::new(endf_path)), name)
+        }
+        (None, Some(ace_path)) => {
+            MaterialData::from_ace(&AceTable::from_file(Path::new(ace_path)), name)
+        }
+        (None, None) => MaterialData::get_void(),
+    }
+}
+
+/// Sums the cross-sections of the isotopes weighted by their abundance. Tables without data (a single point) are skipped.
+fn sum_isotope_cross_sections<'a>(
+    tables: impl Iterator<Item = (&'a Vec<f64>, &'a Vec<f64>, f64)>,
+) -> (Vec<f64>, Vec<f64>) {
+    let weighted_tables: Vec<(&[f64], &[f64], f64)> = tables
+        .filter(|(energies, _, _)| energies.len() > 1)
+        .map(|(energies, cross_sections, abundance)| {
+            (energies.as_slice(), cross_sections.as_slice(), abundance)
+        })
+        .collect();
+
+    if weighted_tables.is_empty() {
+        return (vec![0.0], vec![0.0]);
+    }
+
+    sum_on_union_grid(&weighted_tables)
+}
+
+impl MaterialData {
+    /// Combines the evaluated data of the isotopes of a natural element, weighted by their abundance.
+    /// The abundances are normalized. Fissionable isotopes are not supported, because ν̄ and the fission spectrum cannot simply be averaged.
+    pub fn from_isotopes(isotopes: &[(MaterialData, f64)], name: &str) -> MaterialData {
+        let total_abundance: f64 = isotopes.iter().map(|(_, abundance)| abundance).sum();
+        assert!(
+            total_abundance > 0.0,
+            "Material {} has no isotopes with a positive abundance.",
+            name
+        );
+        assert!(
+            isotopes.iter().all(|(isotope, _)| !isotope.fissionable),
+            "Material {} contains fissionable isotopes, which have to be separate materials.",
+            name
+        );
+
+        let (energy_fission_cross_sections, fission_cross_sections) =
+            sum_isotope_cross_sections(isotopes.iter().map(|(isotope, abundance)| {
+                (
+                    &isotope.energy_fission_cross_sections,
+                    &isotope.fission_cross_sections,
+                    abundance / total_abundance,
+                )
+            }));
+        let (energy_scattering_cross_sections, elastic_cross_sections) =
+            sum_isotope_cross_sections(isotopes.iter().map(|(isotope, abundance)| {
+                (
+                    &isotope.energy_scattering_cross_sections,
+                    &isotope.elastic_cross_sections,
+                    abundance / total_abundance,
+                )
+            }));
+        let (energy_inelastic_cross_sections, inelastic_cross_sections) =
+            sum_isotope_cross_sections(isotopes.iter().map(|(isotope, abundance)| {
+                (
+                    &isotope.energy_inelastic_cross_sections,
+                    &isotope.inelastic_cross_sections,
+                    abundance / total_abundance,
+                )
+            }));
+        let (energy_absorption_cross_sections, absorption_cross_sections) =
+            sum_isotope_cross_sections(isotopes.iter().map(|(isotope, abundance)| {
+                (
+                    &isotope.energy_absorption_cross_sections,
+                    &isotope.absorption_cross_sections,
+                    abundance / total_abundance,
+                )
+            }));
+
+        MaterialData {
+            energy_fission_cross_sections,
+            fission_cross_sections,
+            energy_scattering_cross_sections,
+            elastic_cross_sections,
+            energy_inelastic_cross_sections,
+            inelastic_cross_sections,
+            energy_absorption_cross_sections,
+            absorption_cross_sections,
+            atomic_mass: isotopes
+                .iter()
+                .map(|(isotope, abundance)| isotope.atomic_mass * abundance / total_abundance)
+                .sum(),
+            name: name.to_string(),
+            ..MaterialData::get_void()
+        }
+    }
+
+    /// The void material, without any interactions.
+    pub fn get_void() -> MaterialData {
+        MaterialData {
+            energy_fission_cross_sections: vec![0.0],
+            fission_cross_sections: vec![0.0],
+            energy_scattering_cross_sections: vec![0.0],
+            elastic_cross_sections: vec![0.0],
+            energy_inelastic_cross_sections: vec![0.0],
+            inelastic_cross_sections: vec![0.0],
+            energy_absorption_cross_sections: vec![0.0],
+            absorption_cross_sections: vec![0.0],
+            energy_nu_bar: vec![0.0],
+            nu_bar: vec![0.0],
+            energy_watt_parameters: vec![0.0],
+            watt_parameters_a: vec![0.0],
+            watt_parameters_b: vec![0.0],
+            name: VOID_MATERIAL_NAME.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Creates the material data from a library entry, loading all the referenced data files.
+    pub fn from_toml(material_toml: MaterialTOML) -> MaterialData {
+        debug!("Loading material {}.", material_toml.name);
+
+        // Reactions without a CSV fall back to the evaluated data, or to the placeholder for reactions that do not occur.
+        let endf_data = match &material_toml.isotopes {
+            Some(_) if material_toml.endf_path.is_some() || material_toml.ace_path.is_some() => {
+                panic!(
+                    "Material {} specifies both isotopes and its own evaluation.",
+                    material_toml.name
+                )
+            }
+            Some(isotopes) => {
+                let isotope_data: Vec<(MaterialData, f64)> = isotopes
+                    .iter()
+                    .map(|isotope| {
+                        (
+                            load_evaluated_data(
+                                &isotope.endf_path,
+                                &isotope.ace_path,
+                                &material_toml.name,
+                            ),
+                            isotope.abundance,
+                        )
+                    })
+                    .collect();
+                MaterialData::from_isotopes(&isotope_data, &material_toml.name)
+            }
+            None => load_evaluated_data(
+                &material_toml.endf_path,
+                &material_toml.ace_path,
+                &material_toml.name,
+            ),
+        };
+
+        let endf_schemes = endf_data.table_schemes;
+
+        // Fission
+        let (energy_fission_cross_sections, fission_cross_sections, fission_scheme) =
+            load_optional_cross_sections(
+                &material_toml.fission_path,
+                (
+                    endf_data.energy_fission_cross_sections,
+                    endf_data.fission_cross_sections,
+                    endf_schemes.fission,
+                ),
+            );
+
+        // Scattering
+        let (energy_scattering_cross_sections, elastic_cross_sections, scattering_scheme) =
+            load_optional_cross_sections(
+                &material_toml.scattering_path,
+                (
+                    endf_data.energy_scattering_cross_sections,
+                    endf_data.elastic_cross_sections,
+                    endf_schemes.scattering,
+                ),
+            );
+
+        // Inelastic scattering, as part of the scattering.
+        let (energy_inelastic_cross_sections, inelastic_cross_sections, inelastic_scheme) =
+            load_optional_cross_sections(
+                &material_toml.inelastic_path,
+                (
+                    endf_data.energy_inelastic_cross_sections,
+                    endf_data.inelastic_cross_sections,
+                    endf_schemes.inelastic,
+                ),
+            );
+
+        // Absorption
+        let (energy_absorption_cross_sections, absorption_cross_sections, absorption_scheme) =
+            load_optional_cross_sections(
+                &material_toml.absorption_path,
+                (
+                    endf_data.energy_absorption_cross_sections,
+                    endf_data.absorption_cross_sections,
+                    endf_schemes.absorption,
+                ),
+            );
+
+        // Nu bar
+        let (energy_nu_bar, nu_bar, nu_bar_scheme) = load_optional_cross_sections(
+            &material_toml.nu_bar_path,
+            (
+                endf_data.energy_nu_bar,
+                endf_data.nu_bar,
+                endf_schemes.nu_bar,
+            ),
+        );
+
+        // Watt parameters
+        let (energy_watt_parameters, watt_parameters_a, watt_parameters_b, watt_parameters_scheme) =
+            match &material_toml.watt_parameters_path {
+                Some(file_path) => {
+                    let (energies, a_values, b_values) = load_watt_parameters(Path::new(file_path));
+                    (
+                        energies,
+                        a_values,
+                        b_values,
+                        load_table_scheme(Path::new(file_path)),
+                    )
+                }
+                None => (
+                    endf_data.energy_watt_parameters,
+                    endf_data.watt_parameters_a,
+                    endf_data.watt_parameters_b,
+                    endf_schemes.watt_parameters,
+                ),
+            };
+
+        // The schemes in the library take precedence over the headers of the data files.
+        let table_schemes_toml = material_toml.table_schemes.unwrap_or_default();
+        let table_schemes = MaterialTableSchemes {
+            fission: table_schemes_toml.fission.unwrap_or(fission_scheme),
+            scattering: table_schemes_toml.scattering.unwrap_or(scattering_scheme),
+            inelastic: table_schemes_toml.inelastic.unwrap_or(inelastic_scheme),
+            absorption: table_schemes_toml.absorption.unwrap_or(absorption_scheme),
+            nu_bar: table_schemes_toml.nu_bar.unwrap_or(nu_bar_scheme),
+            watt_parameters: table_schemes_toml
+                .watt_parameters
+                .unwrap_or(watt_parameters_scheme),
+        };
+
+        MaterialData {
+            energy_fission_cross_sections,
+            fission_cross_sections,
+
+            energy_scattering_cross_sections,
+            elastic_cross_sections,
+
+            energy_inelastic_cross_sections,
+            inelastic_cross_sections,
+
+            energy_absorption_cross_sections,
+            absorption_cross_sections,
+
+            energy_nu_bar,
+            nu_bar,
+
+            energy_watt_parameters,
+            watt_parameters_a,
+            watt_parameters_b,
+
+            table_schemes,
+
+            number_density: material_toml.number_density,
+            atomic_mass: material_toml.atomic_mass,
+
+            thermal_conductivity: material_toml
+                .thermal_conductivity
+                .into_thermal_property(&material_toml.name),
+            density: material_toml
+                .density
+                .into_thermal_property(&material_toml.name),
+            heat_capacity: material_toml
+                .heat_capacity
+                .into_thermal_property(&material_toml.name),
+            melting_point: material_toml.melting_point,
+
+            name: material_toml.name,
+            fissionable: material_toml.fissionable || endf_data.fissionable,
+        }
+    }
+}
+
+/// Reads the material entries from a single library TOML.
+fn read_material_library_file(toml_path: &Path) -> Vec<MaterialTOML> {
+    let toml_str = fs::read_to_string(toml_path)
+        .unwrap_or_else(|_| panic!("Failed to read material library: {:?}", toml_path));
+    let material_library: MaterialLibraryTOML = toml::from_str(&toml_str).unwrap_or_else(|error| {
+        panic!(
+            "Failed to parse material library {:?}: {}",
+            toml_path, error
+        )
+    });
+
+    material_library.materials
+}
+
+/// Returns the paths of the library TOMLs: either the single manifest, or all TOMLs in the directory in alphabetical order.
+fn get_library_toml_paths(library_path: &Path) -> Vec<PathBuf> {
+    if library_path.is_dir() {
+        let mut toml_paths: Vec<_> = fs::read_dir(library_path)
+            .expect("Failed to read material library directory.")
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "toml")
+            })
+            .collect();
+        toml_paths.sort();
+        toml_paths
+    } else {
+        vec![library_path.to_path_buf()]
+    }
+}
+
+impl MaterialTOML {
+    /// Returns all data files this material is read from.
+    pub fn get_data_paths(&self) -> Vec<PathBuf> {
+        let isotope_paths = self
+            .isotopes
+            .iter()
+            .flatten()
+            .flat_map(|isotope| [&isotope.endf_path, &isotope.ace_path]);
+        let thermal_property_paths = [
+            &self.thermal_conductivity,
+            &self.density,
+            &self.heat_capacity,
+        ]
+        .into_iter()
+        .filter_map(|thermal_property| match thermal_property {
+            ThermalPropertyTOML::File { path } => Some(path),
+            _ => None,
+        });
+
+        [
+            &self.fission_path,
+            &self.scattering_path,
+            &self.inelastic_path,
+            &self.absorption_path,
+            &self.nu_bar_path,
+            &self.watt_parameters_path,
+            &self.endf_path,
+            &self.ace_path,
+        ]
+        .into_iter()
+        .chain(isotope_paths)
+        .flatten()
+        .chain(thermal_property_paths)
+        .map(PathBuf::from)
+        .collect()
+    }
+}
+
+/// Reads the material entries from either a single TOML manifest, or a directory of TOMLs, without loading any of their data.
+pub fn read_material_tomls(library_path: &Path) -> Vec<MaterialTOML> {
+    get_library_toml_paths(library_path)
+        .iter()
+        .flat_map(|toml_path| read_material_library_file(toml_path))
+        .collect()
+}
+
+/// Returns every file the material library is built from: the library TOMLs, followed by all data files they reference.
+pub fn get_library_source_paths(library_path: &Path) -> Vec<PathBuf> {
+    let data_paths: Vec<PathBuf> = read_material_tomls(library_path)
+        .iter()
+        .flat_map(|material_toml| material_toml.get_data_paths())
+        .collect();
+
+    get_library_toml_paths(library_path)
+        .into_iter()
+        .chain(data_paths)
+        .collect()
+}
+
+/// Loads the material library from either a single TOML manifest, or a directory of TOMLs (read in alphabetical order).
+/// Void is always inserted as the first material, so that index 0 is the default outside all parts.
+pub fn load_material_library(library_path: &Path) -> Vec<MaterialData> {
+    load_materials(read_material_tomls(library_path))
+}
+
+/// Loads only the named materials of the library, after Void, so that the data files of the other materials are not read and do not have to exist.
+/// Names that are not in the library are left out.
+pub fn load_selected_materials(library_path: &Path, material_names: &[&str]) -> Vec<MaterialData> {
+    load_materials(
+        read_material_tomls(library_path)
+            .into_iter()
+            .filter(|material_toml| material_names.contains(&material_toml.name.as_str()))
+            .collect(),
+    )
+}
+
+/// Loads the data of the material entries, with Void as the first material.
+fn load_materials(material_tomls: Vec<MaterialTOML>) -> Vec<MaterialData> {
+    let mut material_data_vector: Vec<MaterialData> = vec![MaterialData::get_void()];
+
+    for material_toml in material_tomls {
+        assert!(
+            get_material_index_by_name(&material_data_vector, &material_toml.name).is_none(),
+            "Material {} is defined more than once in the material library.",
+            material_toml.name
+        );
+        let material_data = MaterialData::from_toml(material_toml);
+
+        // Unusable tables otherwise only show up as a failing binary search during the simulation.
+        for issue in validate_material_data(&material_data)
+            .iter()
+            .filter(|issue| issue.severity == IssueSeverity::Error)
+        {
+            warn!("{}", issue);
+        }
+
+        material_data_vector.push(material_data);
+    }
+
+    material_data_vector
+}
+
+/// Loads only the names, atomic masses and number densities of the library, without reading any data files, for calculations that need no cross-sections such as masses.
+/// As for the full library, Void is at index 0.
+pub fn load_material_constants(library_path: &Path) -> Vec<MaterialData> {
+    std::iter::once(MaterialData::get_void())
+        .chain(
+            read_material_tomls(library_path)
+                .into_iter()
+                .map(|material_toml| MaterialData {
+                    name: material_toml.name,
+                    atomic_mass: material_toml.atomic_mass,
+                    number_density: material_toml.number_density,
+                    fissionable: material_toml.fissionable,
+                    ..Default::default()
+                }),
+        )
+        .collect()
+}
+
+/// Returns the index of a material in the material data vector, or None if it is not defined.
+pub fn get_material_index_by_name(
+    material_data_vector: &[MaterialData],
+    material_name: &str,
+) -> Option<usize> {
+    material_data_vector
+        .iter()
+        .position(|material_data| material_data.name == material_name)
+}
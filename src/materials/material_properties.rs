@@ -1,72 +1,24 @@
-use serde::{Deserialize, Serialize};
-
+use crate::materials::energy_grid::{GridPosition, MaterialGridIndices};
 use crate::materials::material_data::MaterialData;
-use crate::utils::data_handling::{get_watt_parameters, linear_interpolation};
+use crate::materials::material_library::{load_material_library, DEFAULT_MATERIAL_LIBRARY_PATH};
+use crate::utils::data_handling::{get_watt_parameters_with_scheme, interpolate_table};
 
 use std::fmt;
-
-mod b10;
-mod be9;
-mod fe54;
-mod h1;
-mod o16;
-mod u235;
-mod u238;
-mod void;
-
-/// All currently defined materials.
-#[derive(PartialEq, Eq, Debug, Default, Copy, Clone, Serialize, Deserialize)]
-
-pub enum MaterialNames {
-    #[default]
-    Void,
-    H1,
-    Be9,
-    B10,
-    O16,
-    Fe54,
-    U235,
-    U238,
-}
-
-/// Mapping between the enum and indices.
-pub fn map_enum_to_indices(material_name: &MaterialNames) -> usize {
-    match material_name {
-        MaterialNames::Void => 0,
-        MaterialNames::H1 => 1,
-        MaterialNames::Be9 => 2,
-        MaterialNames::B10 => 3,
-        MaterialNames::O16 => 4,
-        MaterialNames::Fe54 => 5,
-        MaterialNames::U235 => 6,
-        MaterialNames::U238 => 7,
-    }
-}
-
-/// Mapping between the indices and the enum.
-pub fn map_indices_to_enum(material_index: usize) -> MaterialNames {
-    match material_index {
-        0 => MaterialNames::Void,
-        1 => MaterialNames::H1,
-        2 => MaterialNames::Be9,
-        3 => MaterialNames::B10,
-        4 => MaterialNames::O16,
-        5 => MaterialNames::Fe54,
-        6 => MaterialNames::U235,
-        7 => MaterialNames::U238,
-        _ => MaterialNames::Void,
-    }
-}
+use std::path::Path;
 
 /// Used to instantiate materials from the ```MaterialData``` struct and return data on them for interactions.
 #[derive(Default, Clone, Debug)]
 pub struct MaterialProperties {
     pub number_density: f64,
     pub scattering: f64,
+    /// Part of ```scattering``` that is inelastic.
+    pub inelastic: f64,
+    /// Energy below which inelastic scattering is not possible.
+    pub inelastic_threshold: f64,
     pub absorption: f64,
     pub fission: f64,
     pub fissionable: bool,
-    pub name: MaterialNames,
+    pub name: String,
     pub watt_a: f64,
     pub watt_b: f64,
     pub nu_bar: f64,
@@ -87,46 +39,126 @@ impl fmt::Display for MaterialProperties {
 }
 
 impl MaterialProperties {
-    /// Takes a ```MaterialData``` instance and uses linear interpolation to return information on the fission, scattering and absorption cross-sections for the neutron's energy.
+    /// Takes a ```MaterialData``` instance and interpolates the fission, scattering and absorption cross-sections for the neutron's energy, using each table's scheme.
     pub fn get_properties(&mut self, material_data: &MaterialData, energy: f64) -> () {
         self.number_density = material_data.number_density;
+        let table_schemes = &material_data.table_schemes;
 
-        (self.fission, _) = linear_interpolation(
+        self.fission = interpolate_table(
             &material_data.energy_fission_cross_sections,
             &material_data.fission_cross_sections,
+            &table_schemes.fission,
             energy,
         );
 
-        (self.scattering, _) = linear_interpolation(
+        self.scattering = interpolate_table(
             &material_data.energy_scattering_cross_sections,
             &material_data.elastic_cross_sections,
+            &table_schemes.scattering,
+            energy,
+        );
+
+        self.inelastic = interpolate_table(
+            &material_data.energy_inelastic_cross_sections,
+            &material_data.inelastic_cross_sections,
+            &table_schemes.inelastic,
             energy,
         );
 
-        (self.absorption, _) = linear_interpolation(
+        self.absorption = interpolate_table(
             &material_data.energy_absorption_cross_sections,
             &material_data.absorption_cross_sections,
+            &table_schemes.absorption,
             energy,
         );
 
-        (self.nu_bar, _) =
-            linear_interpolation(&material_data.energy_nu_bar, &material_data.nu_bar, energy);
+        self.nu_bar = interpolate_table(
+            &material_data.energy_nu_bar,
+            &material_data.nu_bar,
+            &table_schemes.nu_bar,
+            energy,
+        );
 
-        (self.watt_a, self.watt_b) = get_watt_parameters(
+        (self.watt_a, self.watt_b) = get_watt_parameters_with_scheme(
             &material_data.energy_watt_parameters,
             &material_data.watt_parameters_a,
             &material_data.watt_parameters_b,
+            &table_schemes.watt_parameters,
             energy,
         );
 
         self.fission = self.fission * 1e-28 * self.number_density;
         self.scattering = self.scattering * 1e-28 * self.number_density;
+        self.inelastic = self.inelastic * 1e-28 * self.number_density;
         self.absorption = self.absorption * 1e-28 * self.number_density;
 
         self.fissionable = material_data.fissionable;
         self.atomic_mass = material_data.atomic_mass;
+        self.inelastic_threshold = material_data.inelastic_threshold();
+    }
+
+    /// Same as ```get_properties```, but uses the hashed energy grid to look up all tables from a single grid position.
+    pub fn get_properties_on_grid(
+        &mut self,
+        material_data: &MaterialData,
+        grid_indices: &MaterialGridIndices,
+        grid_position: &GridPosition,
+    ) {
+        self.number_density = material_data.number_density;
+
+        let table_schemes = &material_data.table_schemes;
+
+        self.fission = grid_indices.fission.interpolate(
+            &material_data.energy_fission_cross_sections,
+            &material_data.fission_cross_sections,
+            &table_schemes.fission,
+            grid_position,
+        );
 
-        self.name = material_data.name;
+        self.scattering = grid_indices.scattering.interpolate(
+            &material_data.energy_scattering_cross_sections,
+            &material_data.elastic_cross_sections,
+            &table_schemes.scattering,
+            grid_position,
+        );
+
+        self.inelastic = grid_indices.inelastic.interpolate(
+            &material_data.energy_inelastic_cross_sections,
+            &material_data.inelastic_cross_sections,
+            &table_schemes.inelastic,
+            grid_position,
+        );
+
+        self.absorption = grid_indices.absorption.interpolate(
+            &material_data.energy_absorption_cross_sections,
+            &material_data.absorption_cross_sections,
+            &table_schemes.absorption,
+            grid_position,
+        );
+
+        self.nu_bar = grid_indices.nu_bar.interpolate(
+            &material_data.energy_nu_bar,
+            &material_data.nu_bar,
+            &table_schemes.nu_bar,
+            grid_position,
+        );
+
+        (self.watt_a, self.watt_b) = grid_indices.watt_parameters.interpolate_watt_parameters(
+            &material_data.energy_watt_parameters,
+            &material_data.watt_parameters_a,
+            &material_data.watt_parameters_b,
+            &table_schemes.watt_parameters,
+            grid_position,
+        );
+
+        self.fission *= 1e-28 * self.number_density;
+        self.scattering *= 1e-28 * self.number_density;
+        self.inelastic *= 1e-28 * self.number_density;
+        self.absorption *= 1e-28 * self.number_density;
+
+        self.fissionable = material_data.fissionable;
+        self.atomic_mass = material_data.atomic_mass;
+        self.inelastic_threshold = material_data.inelastic_threshold();
     }
 
     /// Returns the total fission cross section, which for now is a combination of scattering, fission and absorption.
@@ -134,21 +166,14 @@ impl MaterialProperties {
         let total_cross_section = self.scattering + self.fission + self.absorption;
         total_cross_section
     }
+
+    /// Returns the total cross-section with the absorption scaled by the part's Doppler factor.
+    pub fn total_cross_section_with_feedback(&self, doppler_factor: f64) -> f64 {
+        self.scattering + self.fission + self.absorption * doppler_factor
+    }
 }
 
-/// Creates and returns a vector with all different materials defined.
+/// Creates and returns a vector with all materials defined in the default material library.
 pub fn get_material_data_vector() -> Vec<MaterialData> {
-    let void = MaterialData::get_void();
-    let h1 = MaterialData::get_h1();
-    let be9 = MaterialData::get_be9();
-    let b10 = MaterialData::get_b10();
-    let o16 = MaterialData::get_o16();
-    let fe54 = MaterialData::get_fe54();
-    let u235 = MaterialData::get_u235();
-    let u238 = MaterialData::get_u238();
-
-    // Setting void as index 0, so the default option.
-    let material_data_vector: Vec<MaterialData> = vec![void, h1, be9, b10, o16, fe54, u235, u238];
-
-    material_data_vector
+    load_material_library(Path::new(DEFAULT_MATERIAL_LIBRARY_PATH))
 }
@@ -109,6 +109,47 @@ impl Neutron {
         // );
     }
 
+    /// Isotropic inelastic scattering of the neutron, which leaves the nucleus excited.
+    /// The new energy is sampled from an evaporation spectrum E' exp(-E'/T), limited to the energy available above the inelastic threshold.
+    /// The nuclear temperature T follows from that available energy, with a level density parameter of A/10 per MeV.
+    pub fn scatter_inelastic(
+        &mut self,
+        atomic_mass: f64,
+        inelastic_threshold: f64,
+        rng: &mut rand::rngs::SmallRng,
+    ) {
+        self.direction = Vec3D::random_unit_vector(rng);
+        self.has_scattered = true;
+
+        let available_energy = self.energy - inelastic_threshold;
+        if available_energy <= 0.0 {
+            return;
+        }
+
+        // Energies in eV, the temperature follows from E = a T^2 with E in MeV.
+        let nuclear_temperature = f64::sqrt(available_energy * 1e-6 / (atomic_mass / 10.0)) * 1e6;
+        let maximum_ratio = available_energy / nuclear_temperature;
+
+        // Sampling x = E'/T from x exp(-x) on [0, maximum_ratio]. Close to the threshold, most of the full spectrum would be rejected, so x is sampled from x instead and accepted with exp(-x).
+        let ratio = if maximum_ratio < 1.0 {
+            loop {
+                let ratio = maximum_ratio * f64::sqrt(1.0 - rng.gen::<f64>());
+                if rng.gen::<f64>() < f64::exp(-ratio) {
+                    break ratio;
+                }
+            }
+        } else {
+            loop {
+                let ratio = -f64::ln((1.0 - rng.gen::<f64>()) * (1.0 - rng.gen::<f64>()));
+                if ratio < maximum_ratio {
+                    break ratio;
+                }
+            }
+        };
+
+        self.energy = ratio * nuclear_temperature;
+    }
+
     /// Translation of the neutron in the current movement direction.
     pub fn translate(&mut self) -> () {
         self.position.x += self.direction.x * self.distance_step;
@@ -133,53 +174,47 @@ impl Neutron {
         }
     }
 
-    /// Interactions between a material instance from ```MaterialProperties``` and the neutron. All interactions (fission, absorption, scattering) are energy-dependent and continuous.
-    pub fn interact(
+    /// Checks whether the neutron is outside the simulation range.
+    pub fn has_escaped(&self, simulation_range_squared: f64) -> bool {
+        self.position.norm_squared() >= simulation_range_squared
+    }
+
+    /// Samples whether the neutron collides within the current step, based on the macroscopic total cross-section at its position.
+    pub fn collides(
         &self,
-        material: &MaterialProperties,
         composition_total_cross_section: f64,
-        simulation_range_squared: f64,
         rng: &mut rand::rngs::SmallRng,
-    ) -> InteractionTypes {
-        if self.position.norm_squared() >= simulation_range_squared {
-            return InteractionTypes::Escaped;
-        }
-
+    ) -> bool {
         let interaction_criterion = rng.gen::<f64>();
         let interaction_probability =
             1.0 - f64::exp(-self.distance_step * composition_total_cross_section);
 
-        // if composition_total_cross_section > 0.0 {
-        //     debug!("Interaction probability: {}", interaction_probability);
-        // }
-
-        if interaction_criterion <= interaction_probability {
-            let interaction_type_criterion = rng.gen::<f64>();
-
-            // Order:
-            // 0 < Fission <= Scattering <= Absorption < 1
-            // Random number here is [0, 1), so exclusive 1. The else-clause is just there because otherwise Rust complains.
-            let fission_probability = material.fission / material.total_cross_section();
-            let scatter_probability = material.scattering / material.total_cross_section();
-            let absorption_probability = material.absorption / material.total_cross_section();
-
-            if interaction_type_criterion >= 0.0 && interaction_type_criterion < fission_probability
-            {
-                return InteractionTypes::Fission;
-            } else if interaction_type_criterion >= fission_probability
-                && interaction_type_criterion < fission_probability + scatter_probability
-            {
-                return InteractionTypes::Scattering;
-            } else if interaction_type_criterion >= fission_probability + scatter_probability
-                && interaction_type_criterion
-                    < fission_probability + scatter_probability + absorption_probability
-            {
-                return InteractionTypes::Absorption;
-            } else {
-                return InteractionTypes::Absorption;
-            }
+        interaction_criterion <= interaction_probability
+    }
+
+    /// Samples the type of interaction once the neutron collides with the given material.
+    pub fn select_interaction(
+        &self,
+        material: &MaterialProperties,
+        doppler_factor: f64,
+        rng: &mut rand::rngs::SmallRng,
+    ) -> InteractionTypes {
+        let interaction_type_criterion = rng.gen::<f64>();
+
+        // Order:
+        // 0 <= Fission < Scattering < Absorption < 1
+        // Absorption, which is scaled by the Doppler factor of the part, takes the remainder of [0, 1).
+        let material_total_cross_section =
+            material.total_cross_section_with_feedback(doppler_factor);
+        let fission_probability = material.fission / material_total_cross_section;
+        let scatter_probability = material.scattering / material_total_cross_section;
+
+        if interaction_type_criterion < fission_probability {
+            InteractionTypes::Fission
+        } else if interaction_type_criterion < fission_probability + scatter_probability {
+            InteractionTypes::Scattering
         } else {
-            return InteractionTypes::None;
+            InteractionTypes::Absorption
         }
     }
 
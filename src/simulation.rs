@@ -7,6 +7,7 @@ use crate::utils::config_loading::SimulationParametersTOML;
 pub mod aggregate_runs;
 pub mod initialization;
 pub mod simulation;
+pub mod thermal_coupling;
 
 pub struct Simulation {
     pub rng: rand::rngs::SmallRng,
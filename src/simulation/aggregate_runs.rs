@@ -1,5 +1,7 @@
 use crate::{
-    diagnostics::{geometry_diagnostics::GeometryDiagnostics, BinData},
+    diagnostics::{
+        geometry_diagnostics::GeometryDiagnostics, multigroup::MultigroupTallies, BinData,
+    },
     utils::vectors::Vec3D,
 };
 
@@ -21,4 +23,5 @@ pub struct AggregateRunResult {
 
     bin_parameters: GeometryDiagnostics,
     convergence_per_generation: Vec<(i64, f64)>,
+    multigroup_tallies: Option<MultigroupTallies>,
 }
@@ -1,3 +1,4 @@
+use crate::diagnostics::multigroup::MultigroupTallies;
 use crate::simulation::aggregate_runs::report_creation::write_aggregate_report;
 use crate::simulation::aggregate_runs::AggregateRunResult;
 use crate::utils::config_loading::Config;
@@ -39,6 +40,21 @@ fn combine_fission_vector_data(simulation_results: &Vec<Simulation>) -> Vec<Vec3
     aggregated_fission_vector
 }
 
+fn combine_multigroup_tallies(simulation_results: &[Simulation]) -> Option<MultigroupTallies> {
+    let mut aggregated_tallies: Option<MultigroupTallies> = None;
+
+    for simulation in simulation_results {
+        if let Some(multigroup_tallies) = &simulation.neutron_diagnostics.multigroup_tallies {
+            match &mut aggregated_tallies {
+                Some(aggregated_tallies) => aggregated_tallies.add(multigroup_tallies),
+                None => aggregated_tallies = Some(multigroup_tallies.clone()),
+            }
+        }
+    }
+
+    aggregated_tallies
+}
+
 pub fn post_process_aggregate_runs(
     config: &Config,
     simulation_results: Vec<Simulation>,
@@ -84,6 +100,7 @@ pub fn post_process_aggregate_runs(
 
     let combined_bins: Vec<BinData> = combine_bin_data(&simulation_results);
     let combined_fission_vector: Vec<Vec3D> = combine_fission_vector_data(&simulation_results);
+    let multigroup_tallies = combine_multigroup_tallies(&simulation_results);
     let bin_parameters = simulation_results[0]
         .neutron_diagnostics
         .bin_parameters
@@ -98,6 +115,7 @@ pub fn post_process_aggregate_runs(
         total_neutrons_tracked,
         bin_parameters,
         convergence_per_generation,
+        multigroup_tallies,
     };
 
     write_aggregate_report(&config, &aggregate_run_result, simulation_time);
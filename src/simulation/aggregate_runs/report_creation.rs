@@ -7,6 +7,7 @@ use std::{
     time::Duration,
 };
 
+use crate::diagnostics::multigroup::write_multigroup_cross_sections;
 use crate::utils::{
     config_loading::Config,
     data_writing::{
@@ -54,6 +55,18 @@ pub fn write_aggregate_report(
         Path::new(&convergence_per_generation_string),
     );
 
+    if let Some(multigroup_tallies) = &aggregate_run_result.multigroup_tallies {
+        let multigroup_directory = format!("{}/multigroup", &dir_path);
+        write_multigroup_cross_sections(
+            &multigroup_tallies.get_cross_sections(),
+            Path::new(&multigroup_directory),
+        );
+        info!(
+            "Multigroup cross-sections written to {}.",
+            multigroup_directory
+        );
+    }
+
     let average_k = aggregate_run_result.averaged_k / aggregate_run_result.simulation_count as f64;
     info!("Average k: {:.3}", average_k);
 
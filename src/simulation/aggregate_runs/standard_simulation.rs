@@ -1,13 +1,15 @@
 use crate::diagnostics::geometry_diagnostics::GeometryDiagnostics;
+use crate::diagnostics::multigroup::MultigroupTallies;
 use crate::diagnostics::NeutronDiagnostics;
 use crate::geometry::components::Components;
-use crate::materials::material_properties::get_material_data_vector;
+use crate::materials::compounds::load_compound_library;
+use crate::materials::data_cache::load_shared_material_library;
 use crate::neutrons::neutron_scheduler::NeutronScheduler;
 use crate::simulation::Simulation;
 use crate::utils::config_loading::GridBinParametersTOML;
 
 use crate::utils::config_loading::load_config;
-use crate::utils::parts_loading::load_geometries;
+use crate::utils::parts_loading::load_parametric_geometry;
 
 use rand::rngs::SmallRng;
 use rand::SeedableRng;
@@ -16,25 +18,47 @@ use std::path::Path;
 use log::info;
 
 pub fn create_simulation() -> Simulation {
+    create_simulation_from_config(Path::new("config/simulation/default.toml"))
+}
+
+/// Creates a simulation from the given config, loading its geometry and material library.
+pub fn create_simulation_from_config(config_path: &Path) -> Simulation {
     // Creating the RNG object.
     let rng = SmallRng::from_entropy();
 
     // Loading config
-    let config = load_config(Path::new("config/simulation/default.toml"));
+    let config = load_config(config_path);
     let simulation_parameters: crate::utils::config_loading::SimulationParametersTOML =
         config.simulation_parameters;
     let neutron_bin_parameters: GridBinParametersTOML = config.neutron_bins;
 
     // Required structs.
-    let material_data_vector = get_material_data_vector();
-    let parts_vector = load_geometries(Path::new(&simulation_parameters.geometries_path));
-    let components: Components = Components::new(material_data_vector, parts_vector);
+    // The material library is only loaded once, and then shared by all simulations.
+    let material_data_vector = load_shared_material_library(
+        Path::new(&simulation_parameters.material_library_path),
+        simulation_parameters
+            .data_cache_path
+            .as_deref()
+            .map(Path::new),
+    );
+    let compounds = load_compound_library(Path::new(&simulation_parameters.compound_library_path));
+    let geometry = load_parametric_geometry(
+        Path::new(&simulation_parameters.geometries_path),
+        &compounds,
+        &simulation_parameters
+            .geometry_parameters
+            .clone()
+            .unwrap_or_default(),
+    );
+    let components: Components = Components::new(material_data_vector, geometry.parts_vector)
+        .with_lattice_index(geometry.lattice_index)
+        .with_boundary(geometry.boundary);
     components.check_material_fractions_sum();
     let neutron_scheduler: NeutronScheduler = NeutronScheduler::default();
 
     let bin_parameters = GeometryDiagnostics::new(neutron_bin_parameters);
 
-    let neutron_diagnostics: NeutronDiagnostics = NeutronDiagnostics::new(
+    let mut neutron_diagnostics: NeutronDiagnostics = NeutronDiagnostics::new(
         simulation_parameters.estimate_k,
         simulation_parameters.track_bins,
         simulation_parameters.track_fission_positions,
@@ -42,6 +66,10 @@ pub fn create_simulation() -> Simulation {
         bin_parameters,
         simulation_parameters.initial_neutron_count,
     );
+    neutron_diagnostics.multigroup_tallies = config
+        .multigroup_parameters
+        .as_ref()
+        .map(MultigroupTallies::from_parameters);
 
     // Instantiating simulation.
     let simulation: Simulation = Simulation {
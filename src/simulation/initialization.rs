@@ -21,7 +21,7 @@ impl Simulation {
             }
 
             // Getting the material.
-            let (material_properties, _) = self
+            let (material_properties, _, _) = self
                 .components
                 .get_material_properties(&mut self.rng, &parent_neutron.position);
 
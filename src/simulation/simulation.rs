@@ -2,6 +2,7 @@ use crate::diagnostics::halt_causes::SimulationHaltCauses;
 use crate::neutrons::neutron_dynamics::InteractionTypes;
 use crate::neutrons::Neutron;
 use crate::simulation::Simulation;
+use rand::Rng;
 
 impl Simulation {
     /// Runs the main simulation loop until certain termination conditions are met.
@@ -60,36 +61,78 @@ impl Simulation {
                     neutron.has_scattered = false;
                 }
 
-                // Getting material properties.
-                // This gives the total cross-section and the selected material, for if the material actually interacts.
-                let (material_properties, composition_total_cross_section) = self
-                    .components
-                    .get_material_properties(&mut self.rng, &neutron.position);
+                // Neutrons either escape, or are sent back inside by the boundary conditions.
+                if self.components.has_escaped(neutron, &mut self.rng) {
+                    self.neutron_scheduler.remove_neutron(0);
+                    // debug!("Escaped");
+                    break;
+                }
 
-                // Interacting with the material.
-                let interaction_type: InteractionTypes = neutron.interact(
-                    &material_properties,
-                    composition_total_cross_section,
-                    self.components.simulation_range_squared,
-                    &mut self.rng,
+                // Getting the total cross-section of the part the neutron is in, which is cached until its energy changes.
+                let part_index = self.components.get_part_index(&neutron.position);
+                let composition_total_cross_section =
+                    self.components.get_part_total_cross_section(part_index);
+
+                self.neutron_diagnostics.track_multigroup_flux(
+                    neutron.generation_number,
+                    part_index,
+                    neutron.energy,
+                    neutron.distance_step,
                 );
 
-                // Responding to the interactions types.
-                if interaction_type == InteractionTypes::None {
+                if !neutron.collides(composition_total_cross_section, &mut self.rng) {
                     continue;
                 }
 
-                if interaction_type == InteractionTypes::Escaped {
-                    self.neutron_scheduler.remove_neutron(0);
-                    // debug!("Escaped");
-                    break;
-                }
+                // The specific isotope is only sampled once a collision actually occurs.
+                let material_index = self.components.select_collision_material(
+                    &mut self.rng,
+                    part_index,
+                    composition_total_cross_section,
+                );
+                let material_properties =
+                    &self.components.cached_material_properties[material_index];
+                let doppler_factor = self.components.get_part_feedback(part_index).doppler_factor;
 
+                // Interacting with the material.
+                let interaction_type: InteractionTypes =
+                    neutron.select_interaction(material_properties, doppler_factor, &mut self.rng);
+
+                self.neutron_diagnostics.track_multigroup_collision(
+                    neutron.generation_number,
+                    part_index,
+                    neutron.energy,
+                    &interaction_type,
+                    material_properties.nu_bar,
+                );
+
+                // Responding to the interactions types.
                 if interaction_type == InteractionTypes::Scattering {
-                    neutron.scatter(
-                        material_properties.atomic_mass,
-                        &mut self.rng,
-                        self.simulation_parameters.maximum_neutron_energy_difference,
+                    let (initial_energy, initial_direction) = (neutron.energy, neutron.direction);
+
+                    // The inelastic part of the scattering is sampled once the neutron scatters.
+                    if self.rng.gen::<f64>() * material_properties.scattering
+                        < material_properties.inelastic
+                    {
+                        neutron.scatter_inelastic(
+                            material_properties.atomic_mass,
+                            material_properties.inelastic_threshold,
+                            &mut self.rng,
+                        );
+                    } else {
+                        neutron.scatter(
+                            material_properties.atomic_mass,
+                            &mut self.rng,
+                            self.simulation_parameters.maximum_neutron_energy_difference,
+                        );
+                    }
+
+                    self.neutron_diagnostics.track_multigroup_scattering(
+                        neutron.generation_number,
+                        part_index,
+                        initial_energy,
+                        neutron.energy,
+                        initial_direction.dot(neutron.direction),
                     );
                     // debug!("Scattering");
                 }
@@ -120,6 +163,13 @@ impl Simulation {
                         &mut self.rng,
                     );
 
+                    self.neutron_diagnostics.track_multigroup_fission_neutrons(
+                        neutron.generation_number,
+                        part_index,
+                        new_neutron.energy,
+                        fission_count,
+                    );
+
                     self.neutron_scheduler.remove_neutron(0);
 
                     for _ in 0..fission_count {
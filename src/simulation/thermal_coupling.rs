@@ -0,0 +1,259 @@
+use crate::heat_diffusion::HeatDiffusion;
+use crate::simulation::aggregate_runs::standard_simulation::create_simulation_from_config;
+use crate::simulation::Simulation;
+use crate::utils::config_loading::{load_config, Config};
+use crate::utils::vectors::Vec3D;
+
+use chrono::{DateTime, Local};
+use log::{error, info, warn};
+use serde::Serialize;
+use std::fs::{create_dir_all, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::thread;
+
+/// Results of a single Picard iteration of the coupled neutronics-thermal loop.
+#[derive(Serialize, Debug, Clone)]
+pub struct PicardIterationData {
+    pub iteration: i64,
+    pub k: f64,
+    pub peak_fuel_temperature: f64,
+    pub power_residual: f64,
+    pub temperature_residual: f64,
+    pub heat_diffusion_converged: bool,
+}
+
+/// Runs the transport simulations in parallel with the given part temperatures applied.
+/// Returns the averaged _k_ and all fission positions, which are used as the heat source, or an error if any of the threads failed.
+fn run_transport(
+    config_path: &Path,
+    config: &Config,
+    part_temperatures: &[f64],
+) -> Result<(f64, Vec<Vec3D>), String> {
+    let mut threads = vec![];
+
+    let number_threads = config.parallelization_parameters.number_threads;
+    let simulations_per_thread = config.parallelization_parameters.simulations_per_thread;
+
+    for _ in 0..number_threads {
+        let part_temperatures = part_temperatures.to_vec();
+        let coupling_parameters = config.coupling_parameters.clone();
+        let config_path: PathBuf = config_path.to_path_buf();
+
+        threads.push(thread::spawn(move || {
+            let mut results: Vec<(f64, Vec<Vec3D>)> = Vec::new();
+            for _ in 0..simulations_per_thread {
+                let mut simulation: Simulation = create_simulation_from_config(&config_path);
+
+                // The fission positions are the heat source, so they always have to be tracked.
+                simulation.simulation_parameters.track_fission_positions = true;
+                simulation.neutron_diagnostics.track_fission_positions = true;
+
+                simulation
+                    .components
+                    .set_part_temperatures(&part_temperatures, &coupling_parameters);
+
+                simulation.run_simulation();
+                simulation
+                    .neutron_diagnostics
+                    .post_process(simulation.simulation_parameters.halt_time);
+
+                results.push((
+                    simulation.neutron_diagnostics.averaged_k,
+                    simulation.neutron_diagnostics.neutron_fission_locations,
+                ));
+            }
+            results
+        }));
+    }
+
+    let mut averaged_k = 0.0;
+    let mut simulation_count = 0;
+    let mut fission_vector: Vec<Vec3D> = Vec::new();
+
+    for thread in threads {
+        match thread.join() {
+            Ok(results) => {
+                for (k, fission_locations) in results {
+                    averaged_k += k;
+                    simulation_count += 1;
+                    fission_vector.extend(fission_locations);
+                }
+            }
+            // A missing run would bias the power distribution, so the iteration cannot continue.
+            Err(_) => {
+                error!("Transport thread failed to complete.");
+                return Err("A transport thread failed to complete.".to_string());
+            }
+        }
+    }
+
+    averaged_k /= simulation_count.max(1) as f64;
+
+    Ok((averaged_k, fission_vector))
+}
+
+/// L1-difference between two distributions after normalizing both to unity, the same measure as used for the convergence analysis.
+fn normalized_distribution_difference(current: &[f64], previous: &[f64]) -> f64 {
+    let current_sum = current.iter().sum::<f64>().max(f64::MIN_POSITIVE);
+    let previous_sum = previous.iter().sum::<f64>().max(f64::MIN_POSITIVE);
+
+    current
+        .iter()
+        .zip(previous.iter())
+        .map(|(current_value, previous_value)| {
+            (current_value / current_sum - previous_value / previous_sum).abs()
+        })
+        .sum()
+}
+
+/// Writes the results per Picard iteration to a CSV.
+fn write_picard_iterations(
+    picard_iteration_data: &[PicardIterationData],
+    dir_path: &Path,
+) -> Result<(), String> {
+    let file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(dir_path.join("picard_iterations.csv"))
+        .map_err(|error| format!("Failed to open Picard iterations file: {}", error))?;
+
+    let mut wtr = csv::Writer::from_writer(file);
+
+    for record in picard_iteration_data {
+        wtr.serialize(record)
+            .map_err(|error| format!("Failed to serialize Picard iteration data: {}", error))?;
+    }
+
+    wtr.flush()
+        .map_err(|error| format!("Failed to flush Picard iterations file: {}", error))
+}
+
+/// Couples the neutron transport and heat diffusion through Picard iterations.
+/// Every iteration runs the transport with the current part temperatures, converts the fissions into a heat source normalized to the specified power, solves the heat diffusion to steady state, and maps the temperatures back onto the parts.
+/// The power distribution is under-relaxed between iterations. The loop halts once both the power distribution and the part temperatures have converged.
+/// Returns the results of all iterations, or an error if a transport run failed or the results could not be written.
+pub fn coupled_runs(config_path: &Path) -> Result<Vec<PicardIterationData>, String> {
+    let config = load_config(config_path);
+    let coupling_parameters = config.coupling_parameters.clone();
+
+    let local_date_time: DateTime<Local> = Local::now();
+    let date_time_string = local_date_time.format("%Y-%m-%d_%H-%M-%S.%f").to_string();
+    let dir_path_string = format!(
+        "results/coupling/{} - {}",
+        config.simulation_parameters.run_name, date_time_string
+    );
+    let dir_path = Path::new(&dir_path_string);
+    create_dir_all(dir_path).map_err(|error| {
+        format!(
+            "Failed to create coupling results directory {}: {}",
+            dir_path.display(),
+            error
+        )
+    })?;
+
+    let mut heat_diffusion = HeatDiffusion::from_simulation(
+        load_config(config_path),
+        create_simulation_from_config(config_path),
+    );
+    heat_diffusion.initialize_grid();
+
+    let part_count = heat_diffusion.get_part_temperatures().len();
+    let mut part_temperatures = vec![coupling_parameters.reference_temperature; part_count];
+    let mut previous_source_array: Option<Vec<f64>> = None;
+
+    let mut picard_iteration_data: Vec<PicardIterationData> = Vec::new();
+
+    for iteration in 0..coupling_parameters.maximum_picard_iterations {
+        let (k, fission_vector) = run_transport(config_path, &config, &part_temperatures)?;
+
+        let current_source_array = heat_diffusion.bin_fission_source(&fission_vector);
+
+        let (source_array, power_residual) = match &previous_source_array {
+            Some(previous_source_array) => {
+                let power_residual = normalized_distribution_difference(
+                    &current_source_array,
+                    previous_source_array,
+                );
+
+                // Normalizing both before relaxing, as the number of fissions differs per transport run.
+                let current_sum = current_source_array
+                    .iter()
+                    .sum::<f64>()
+                    .max(f64::MIN_POSITIVE);
+                let previous_sum = previous_source_array
+                    .iter()
+                    .sum::<f64>()
+                    .max(f64::MIN_POSITIVE);
+
+                let relaxed_source_array: Vec<f64> = current_source_array
+                    .iter()
+                    .zip(previous_source_array.iter())
+                    .map(|(current_value, previous_value)| {
+                        coupling_parameters.relaxation_factor * current_value / current_sum
+                            + (1.0 - coupling_parameters.relaxation_factor) * previous_value
+                                / previous_sum
+                    })
+                    .collect();
+
+                (relaxed_source_array, power_residual)
+            }
+            None => (current_source_array, f64::INFINITY),
+        };
+
+        heat_diffusion
+            .set_normalized_fission_source(source_array.clone(), coupling_parameters.total_power);
+        let heat_diffusion_converged =
+            heat_diffusion.solve_steady_state(coupling_parameters.steady_state_tolerance);
+
+        // Parts outside the heat diffusion grid keep their previous temperature.
+        let mut temperature_residual: f64 = 0.0;
+        for (part_temperature, new_temperature) in part_temperatures
+            .iter_mut()
+            .zip(heat_diffusion.get_part_temperatures())
+        {
+            if let Some(new_temperature) = new_temperature {
+                temperature_residual =
+                    temperature_residual.max((new_temperature - *part_temperature).abs());
+                *part_temperature = new_temperature;
+            }
+        }
+
+        let peak_fuel_temperature = heat_diffusion.get_peak_fuel_temperature();
+
+        info!(
+            "Picard iteration {}: k = {:.5}, peak fuel temperature = {:.2} K, power residual = {:.3e}, temperature residual = {:.3} K.",
+            iteration, k, peak_fuel_temperature, power_residual, temperature_residual
+        );
+
+        picard_iteration_data.push(PicardIterationData {
+            iteration,
+            k,
+            peak_fuel_temperature,
+            power_residual,
+            temperature_residual,
+            heat_diffusion_converged,
+        });
+
+        write_picard_iterations(&picard_iteration_data, dir_path)?;
+
+        previous_source_array = Some(source_array);
+
+        if power_residual < coupling_parameters.power_tolerance
+            && temperature_residual < coupling_parameters.temperature_tolerance
+        {
+            info!(
+                "Coupled solution converged after {} iterations.",
+                iteration + 1
+            );
+            return Ok(picard_iteration_data);
+        }
+    }
+
+    warn!(
+        "Coupled solution did not converge within {} Picard iterations.",
+        coupling_parameters.maximum_picard_iterations
+    );
+
+    Ok(picard_iteration_data)
+}
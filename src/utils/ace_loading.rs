@@ -0,0 +1,493 @@
+use crate::materials::material_data::MaterialData;
+use crate::utils::data_handling::InterpolationLaw;
+use crate::utils::endf_loading::{EndfTable, NEUTRON_MASS_AMU};
+
+use log::{debug, warn};
+use std::fs;
+use std::path::Path;
+
+/// Number of (IZ, AW) pairs in the header of an ACE table.
+const IZAW_PAIR_COUNT: usize = 16;
+/// Length of the NXS array.
+const NXS_LENGTH: usize = 16;
+/// Length of the JXS array.
+const JXS_LENGTH: usize = 32;
+/// ACE tables use MeV, while the cross-section data is stored in eV.
+const EV_PER_MEV: f64 = 1e6;
+/// Partial fission reactions, used if the table does not contain the total fission MT18.
+const PARTIAL_FISSION_MTS: [u32; 4] = [19, 20, 21, 38];
+
+/// A continuous-energy neutron ACE table in the ASCII (type 1) format.
+/// The XSS array is accessed with the one-based locators from the JXS array, as in the MCNP manual.
+pub struct AceTable {
+    pub zaid: String,
+    pub atomic_weight_ratio: f64,
+    pub temperature: f64,
+    nxs: Vec<i64>,
+    jxs: Vec<i64>,
+    xss: Vec<f64>,
+}
+
+impl AceTable {
+    /// Parses the first table in an ACE file. Both the legacy header and the 2.0 header are supported.
+    pub fn from_string(ace_string: &str) -> AceTable {
+        let mut lines = ace_string.lines();
+
+        let mut header_line = lines.next().expect("ACE file is empty.");
+
+        // The 2.0 header has two lines plus a number of comment lines before the legacy header.
+        if header_line.trim_start().starts_with("2.0") {
+            let comment_line_count = lines
+                .next()
+                .and_then(|line| line.split_whitespace().nth(3))
+                .and_then(|word| word.parse::<usize>().ok())
+                .expect("Failed to read the number of comment lines in the ACE 2.0 header.");
+            for _ in 0..comment_line_count {
+                lines.next();
+            }
+            header_line = lines.next().expect("ACE file ends after the header.");
+        }
+
+        let mut header_words = header_line.split_whitespace();
+        let zaid = header_words
+            .next()
+            .expect("ACE header is missing the ZAID.")
+            .to_string();
+        let atomic_weight_ratio = header_words
+            .next()
+            .and_then(|word| word.parse::<f64>().ok())
+            .expect("ACE header is missing the atomic weight ratio.");
+        let temperature = header_words
+            .next()
+            .and_then(|word| word.parse::<f64>().ok())
+            .expect("ACE header is missing the temperature.");
+
+        // Comment and material identifier.
+        lines.next();
+
+        let mut values = lines.flat_map(|line| line.split_whitespace());
+
+        // The IZAW pairs are only used for thermal scattering tables.
+        for _ in 0..2 * IZAW_PAIR_COUNT {
+            values.next();
+        }
+
+        let mut read_integer = || {
+            values
+                .next()
+                .and_then(|word| word.parse::<i64>().ok())
+                .expect("Failed to read the ACE NXS/JXS arrays.")
+        };
+        let nxs: Vec<i64> = (0..NXS_LENGTH).map(|_| read_integer()).collect();
+        let jxs: Vec<i64> = (0..JXS_LENGTH).map(|_| read_integer()).collect();
+
+        // Only reading the length of this table, as files can contain multiple tables.
+        let xss: Vec<f64> = values
+            .take(nxs[0] as usize)
+            .map(|word| {
+                word.parse::<f64>()
+                    .unwrap_or_else(|_| panic!("Failed to parse ACE value: {:?}", word))
+            })
+            .collect();
+
+        assert_eq!(
+            xss.len(),
+            nxs[0] as usize,
+            "ACE table {} is shorter than specified in NXS(1).",
+            zaid
+        );
+
+        debug!("Read ACE table {} with {} values.", zaid, xss.len());
+
+        AceTable {
+            zaid,
+            atomic_weight_ratio,
+            temperature,
+            nxs,
+            jxs,
+            xss,
+        }
+    }
+
+    pub fn from_file(ace_path: &Path) -> AceTable {
+        let ace_string = fs::read_to_string(ace_path)
+            .unwrap_or_else(|_| panic!("Failed to read ACE file: {:?}", ace_path));
+        AceTable::from_string(&ace_string)
+    }
+
+    /// One-based NXS entry.
+    fn nxs(&self, index: usize) -> i64 {
+        self.nxs[index - 1]
+    }
+
+    /// One-based JXS entry.
+    fn jxs(&self, index: usize) -> usize {
+        self.jxs[index - 1] as usize
+    }
+
+    /// One-based XSS entry.
+    fn xss(&self, index: usize) -> f64 {
+        self.xss[index - 1]
+    }
+
+    fn xss_slice(&self, start_index: usize, length: usize) -> &[f64] {
+        &self.xss[start_index - 1..start_index - 1 + length]
+    }
+
+    /// Number of energies on the main grid.
+    fn energy_count(&self) -> usize {
+        self.nxs(3) as usize
+    }
+
+    /// The main energy grid in MeV.
+    pub fn energies(&self) -> &[f64] {
+        self.xss_slice(self.jxs(1), self.energy_count())
+    }
+
+    pub fn total_cross_sections(&self) -> &[f64] {
+        self.xss_slice(self.jxs(1) + self.energy_count(), self.energy_count())
+    }
+
+    /// The disappearance cross-section, which is the sum of MT102-117.
+    pub fn absorption_cross_sections(&self) -> &[f64] {
+        self.xss_slice(self.jxs(1) + 2 * self.energy_count(), self.energy_count())
+    }
+
+    pub fn elastic_cross_sections(&self) -> &[f64] {
+        self.xss_slice(self.jxs(1) + 3 * self.energy_count(), self.energy_count())
+    }
+
+    /// MT numbers of all reactions apart from elastic scattering.
+    pub fn reaction_mts(&self) -> Vec<u32> {
+        let reaction_count = self.nxs(4) as usize;
+        self.xss_slice(self.jxs(3), reaction_count)
+            .iter()
+            .map(|&mt| mt as u32)
+            .collect()
+    }
+
+    /// Cross-section of a reaction on the main energy grid, zero below its threshold.
+    pub fn reaction_cross_sections(&self, mt: u32) -> Option<Vec<f64>> {
+        let reaction_index = self
+            .reaction_mts()
+            .iter()
+            .position(|&reaction_mt| reaction_mt == mt)?;
+
+        let locator = self.xss(self.jxs(6) + reaction_index) as usize;
+        let block_index = self.jxs(7) + locator - 1;
+        let first_energy_index = self.xss(block_index) as usize;
+        let reaction_energy_count = self.xss(block_index + 1) as usize;
+
+        let mut cross_sections = vec![0.0; self.energy_count()];
+        cross_sections[first_energy_index - 1..first_energy_index - 1 + reaction_energy_count]
+            .copy_from_slice(self.xss_slice(block_index + 2, reaction_energy_count));
+
+        Some(cross_sections)
+    }
+
+    /// Fission cross-section from MT18, or as the sum of the partial fission reactions.
+    pub fn fission_cross_sections(&self) -> Option<Vec<f64>> {
+        if let Some(fission_cross_sections) = self.reaction_cross_sections(18) {
+            return Some(fission_cross_sections);
+        }
+
+        PARTIAL_FISSION_MTS
+            .iter()
+            .filter_map(|&mt| self.reaction_cross_sections(mt))
+            .reduce(|summed, partial| {
+                summed
+                    .iter()
+                    .zip(partial.iter())
+                    .map(|(summed_value, partial_value)| summed_value + partial_value)
+                    .collect()
+            })
+    }
+
+    /// Reads an interpolation table (NR, NBT, INT, NE, x, y) starting at an XSS index, returning it and the index after it.
+    fn read_table(&self, start_index: usize) -> (EndfTable, usize) {
+        let range_count = self.xss(start_index) as usize;
+        let interpolation_ranges = if range_count == 0 {
+            vec![]
+        } else {
+            self.xss_slice(start_index + 1, range_count)
+                .iter()
+                .zip(self.xss_slice(start_index + 1 + range_count, range_count))
+                .map(|(&last_point, &code)| {
+                    (
+                        last_point as usize,
+                        InterpolationLaw::from_code(code as i64),
+                    )
+                })
+                .collect()
+        };
+
+        let point_index = start_index + 1 + 2 * range_count;
+        let point_count = self.xss(point_index) as usize;
+        let x = self.xss_slice(point_index + 1, point_count).to_vec();
+        let y = self
+            .xss_slice(point_index + 1 + point_count, point_count)
+            .to_vec();
+
+        let table = EndfTable {
+            interpolation_ranges,
+            x,
+            y,
+            ..Default::default()
+        };
+
+        (table, point_index + 1 + 2 * point_count)
+    }
+
+    /// Total ν̄ with energies in MeV, which is either a polynomial (LNU = 1) or tabulated (LNU = 2).
+    pub fn nu_bar(&self) -> Option<EndfTable> {
+        let nu_index = self.jxs(2);
+        if nu_index == 0 {
+            return None;
+        }
+
+        // A negative first value means both prompt and total ν̄ are given, with the total second.
+        let total_nu_index = if self.xss(nu_index) < 0.0 {
+            nu_index + self.xss(nu_index).abs() as usize + 1
+        } else {
+            nu_index
+        };
+
+        match self.xss(total_nu_index) as i64 {
+            1 => {
+                let coefficient_count = self.xss(total_nu_index + 1) as usize;
+                let coefficients = self.xss_slice(total_nu_index + 2, coefficient_count);
+
+                let mut energies: Vec<f64> = (0..=70)
+                    .map(|index| 1e-11 * 10f64.powf(index as f64 / 10.0))
+                    .collect();
+                energies.push(self.energies()[self.energy_count() - 1].max(1e-4));
+                energies.dedup_by(|current, previous| current <= previous);
+
+                let nu_bar = energies
+                    .iter()
+                    .map(|energy| {
+                        coefficients
+                            .iter()
+                            .enumerate()
+                            .map(|(power, coefficient)| coefficient * energy.powi(power as i32))
+                            .sum()
+                    })
+                    .collect();
+
+                Some(EndfTable {
+                    x: energies,
+                    y: nu_bar,
+                    ..Default::default()
+                })
+            }
+            2 => Some(self.read_table(total_nu_index + 1).0),
+            nu_representation => {
+                warn!(
+                    "Unsupported ν̄ representation LNU = {} in ACE table {}.",
+                    nu_representation, self.zaid
+                );
+                None
+            }
+        }
+    }
+
+    /// Mean outgoing energy of a tabular (law 4) distribution, integrating the PDF with its interpolation scheme.
+    fn tabular_mean_energy(&self, distribution_index: usize) -> f64 {
+        let interpolation = self.xss(distribution_index) as i64 % 10;
+        let point_count = self.xss(distribution_index + 1) as usize;
+        let outgoing_energies = self.xss_slice(distribution_index + 2, point_count);
+        let probabilities = self.xss_slice(distribution_index + 2 + point_count, point_count);
+
+        let mut mean_energy = 0.0;
+        let mut total_probability = 0.0;
+
+        for index in 0..point_count.saturating_sub(1) {
+            let (left_energy, right_energy) =
+                (outgoing_energies[index], outgoing_energies[index + 1]);
+            let (left_probability, right_probability) =
+                (probabilities[index], probabilities[index + 1]);
+            let width = right_energy - left_energy;
+
+            if interpolation == 1 {
+                mean_energy +=
+                    left_probability * (right_energy.powi(2) - left_energy.powi(2)) / 2.0;
+                total_probability += left_probability * width;
+            } else {
+                // Exact integral of E·p(E) for a linear p(E).
+                let slope = (right_probability - left_probability) / width;
+                let intercept = left_probability - slope * left_energy;
+                mean_energy += intercept * (right_energy.powi(2) - left_energy.powi(2)) / 2.0
+                    + slope * (right_energy.powi(3) - left_energy.powi(3)) / 3.0;
+                total_probability += (left_probability + right_probability) * width / 2.0;
+            }
+        }
+
+        mean_energy / total_probability.max(f64::MIN_POSITIVE)
+    }
+
+    /// Fission spectrum as Watt parameters, with energies and _a_ in MeV and _b_ in 1/MeV, as used by ```rejection_sample_watt```.
+    /// Watt spectra (law 11) are used directly. Maxwellian and evaporation spectra (laws 7 and 9) are represented by a Watt spectrum with a vanishing _b_.
+    /// Tabular spectra (law 4) are represented by the Maxwellian with the same mean energy, θ = 2/3 ⟨E⟩, as the sampling only supports Watt spectra.
+    pub fn watt_parameters(&self) -> Option<(Vec<f64>, Vec<f64>, Vec<f64>)> {
+        let secondary_reaction_count = self.nxs(5) as usize;
+        let fission_index = self
+            .reaction_mts()
+            .iter()
+            .take(secondary_reaction_count)
+            .position(|&mt| mt == 18 || PARTIAL_FISSION_MTS.contains(&mt))?;
+
+        let distribution_locator_index = self.jxs(10);
+        let distribution_index = self.jxs(11);
+
+        let law_index =
+            distribution_index + self.xss(distribution_locator_index + fission_index) as usize - 1;
+        let law = self.xss(law_index + 1) as i64;
+        let law_data_index = distribution_index + self.xss(law_index + 2) as usize - 1;
+
+        // Small enough that sinh(√(bE)) ≈ √(bE) over the full energy range.
+        let maxwellian_b = 1e-6;
+
+        match law {
+            11 => {
+                let (a_table, b_table_index) = self.read_table(law_data_index);
+                let (b_table, _) = self.read_table(b_table_index);
+
+                let mut energies: Vec<f64> =
+                    a_table.x.iter().chain(b_table.x.iter()).copied().collect();
+                energies
+                    .sort_by(|left, right| left.partial_cmp(right).expect("NaN in ACE energies."));
+                energies.dedup();
+
+                let a_values = energies
+                    .iter()
+                    .map(|&energy| a_table.evaluate(energy))
+                    .collect();
+                let b_values = energies
+                    .iter()
+                    .map(|&energy| b_table.evaluate(energy))
+                    .collect();
+
+                Some((energies, a_values, b_values))
+            }
+            7 | 9 => {
+                let (temperature_table, _) = self.read_table(law_data_index);
+                let b_values = vec![maxwellian_b; temperature_table.x.len()];
+                Some((temperature_table.x, temperature_table.y, b_values))
+            }
+            4 => {
+                // Incident energy interpolation ranges, followed by the incident energies and distribution locators.
+                let range_count = self.xss(law_data_index) as usize;
+                let energy_count_index = law_data_index + 1 + 2 * range_count;
+                let energy_count = self.xss(energy_count_index) as usize;
+
+                let energies = self
+                    .xss_slice(energy_count_index + 1, energy_count)
+                    .to_vec();
+                let a_values = self
+                    .xss_slice(energy_count_index + 1 + energy_count, energy_count)
+                    .iter()
+                    .map(|&locator| {
+                        2.0 / 3.0
+                            * self.tabular_mean_energy(distribution_index + locator as usize - 1)
+                    })
+                    .collect();
+
+                Some((energies, a_values, vec![maxwellian_b; energy_count]))
+            }
+            _ => {
+                warn!(
+                    "Unsupported fission spectrum law {} in ACE table {}.",
+                    law, self.zaid
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Scales the energies from MeV to eV, as used for all the cross-section data.
+fn to_cross_section_data(energies: &[f64], cross_sections: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    (
+        energies.iter().map(|energy| energy * EV_PER_MEV).collect(),
+        cross_sections.to_vec(),
+    )
+}
+
+impl MaterialData {
+    /// Creates the material data from an ACE table.
+    /// Elastic, absorption and fission are taken directly from the table. Inelastic and (n,xn) reactions are added to the scattering, which keeps the total cross-section of the table, and are kept as its inelastic part.
+    /// Scattering stays isotropic, so angular distributions are not read. The number density and thermal properties are not part of the table, so they are left at zero to be set by the caller.
+    pub fn from_ace(ace_table: &AceTable, name: &str) -> MaterialData {
+        let energies = ace_table.energies();
+        let fission_cross_sections_option = ace_table.fission_cross_sections();
+        let fissionable = fission_cross_sections_option.is_some();
+        let fission_cross_sections =
+            fission_cross_sections_option.unwrap_or_else(|| vec![0.0; energies.len()]);
+
+        let scattering_cross_sections: Vec<f64> = ace_table
+            .total_cross_sections()
+            .iter()
+            .zip(ace_table.absorption_cross_sections())
+            .zip(fission_cross_sections.iter())
+            .map(|((total, absorption), fission)| (total - absorption - fission).max(0.0))
+            .collect();
+
+        let inelastic_cross_sections: Vec<f64> = scattering_cross_sections
+            .iter()
+            .zip(ace_table.elastic_cross_sections())
+            .map(|(scattering, elastic)| (scattering - elastic).max(0.0))
+            .collect();
+
+        let (energy_scattering_cross_sections, elastic_cross_sections) =
+            to_cross_section_data(energies, &scattering_cross_sections);
+        let (energy_inelastic_cross_sections, inelastic_cross_sections) =
+            to_cross_section_data(energies, &inelastic_cross_sections);
+        let (energy_absorption_cross_sections, absorption_cross_sections) =
+            to_cross_section_data(energies, ace_table.absorption_cross_sections());
+        let (energy_fission_cross_sections, fission_cross_sections) = if fissionable {
+            to_cross_section_data(energies, &fission_cross_sections)
+        } else {
+            (vec![0.0], vec![0.0])
+        };
+
+        let (energy_nu_bar, nu_bar) = match ace_table.nu_bar() {
+            Some(nu_bar_table) => {
+                let (nu_bar_energies, nu_bar) = nu_bar_table.linearise();
+                to_cross_section_data(&nu_bar_energies, &nu_bar)
+            }
+            None => (vec![0.0], vec![0.0]),
+        };
+
+        let (energy_watt_parameters, watt_parameters_a, watt_parameters_b) = ace_table
+            .watt_parameters()
+            .unwrap_or_else(|| (vec![0.0], vec![0.0], vec![0.0]));
+
+        MaterialData {
+            energy_fission_cross_sections,
+            fission_cross_sections,
+
+            energy_scattering_cross_sections,
+            elastic_cross_sections,
+
+            energy_inelastic_cross_sections,
+            inelastic_cross_sections,
+
+            energy_absorption_cross_sections,
+            absorption_cross_sections,
+
+            energy_nu_bar,
+            nu_bar,
+
+            energy_watt_parameters,
+            watt_parameters_a,
+            watt_parameters_b,
+
+            atomic_mass: ace_table.atomic_weight_ratio * NEUTRON_MASS_AMU,
+
+            name: name.to_string(),
+            fissionable,
+
+            ..Default::default()
+        }
+    }
+}
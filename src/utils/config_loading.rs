@@ -1,5 +1,6 @@
 use crate::utils::vectors::Vec3D;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 use toml;
@@ -10,9 +11,11 @@ pub struct Config {
     pub parallelization_parameters: ParallelizationParametersTOML,
     pub simulation_parameters: SimulationParametersTOML,
     pub heat_diffusion_parameters: HeatDiffusionParametersTOML,
+    pub coupling_parameters: CouplingParametersTOML,
     pub neutron_bins: GridBinParametersTOML,
     pub geometry_plot_bins: GridBinParametersTOML,
     pub heat_diffusion_bins: GridBinParametersTOML,
+    pub multigroup_parameters: Option<MultigroupParametersTOML>,
 }
 
 /// Parameters for multithreading
@@ -41,7 +44,12 @@ pub struct SimulationParametersTOML {
     pub halt_time: Option<f64>,
     pub maximum_neutron_energy_difference: f64,
     pub geometries_path: String,
+    pub geometry_parameters: Option<HashMap<String, f64>>,
+    pub material_library_path: String,
+    pub compound_library_path: String,
+    pub data_cache_path: Option<String>,
     pub model_heat_diffusion: bool,
+    pub model_thermal_coupling: bool,
     pub calculate_convergence: bool,
     pub convergence_analysis_period: i64,
     pub minimum_convergence_level: f64,
@@ -62,6 +70,27 @@ pub struct HeatDiffusionParametersTOML {
     pub save_files: bool,
 }
 
+/// Parameters for the coupled neutronics-thermal Picard iterations.
+#[derive(Deserialize, Clone)]
+pub struct CouplingParametersTOML {
+    pub maximum_picard_iterations: i64,
+    pub total_power: f64,
+    pub relaxation_factor: f64,
+    pub power_tolerance: f64,
+    pub temperature_tolerance: f64,
+    pub steady_state_tolerance: f64,
+    pub reference_temperature: f64,
+    pub thermal_expansion_coefficient: f64,
+    pub doppler_coefficient: f64,
+}
+
+/// Parameters for generating multigroup cross-sections over selected parts.
+#[derive(Deserialize, Clone)]
+pub struct MultigroupParametersTOML {
+    pub group_boundaries: Vec<f64>,
+    pub part_indices: Vec<usize>,
+}
+
 /// Parameters for the bins for neutron behavior, plotting, and heat diffusion.
 #[derive(Deserialize, Copy, Clone)]
 pub struct GridBinParametersTOML {
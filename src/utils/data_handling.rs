@@ -1,4 +1,5 @@
 use log::warn;
+use serde::{Deserialize, Serialize};
 
 /// Struct used for binary search. Contains information on the value obtained and the convergence.
 pub struct BinarySearchResult {
@@ -60,6 +61,143 @@ pub fn binary_search(input_vector: &Vec<f64>, target_value: f64) -> Option<Binar
     None
 }
 
+/// Interpolation laws for tabulated data, numbered and named as in the ENDF-6 manual.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InterpolationLaw {
+    Histogram,
+    #[default]
+    LinLin,
+    LinLog,
+    LogLin,
+    LogLog,
+}
+
+impl InterpolationLaw {
+    /// Converts the ENDF-6 INT code, which ACE tables share.
+    pub fn from_code(code: i64) -> InterpolationLaw {
+        match code {
+            1 => InterpolationLaw::Histogram,
+            2 => InterpolationLaw::LinLin,
+            3 => InterpolationLaw::LinLog,
+            4 => InterpolationLaw::LogLin,
+            5 => InterpolationLaw::LogLog,
+            _ => {
+                warn!(
+                    "Unsupported ENDF interpolation law {} - using lin-lin instead.",
+                    code
+                );
+                InterpolationLaw::LinLin
+            }
+        }
+    }
+
+    /// Interpolates between two points with this law.
+    pub fn interpolate(&self, left_x: f64, right_x: f64, left_y: f64, right_y: f64, x: f64) -> f64 {
+        // Logarithmic laws are undefined for non-positive values, where we fall back to lin-lin.
+        let log_x_valid = left_x > 0.0 && right_x > 0.0 && x > 0.0;
+        let log_y_valid = left_y > 0.0 && right_y > 0.0;
+
+        match self {
+            InterpolationLaw::Histogram => left_y,
+            InterpolationLaw::LinLog if log_x_valid => {
+                left_y + (right_y - left_y) * (x / left_x).ln() / (right_x / left_x).ln()
+            }
+            InterpolationLaw::LogLin if log_y_valid => {
+                left_y * (right_y / left_y).powf((x - left_x) / (right_x - left_x))
+            }
+            InterpolationLaw::LogLog if log_x_valid && log_y_valid => {
+                left_y * (right_y / left_y).powf((x / left_x).ln() / (right_x / left_x).ln())
+            }
+            _ => interpolate_values(left_x, right_x, left_y, right_y, x),
+        }
+    }
+}
+
+/// Behaviour of a table outside its tabulated range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutOfRange {
+    /// Returns the value at the nearest end of the table.
+    #[default]
+    Clamp,
+    /// Extends the first or last interval with the table's interpolation law.
+    Extrapolate,
+    /// Returns 0, e.g. for reactions with a threshold.
+    Zero,
+    /// Panics, for data that has to cover all energies that occur.
+    Error,
+}
+
+/// The interpolation law and out-of-range behaviour of a table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct TableScheme {
+    #[serde(default)]
+    pub interpolation: InterpolationLaw,
+    #[serde(default)]
+    pub out_of_range: OutOfRange,
+}
+
+/// Evaluates a table with its scheme, given the index of the last point at or below ```target_x``` (0 below the table).
+/// Tables with a single point contain no data, e.g. for reactions that do not occur, and always give 0.
+pub fn evaluate_table(
+    x_vector: &[f64],
+    y_vector: &[f64],
+    table_scheme: &TableScheme,
+    index: usize,
+    target_x: f64,
+) -> f64 {
+    if y_vector.len() == 1 {
+        return 0.0;
+    }
+
+    let last_index = x_vector.len() - 1;
+
+    if target_x < x_vector[0] || target_x > x_vector[last_index] {
+        match table_scheme.out_of_range {
+            OutOfRange::Clamp if target_x < x_vector[0] => return y_vector[0],
+            OutOfRange::Clamp => return y_vector[last_index],
+            OutOfRange::Zero => return 0.0,
+            OutOfRange::Error => panic!(
+                "Value {} is outside the tabulated range from {} to {}.",
+                target_x, x_vector[0], x_vector[last_index]
+            ),
+            OutOfRange::Extrapolate => {}
+        }
+    }
+
+    let index = index.min(last_index - 1);
+
+    if x_vector[index] == target_x {
+        return y_vector[index];
+    }
+    if x_vector[index + 1] == target_x {
+        return y_vector[index + 1];
+    }
+
+    table_scheme.interpolation.interpolate(
+        x_vector[index],
+        x_vector[index + 1],
+        y_vector[index],
+        y_vector[index + 1],
+        target_x,
+    )
+}
+
+/// Evaluates a table with its interpolation law and out-of-range behaviour, using binary search.
+pub fn interpolate_table(
+    x_vector: &[f64],
+    y_vector: &[f64],
+    table_scheme: &TableScheme,
+    target_x: f64,
+) -> f64 {
+    let index = x_vector
+        .partition_point(|&x| x <= target_x)
+        .saturating_sub(1);
+
+    evaluate_table(x_vector, y_vector, table_scheme, index, target_x)
+}
+
 /// Interpolates values for the linear interpolation function.
 pub fn interpolate_values(
     left_x: f64,
@@ -74,10 +212,10 @@ pub fn interpolate_values(
     target_y
 }
 
-/// Linearly interpolates two vectors using binary search.
+/// Linearly interpolates two vectors using binary search. Outside the table, the nearest end value is returned.
 pub fn linear_interpolation(
     x_vector: &Vec<f64>,
-    y_vector: &Vec<f64>,
+    y_vector: &[f64],
     target_x: f64,
 ) -> (f64, BinarySearchResult) {
     // Skipping the binary search in case there is no data.
@@ -93,54 +231,94 @@ pub fn linear_interpolation(
     }
 
     let binary_search_result =
-        binary_search(&x_vector, target_x).expect("Binary search failed to converge.");
-
-    if binary_search_result.is_exact {
-        return (y_vector[binary_search_result.index], binary_search_result);
-    }
-
-    if binary_search_result.index == x_vector.len() - 1 {
-        warn!("Value is outside range - returning maximum value.");
-        return (y_vector[binary_search_result.index], binary_search_result);
-    }
+        binary_search(x_vector, target_x).expect("Binary search failed to converge.");
 
-    let target_index = binary_search_result.index;
-
-    let left_x = x_vector[target_index];
-    let right_x = x_vector[target_index + 1];
-
-    let left_y = y_vector[target_index];
-    let right_y = y_vector[target_index + 1];
-
-    let target_y = interpolate_values(left_x, right_x, left_y, right_y, target_x);
+    let target_y = evaluate_table(
+        x_vector,
+        y_vector,
+        &TableScheme::default(),
+        binary_search_result.index,
+        target_x,
+    );
     (target_y, binary_search_result)
 }
 
-/// Returns Watt parameters through linear interpolation. Finds the index of the _a_ parameter and uses that to also get the _b_ parameter without running linear interpolation twice.
+/// Returns Watt parameters through linear interpolation, clamped outside the table.
 pub fn get_watt_parameters(
-    energy_vector: &Vec<f64>,
-    a_vector: &Vec<f64>,
-    b_vector: &Vec<f64>,
+    energy_vector: &[f64],
+    a_vector: &[f64],
+    b_vector: &[f64],
     energy: f64,
 ) -> (f64, f64) {
-    let mev_energy = energy / 1e6;
-
-    let (a_value, binary_search_result) =
-        linear_interpolation(&energy_vector, &a_vector, mev_energy);
-
-    let target_index = binary_search_result.index;
+    get_watt_parameters_with_scheme(
+        energy_vector,
+        a_vector,
+        b_vector,
+        &TableScheme::default(),
+        energy,
+    )
+}
 
-    if target_index + 1 >= energy_vector.len() {
-        return (a_value, b_vector[target_index]);
+/// Returns Watt parameters with the given table scheme. The energy is in eV, while the Watt parameter tables are in MeV.
+pub fn get_watt_parameters_with_scheme(
+    energy_vector: &[f64],
+    a_vector: &[f64],
+    b_vector: &[f64],
+    table_scheme: &TableScheme,
+    energy: f64,
+) -> (f64, f64) {
+    // Tables without data still give their b value, which is used as the placeholder.
+    if a_vector.len() == 1 {
+        return (0.0, b_vector[0]);
     }
 
-    let left_x = energy_vector[target_index];
-    let right_x = energy_vector[target_index + 1];
+    let mev_energy = energy / 1e6;
+    let index = energy_vector
+        .partition_point(|&x| x <= mev_energy)
+        .saturating_sub(1);
+
+    (
+        evaluate_table(energy_vector, a_vector, table_scheme, index, mev_energy),
+        evaluate_table(energy_vector, b_vector, table_scheme, index, mev_energy),
+    )
+}
 
-    let left_b = b_vector[target_index];
-    let right_b = b_vector[target_index + 1];
+/// Sums weighted, linearly interpolated tables on the union of their energy grids.
+/// Each table is taken to be zero outside its tabulated range, e.g. below a threshold.
+pub fn sum_on_union_grid(tables: &[(&[f64], &[f64], f64)]) -> (Vec<f64>, Vec<f64>) {
+    let mut energies: Vec<f64> = tables
+        .iter()
+        .flat_map(|(table_energies, _, _)| table_energies.iter().copied())
+        .collect();
+    energies.sort_by(|left, right| left.partial_cmp(right).expect("NaN in energy grid."));
+    energies.dedup();
 
-    let b_value = interpolate_values(left_x, right_x, left_b, right_b, mev_energy);
+    let values = energies
+        .iter()
+        .map(|&energy| {
+            tables
+                .iter()
+                .map(|(table_energies, table_values, weight)| {
+                    let last_index = table_energies.len() - 1;
+                    if energy < table_energies[0] || energy > table_energies[last_index] {
+                        return 0.0;
+                    }
+                    let index = table_energies.partition_point(|&x| x <= energy) - 1;
+                    if index == last_index {
+                        return weight * table_values[last_index];
+                    }
+                    weight
+                        * interpolate_values(
+                            table_energies[index],
+                            table_energies[index + 1],
+                            table_values[index],
+                            table_values[index + 1],
+                            energy,
+                        )
+                })
+                .sum()
+        })
+        .collect();
 
-    (a_value, b_value)
+    (energies, values)
 }
@@ -1,9 +1,10 @@
 use crate::diagnostics::BinData;
+use crate::utils::data_handling::TableScheme;
 use crate::utils::vectors::Vec3D;
 use csv::ReaderBuilder;
 use serde::Deserialize;
 use std::fs::{self, File};
-use std::io::Read;
+use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
 
 /// Data for the Watt parameters: the energy of the neutron, and the _a_ and _b_ values.
@@ -24,7 +25,9 @@ struct CrossSectionData {
 /// Loading Watt parameters from a specified file path.
 pub fn load_watt_parameters(file_path: &Path) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
     let csv_data = fs::read_to_string(file_path).expect("Should have been able to read the file");
-    let mut reader = ReaderBuilder::new().from_reader(csv_data.as_bytes());
+    let mut reader = ReaderBuilder::new()
+        .comment(Some(b'#'))
+        .from_reader(csv_data.as_bytes());
 
     let mut energy_vector = Vec::<f64>::new();
     let mut a_vector = Vec::<f64>::new();
@@ -40,13 +43,40 @@ pub fn load_watt_parameters(file_path: &Path) -> (Vec<f64>, Vec<f64>, Vec<f64>)
     (energy_vector, a_vector, b_vector)
 }
 
+/// Data on a temperature-dependent thermophysical property, with the temperature in K.
+#[derive(Debug, Deserialize)]
+struct ThermalPropertyData {
+    temperature: f64,
+    value: f64,
+}
+
+/// Loading a thermophysical property table from a specified file path.
+pub fn load_thermal_property(file_path: &Path) -> (Vec<f64>, Vec<f64>) {
+    let csv_data = fs::read_to_string(file_path)
+        .unwrap_or_else(|_| panic!("Should have been able to read the file: {:?}", file_path));
+    let mut reader = ReaderBuilder::new()
+        .comment(Some(b'#'))
+        .from_reader(csv_data.as_bytes());
+
+    reader
+        .deserialize()
+        .map(|result| {
+            let record: ThermalPropertyData =
+                result.unwrap_or_else(|error| panic!("Failed to parse {:?}: {}", file_path, error));
+            (record.temperature, record.value)
+        })
+        .unzip()
+}
+
 /// Loading cross-sections from a specified file path.
 pub fn load_cross_sections(file_path: &Path) -> (Vec<f64>, Vec<f64>) {
     let csv_data = fs::read_to_string(file_path).expect(&format!(
         "Should have been able to read the file: {:?}",
         file_path
     ));
-    let mut reader = ReaderBuilder::new().from_reader(csv_data.as_bytes());
+    let mut reader = ReaderBuilder::new()
+        .comment(Some(b'#'))
+        .from_reader(csv_data.as_bytes());
 
     let mut energy_vector = Vec::<f64>::new();
     let mut cross_section_vector = Vec::<f64>::new();
@@ -60,6 +90,33 @@ pub fn load_cross_sections(file_path: &Path) -> (Vec<f64>, Vec<f64>) {
     (energy_vector, cross_section_vector)
 }
 
+/// Parses the table scheme from the comment lines at the top of a data file, such as ```# interpolation = log-log``` and ```# out_of_range = zero```.
+/// Other comment lines are ignored. Without these comments, the table is interpolated lin-lin and clamped outside its range.
+pub fn parse_table_scheme<'a>(header_lines: impl Iterator<Item = &'a str>) -> TableScheme {
+    let scheme_toml: String = header_lines
+        .take_while(|line| line.starts_with('#'))
+        .filter_map(|line| line.trim_start_matches('#').split_once('='))
+        .filter(|(key, _)| matches!(key.trim(), "interpolation" | "out_of_range"))
+        .map(|(key, value)| format!("{} = \"{}\"\n", key.trim(), value.trim().trim_matches('"')))
+        .collect();
+
+    toml::from_str(&scheme_toml)
+        .unwrap_or_else(|error| panic!("Failed to parse the table scheme: {}", error))
+}
+
+/// Loads the table scheme from the header of a data file, see ```parse_table_scheme```.
+pub fn load_table_scheme(file_path: &Path) -> TableScheme {
+    let file = File::open(file_path)
+        .unwrap_or_else(|_| panic!("Should have been able to read the file: {:?}", file_path));
+    let header_lines: Vec<String> = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .take_while(|line| line.starts_with('#'))
+        .collect();
+
+    parse_table_scheme(header_lines.iter().map(String::as_str))
+}
+
 /// Loads the bin data vector back into memory by serializing it.
 pub fn load_bin_data_vector(file_path: &Path) -> Vec<BinData> {
     let file = File::open(file_path).expect("Failed to open source data file.");
@@ -0,0 +1,553 @@
+use crate::materials::material_data::MaterialData;
+use crate::utils::data_handling::{sum_on_union_grid, InterpolationLaw};
+
+use log::{debug, warn};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Width of a single data field in an ENDF-6 line.
+const FIELD_WIDTH: usize = 11;
+/// Number of data fields per ENDF-6 line.
+const FIELDS_PER_LINE: usize = 6;
+/// Maximum relative error allowed when linearising non-linear interpolation laws.
+const LINEARISATION_TOLERANCE: f64 = 1e-3;
+/// Maximum recursion depth for the linearisation of a single interval.
+const MAXIMUM_LINEARISATION_DEPTH: usize = 20;
+/// Absorption reactions (n,γ), (n,p), (n,α) etc. that are summed into the aggregated absorption, the same MTs as used by ```endf_handling.py```.
+const ABSORPTION_MTS: std::ops::RangeInclusive<u32> = 102..=117;
+/// Neutron mass in atomic mass units, to convert the ENDF atomic weight ratio.
+pub const NEUTRON_MASS_AMU: f64 = 1.00866491595;
+
+/// The fields of a CONT record: C1, C2, L1, L2, N1, N2.
+type EndfCont = (f64, f64, i64, i64, i64, i64);
+
+/// An ENDF-6 TAB1 record: a one-dimensional table with interpolation ranges.
+#[derive(Debug, Clone, Default)]
+pub struct EndfTable {
+    pub c1: f64,
+    pub c2: f64,
+    pub l1: i64,
+    pub l2: i64,
+    pub interpolation_ranges: Vec<(usize, InterpolationLaw)>,
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+}
+
+impl EndfTable {
+    /// Returns the interpolation law for the interval starting at the given (zero-based) point index.
+    fn interpolation_for_interval(&self, interval_index: usize) -> InterpolationLaw {
+        // NBT is the one-based index of the last point in the range, so the interval (i, i + 1) belongs to the first range with i + 2 <= NBT.
+        self.interpolation_ranges
+            .iter()
+            .find(|(last_point, _)| interval_index + 2 <= *last_point)
+            .map(|(_, interpolation)| *interpolation)
+            .unwrap_or(InterpolationLaw::LinLin)
+    }
+
+    /// Evaluates the table at x with the tabulated interpolation laws. Outside the table, the nearest end value is returned.
+    pub fn evaluate(&self, x: f64) -> f64 {
+        if self.x.is_empty() {
+            return 0.0;
+        }
+        if x <= self.x[0] {
+            return self.y[0];
+        }
+        if x >= self.x[self.x.len() - 1] {
+            return self.y[self.y.len() - 1];
+        }
+
+        let interval_index = self.x.partition_point(|&table_x| table_x <= x) - 1;
+        self.interpolation_for_interval(interval_index).interpolate(
+            self.x[interval_index],
+            self.x[interval_index + 1],
+            self.y[interval_index],
+            self.y[interval_index + 1],
+            x,
+        )
+    }
+
+    /// Converts the table into points that can be interpolated lin-lin within ```LINEARISATION_TOLERANCE```.
+    /// Non-linear intervals are bisected recursively, in the same way as NJOY's LINEAR module.
+    pub fn linearise(&self) -> (Vec<f64>, Vec<f64>) {
+        let mut linear_x: Vec<f64> = Vec::with_capacity(self.x.len());
+        let mut linear_y: Vec<f64> = Vec::with_capacity(self.y.len());
+
+        for interval_index in 0..self.x.len().saturating_sub(1) {
+            let interpolation = self.interpolation_for_interval(interval_index);
+            let (left_x, right_x) = (self.x[interval_index], self.x[interval_index + 1]);
+            let (left_y, right_y) = (self.y[interval_index], self.y[interval_index + 1]);
+
+            linear_x.push(left_x);
+            linear_y.push(left_y);
+
+            match interpolation {
+                InterpolationLaw::LinLin => {}
+                InterpolationLaw::Histogram => {
+                    // Representing the step with a point just before the next energy.
+                    let step_x = right_x - (right_x - left_x) * 1e-9;
+                    if step_x > left_x {
+                        linear_x.push(step_x);
+                        linear_y.push(left_y);
+                    }
+                }
+                _ => linearise_interval(
+                    interpolation,
+                    (left_x, left_y),
+                    (right_x, right_y),
+                    0,
+                    &mut linear_x,
+                    &mut linear_y,
+                ),
+            }
+        }
+
+        if let (Some(&last_x), Some(&last_y)) = (self.x.last(), self.y.last()) {
+            linear_x.push(last_x);
+            linear_y.push(last_y);
+        }
+
+        // Duplicate energies (discontinuities) would break the binary search, so they are nudged apart.
+        for index in 1..linear_x.len() {
+            if linear_x[index] <= linear_x[index - 1] {
+                linear_x[index] = linear_x[index - 1] * (1.0 + 1e-9) + f64::MIN_POSITIVE;
+            }
+        }
+
+        (linear_x, linear_y)
+    }
+}
+
+/// Adds the interior points of an interval until the lin-lin midpoint error is within tolerance.
+fn linearise_interval(
+    interpolation: InterpolationLaw,
+    (left_x, left_y): (f64, f64),
+    (right_x, right_y): (f64, f64),
+    depth: usize,
+    linear_x: &mut Vec<f64>,
+    linear_y: &mut Vec<f64>,
+) {
+    let middle_x = if left_x > 0.0 && right_x > 0.0 {
+        (left_x * right_x).sqrt()
+    } else {
+        (left_x + right_x) / 2.0
+    };
+
+    let exact_y = interpolation.interpolate(left_x, right_x, left_y, right_y, middle_x);
+    let linear_estimate =
+        InterpolationLaw::LinLin.interpolate(left_x, right_x, left_y, right_y, middle_x);
+
+    let relative_error = (exact_y - linear_estimate).abs() / exact_y.abs().max(f64::MIN_POSITIVE);
+
+    if relative_error <= LINEARISATION_TOLERANCE || depth >= MAXIMUM_LINEARISATION_DEPTH {
+        return;
+    }
+
+    linearise_interval(
+        interpolation,
+        (left_x, left_y),
+        (middle_x, exact_y),
+        depth + 1,
+        linear_x,
+        linear_y,
+    );
+    linear_x.push(middle_x);
+    linear_y.push(exact_y);
+    linearise_interval(
+        interpolation,
+        (middle_x, exact_y),
+        (right_x, right_y),
+        depth + 1,
+        linear_x,
+        linear_y,
+    );
+}
+
+/// Parses a number in the ENDF-6 format, which allows omitting the E in the exponent (```1.234567+5```).
+pub fn parse_endf_float(field: &str) -> f64 {
+    let trimmed_field = field.trim();
+    if trimmed_field.is_empty() {
+        return 0.0;
+    }
+
+    if let Ok(value) = trimmed_field.parse::<f64>() {
+        return value;
+    }
+
+    // Inserting the E before the sign of the exponent, skipping a leading sign.
+    let exponent_position = trimmed_field
+        .char_indices()
+        .skip(1)
+        .filter(|(_, character)| *character == '+' || *character == '-')
+        .map(|(position, _)| position)
+        .last();
+
+    match exponent_position {
+        Some(position) => {
+            let (mantissa, exponent) = trimmed_field.split_at(position);
+            format!("{}e{}", mantissa.trim(), exponent.trim())
+                .parse::<f64>()
+                .unwrap_or_else(|_| panic!("Failed to parse ENDF number: {:?}", field))
+        }
+        None => panic!("Failed to parse ENDF number: {:?}", field),
+    }
+}
+
+/// The lines of a single ENDF-6 section (MF/MT), without the section end record.
+struct EndfSection {
+    lines: Vec<String>,
+    current_line: usize,
+}
+
+impl EndfSection {
+    /// Reads a single field from a line. Short lines are padded with blanks.
+    fn field(line: &str, field_index: usize) -> &str {
+        let start = (field_index * FIELD_WIDTH).min(line.len());
+        let end = ((field_index + 1) * FIELD_WIDTH).min(line.len());
+        &line[start..end]
+    }
+
+    fn next_line(&mut self) -> &str {
+        let line = self
+            .lines
+            .get(self.current_line)
+            .expect("Unexpected end of ENDF section.");
+        self.current_line += 1;
+        line
+    }
+
+    /// Reads a CONT record: C1, C2, L1, L2, N1, N2.
+    fn read_cont(&mut self) -> EndfCont {
+        let line = self.next_line();
+        (
+            parse_endf_float(Self::field(line, 0)),
+            parse_endf_float(Self::field(line, 1)),
+            parse_endf_float(Self::field(line, 2)) as i64,
+            parse_endf_float(Self::field(line, 3)) as i64,
+            parse_endf_float(Self::field(line, 4)) as i64,
+            parse_endf_float(Self::field(line, 5)) as i64,
+        )
+    }
+
+    /// Reads a number of values, which fill consecutive lines with six values each.
+    fn read_values(&mut self, value_count: usize) -> Vec<f64> {
+        let mut values = Vec::with_capacity(value_count);
+        while values.len() < value_count {
+            let line = self.next_line().to_string();
+            for field_index in 0..FIELDS_PER_LINE {
+                if values.len() == value_count {
+                    break;
+                }
+                values.push(parse_endf_float(Self::field(&line, field_index)));
+            }
+        }
+        values
+    }
+
+    /// Reads a TAB1 record.
+    fn read_tab1(&mut self) -> EndfTable {
+        let (c1, c2, l1, l2, range_count, point_count) = self.read_cont();
+
+        let interpolation_values = self.read_values(2 * range_count as usize);
+        let interpolation_ranges = interpolation_values
+            .chunks(2)
+            .map(|pair| {
+                (
+                    pair[0] as usize,
+                    InterpolationLaw::from_code(pair[1] as i64),
+                )
+            })
+            .collect();
+
+        let point_values = self.read_values(2 * point_count as usize);
+        let x = point_values.iter().step_by(2).copied().collect();
+        let y = point_values.iter().skip(1).step_by(2).copied().collect();
+
+        EndfTable {
+            c1,
+            c2,
+            l1,
+            l2,
+            interpolation_ranges,
+            x,
+            y,
+        }
+    }
+
+    /// Reads a LIST record, returning the header and the values.
+    fn read_list(&mut self) -> (EndfCont, Vec<f64>) {
+        let header = self.read_cont();
+        let values = self.read_values(header.4 as usize);
+        (header, values)
+    }
+}
+
+/// A parsed ENDF-6 evaluation, with the sections indexed by (MF, MT).
+pub struct EndfEvaluation {
+    sections: BTreeMap<(u32, u32), Vec<String>>,
+}
+
+impl EndfEvaluation {
+    /// Splits an ENDF-6 file into its sections, using the MF and MT columns of every line.
+    pub fn from_string(endf_string: &str) -> EndfEvaluation {
+        let mut sections: BTreeMap<(u32, u32), Vec<String>> = BTreeMap::new();
+
+        for line in endf_string.lines() {
+            if line.len() < 75 {
+                continue;
+            }
+
+            let material = line[66..70].trim().parse::<i64>().unwrap_or(-1);
+            let mf = line[70..72].trim().parse::<u32>().unwrap_or(0);
+            let mt = line[72..75].trim().parse::<u32>().unwrap_or(0);
+
+            // Tape header (MAT = 1 or -1), and section, file and material end records (MF or MT = 0).
+            if material <= 1 || mf == 0 || mt == 0 {
+                continue;
+            }
+
+            sections.entry((mf, mt)).or_default().push(line.to_string());
+        }
+
+        debug!("Read {} ENDF sections.", sections.len());
+
+        EndfEvaluation { sections }
+    }
+
+    pub fn from_file(endf_path: &Path) -> EndfEvaluation {
+        let endf_string = fs::read_to_string(endf_path)
+            .unwrap_or_else(|_| panic!("Failed to read ENDF file: {:?}", endf_path));
+        EndfEvaluation::from_string(&endf_string)
+    }
+
+    fn section(&self, mf: u32, mt: u32) -> Option<EndfSection> {
+        self.sections.get(&(mf, mt)).map(|lines| EndfSection {
+            lines: lines.clone(),
+            current_line: 0,
+        })
+    }
+
+    pub fn has_section(&self, mf: u32, mt: u32) -> bool {
+        self.sections.contains_key(&(mf, mt))
+    }
+
+    /// Atomic weight ratio (AWR) to the neutron mass, from the first record of any section.
+    pub fn atomic_weight_ratio(&self) -> Option<f64> {
+        let (&(mf, mt), _) = self.sections.iter().next()?;
+        let (_, atomic_weight_ratio, _, _, _, _) = self.section(mf, mt)?.read_cont();
+        Some(atomic_weight_ratio)
+    }
+
+    /// Whether the resonances are only given as parameters in MF2, which still have to be added to the MF3 cross-sections (LRP = 1 in MF1/MT451).
+    /// Evaluations reconstructed by ```fetch_endf.py``` have LRP = 2.
+    pub fn has_unreconstructed_resonances(&self) -> bool {
+        self.section(1, 451)
+            .map(|mut section| section.read_cont().2 == 1)
+            .unwrap_or(false)
+    }
+
+    /// Reads the MF3 cross-section table for a reaction, in eV and barn.
+    pub fn cross_section(&self, mt: u32) -> Option<EndfTable> {
+        let mut section = self.section(3, mt)?;
+        section.read_cont();
+        Some(section.read_tab1())
+    }
+
+    /// Reads the total ν̄ from MF1/MT452, which is either a polynomial in energy (LNU = 1) or tabulated (LNU = 2).
+    pub fn nu_bar(&self) -> Option<EndfTable> {
+        let mut section = self.section(1, 452)?;
+        let (_, _, _, nu_bar_representation, _, _) = section.read_cont();
+
+        match nu_bar_representation {
+            1 => {
+                // Evaluating the polynomial on a logarithmic grid so it can be stored as a table.
+                let (_, coefficients) = section.read_list();
+                let x: Vec<f64> = (0..=70)
+                    .map(|index| 1e-5 * 10f64.powf(index as f64 / 10.0))
+                    .collect();
+                let y = x
+                    .iter()
+                    .map(|energy| {
+                        coefficients
+                            .iter()
+                            .enumerate()
+                            .map(|(power, coefficient)| coefficient * energy.powi(power as i32))
+                            .sum()
+                    })
+                    .collect();
+
+                Some(EndfTable {
+                    interpolation_ranges: vec![(71, InterpolationLaw::LinLin)],
+                    x,
+                    y,
+                    ..Default::default()
+                })
+            }
+            2 => Some(section.read_tab1()),
+            _ => {
+                warn!(
+                    "Unsupported ν̄ representation LNU = {}.",
+                    nu_bar_representation
+                );
+                None
+            }
+        }
+    }
+
+    /// Reads the fission spectrum from MF5/MT18 as Watt parameters, with energies in MeV, _a_ in MeV and _b_ in 1/MeV, as used by ```rejection_sample_watt```.
+    /// Only the first subsection is used. Maxwellian (LF = 7) and evaporation (LF = 9) spectra are represented by a Watt spectrum with a vanishing _b_, which reduces to a Maxwellian with θ = _a_.
+    pub fn watt_parameters(&self) -> Option<(Vec<f64>, Vec<f64>, Vec<f64>)> {
+        let mut section = self.section(5, 18)?;
+        let (_, _, _, _, subsection_count, _) = section.read_cont();
+
+        if subsection_count > 1 {
+            warn!(
+                "MF5/MT18 has {} subsections - only the first one is used.",
+                subsection_count
+            );
+        }
+
+        let probability_table = section.read_tab1();
+        let spectrum_law = probability_table.l2;
+
+        let (a_table, b_table) = match spectrum_law {
+            11 => {
+                let a_table = section.read_tab1();
+                let b_table = section.read_tab1();
+                (a_table, Some(b_table))
+            }
+            7 | 9 => (section.read_tab1(), None),
+            _ => {
+                warn!(
+                    "Unsupported fission spectrum law LF = {} in MF5/MT18.",
+                    spectrum_law
+                );
+                return None;
+            }
+        };
+
+        // Using the union of both grids, so that a and b share the same energies.
+        let mut energies: Vec<f64> = a_table.x.clone();
+        if let Some(b_table) = &b_table {
+            energies.extend(b_table.x.iter());
+        }
+        energies.sort_by(|left, right| left.partial_cmp(right).expect("NaN in ENDF energies."));
+        energies.dedup();
+
+        // Small enough that sinh(√(bE)) ≈ √(bE) over the full energy range.
+        let maxwellian_b = 1e-6;
+
+        let a_values = energies
+            .iter()
+            .map(|&energy| a_table.evaluate(energy) / 1e6)
+            .collect();
+        let b_values = energies
+            .iter()
+            .map(|&energy| match &b_table {
+                Some(b_table) => b_table.evaluate(energy) * 1e6,
+                None => maxwellian_b,
+            })
+            .collect();
+        let mev_energies = energies.iter().map(|energy| energy / 1e6).collect();
+
+        Some((mev_energies, a_values, b_values))
+    }
+
+    /// Sums the linearised tables of several reactions onto the union of their energy grids.
+    pub fn summed_cross_section(
+        &self,
+        mts: impl Iterator<Item = u32>,
+    ) -> Option<(Vec<f64>, Vec<f64>)> {
+        let linearised_tables: Vec<(Vec<f64>, Vec<f64>)> = mts
+            .filter_map(|mt| self.cross_section(mt))
+            .map(|table| table.linearise())
+            .collect();
+
+        if linearised_tables.is_empty() {
+            return None;
+        }
+        if linearised_tables.len() == 1 {
+            return linearised_tables.into_iter().next();
+        }
+
+        let weighted_tables: Vec<(&[f64], &[f64], f64)> = linearised_tables
+            .iter()
+            .map(|(energies, cross_sections)| (energies.as_slice(), cross_sections.as_slice(), 1.0))
+            .collect();
+
+        Some(sum_on_union_grid(&weighted_tables))
+    }
+}
+
+impl MaterialData {
+    /// Creates the material data directly from an ENDF-6 evaluation.
+    /// Scattering is taken as the sum of the elastic MT2 and inelastic MT4, with MT4 also kept as its inelastic part. Fission is taken from MT18 and absorption as the sum of MT102-117, the same as the CSVs from ```endf_handling.py```.
+    /// The number density and thermal properties are not part of the evaluation, so they are left at zero to be set by the caller.
+    pub fn from_endf(endf_evaluation: &EndfEvaluation, name: &str) -> MaterialData {
+        let placeholder = || (vec![0.0], vec![0.0]);
+
+        if endf_evaluation.has_unreconstructed_resonances() {
+            warn!("The ENDF evaluation of {} has resonance parameters that are not reconstructed into its cross-sections, so the resonances are missing.", name);
+        }
+
+        let (energy_scattering_cross_sections, elastic_cross_sections) = endf_evaluation
+            .summed_cross_section([2, 4].into_iter())
+            .unwrap_or_else(placeholder);
+
+        let (energy_inelastic_cross_sections, inelastic_cross_sections) = endf_evaluation
+            .summed_cross_section(std::iter::once(4))
+            .unwrap_or_else(placeholder);
+
+        let (energy_absorption_cross_sections, absorption_cross_sections) = endf_evaluation
+            .summed_cross_section(ABSORPTION_MTS)
+            .unwrap_or_else(placeholder);
+
+        let fissionable = endf_evaluation.has_section(3, 18);
+
+        let (energy_fission_cross_sections, fission_cross_sections) = endf_evaluation
+            .summed_cross_section(std::iter::once(18))
+            .unwrap_or_else(placeholder);
+
+        let (energy_nu_bar, nu_bar) = endf_evaluation
+            .nu_bar()
+            .map(|table| table.linearise())
+            .unwrap_or_else(placeholder);
+
+        let (energy_watt_parameters, watt_parameters_a, watt_parameters_b) = endf_evaluation
+            .watt_parameters()
+            .unwrap_or_else(|| (vec![0.0], vec![0.0], vec![0.0]));
+
+        let atomic_mass = endf_evaluation
+            .atomic_weight_ratio()
+            .map(|atomic_weight_ratio| atomic_weight_ratio * NEUTRON_MASS_AMU)
+            .unwrap_or(0.0);
+
+        MaterialData {
+            energy_fission_cross_sections,
+            fission_cross_sections,
+
+            energy_scattering_cross_sections,
+            elastic_cross_sections,
+
+            energy_inelastic_cross_sections,
+            inelastic_cross_sections,
+
+            energy_absorption_cross_sections,
+            absorption_cross_sections,
+
+            energy_nu_bar,
+            nu_bar,
+
+            energy_watt_parameters,
+            watt_parameters_a,
+            watt_parameters_b,
+
+            atomic_mass,
+
+            name: name.to_string(),
+            fissionable,
+
+            ..Default::default()
+        }
+    }
+}
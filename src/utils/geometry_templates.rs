@@ -0,0 +1,639 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use toml::{Table, Value};
+
+/// Arithmetic expression over numbers and named variables, as used in geometry templates.
+/// Supports ```+ - * / % ^```, parentheses, the constant ```pi``` and the functions listed in ```evaluate_function```. Angles of the trigonometric functions are in radians.
+#[derive(Debug, Clone)]
+enum Expression {
+    Number(f64),
+    Variable(String),
+    Negation(Box<Expression>),
+    Binary(char, Box<Expression>, Box<Expression>),
+    Function(String, Vec<Expression>),
+}
+
+/// Recursive descent parser for ```Expression```, with the usual precedence: ```^``` (right-associative) binds stronger than negation, which binds stronger than ```* / %``` and ```+ -```.
+struct ExpressionParser<'a> {
+    expression: &'a str,
+    characters: Vec<char>,
+    position: usize,
+}
+
+impl<'a> ExpressionParser<'a> {
+    fn parse(expression: &'a str) -> Result<Expression, String> {
+        let mut parser = Self {
+            expression,
+            characters: expression.chars().collect(),
+            position: 0,
+        };
+
+        let parsed_expression = parser.parse_sum()?;
+        parser.skip_whitespace();
+        match parser.peek() {
+            None => Ok(parsed_expression),
+            Some(character) => Err(parser.error(&format!("unexpected '{}'", character))),
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!(
+            "Invalid expression \"{}\": {} at position {}.",
+            self.expression, message, self.position
+        )
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.characters.get(self.position).copied()
+    }
+
+    /// Consumes the next character after any whitespace if it is one of the given operators.
+    fn next_operator(&mut self, operators: &[char]) -> Option<char> {
+        self.skip_whitespace();
+        let character = self
+            .peek()
+            .filter(|character| operators.contains(character))?;
+        self.position += 1;
+        Some(character)
+    }
+
+    fn parse_sum(&mut self) -> Result<Expression, String> {
+        let mut expression = self.parse_product()?;
+        while let Some(operator) = self.next_operator(&['+', '-']) {
+            expression = Expression::Binary(
+                operator,
+                Box::new(expression),
+                Box::new(self.parse_product()?),
+            );
+        }
+        Ok(expression)
+    }
+
+    fn parse_product(&mut self) -> Result<Expression, String> {
+        let mut expression = self.parse_unary()?;
+        while let Some(operator) = self.next_operator(&['*', '/', '%']) {
+            expression = Expression::Binary(
+                operator,
+                Box::new(expression),
+                Box::new(self.parse_unary()?),
+            );
+        }
+        Ok(expression)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expression, String> {
+        match self.next_operator(&['+', '-']) {
+            Some('-') => Ok(Expression::Negation(Box::new(self.parse_unary()?))),
+            Some(_) => self.parse_unary(),
+            None => self.parse_power(),
+        }
+    }
+
+    fn parse_power(&mut self) -> Result<Expression, String> {
+        let base = self.parse_primary()?;
+        match self.next_operator(&['^']) {
+            Some(operator) => Ok(Expression::Binary(
+                operator,
+                Box::new(base),
+                Box::new(self.parse_unary()?),
+            )),
+            None => Ok(base),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expression, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('(') => {
+                self.position += 1;
+                let expression = self.parse_sum()?;
+                self.expect(')')?;
+                Ok(expression)
+            }
+            Some(character) if character.is_ascii_digit() || character == '.' => {
+                self.parse_number()
+            }
+            Some(character) if character.is_alphabetic() || character == '_' => self.parse_name(),
+            Some(character) => Err(self.error(&format!("unexpected '{}'", character))),
+            None => Err(self.error("unexpected end")),
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() != Some(expected) {
+            return Err(self.error(&format!("expected '{}'", expected)));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn parse_number(&mut self) -> Result<Expression, String> {
+        let start = self.position;
+        while self
+            .peek()
+            .is_some_and(|character| character.is_ascii_digit() || character == '.')
+        {
+            self.position += 1;
+        }
+        // Exponent, e.g. 1.5e-3.
+        if matches!(self.peek(), Some('e' | 'E')) {
+            let mantissa_end = self.position;
+            self.position += 1;
+            if matches!(self.peek(), Some('+' | '-')) {
+                self.position += 1;
+            }
+            if !self
+                .peek()
+                .is_some_and(|character| character.is_ascii_digit())
+            {
+                self.position = mantissa_end;
+            }
+            while self
+                .peek()
+                .is_some_and(|character| character.is_ascii_digit())
+            {
+                self.position += 1;
+            }
+        }
+
+        let number: String = self.characters[start..self.position].iter().collect();
+        number
+            .parse()
+            .map(Expression::Number)
+            .map_err(|_| self.error(&format!("invalid number {}", number)))
+    }
+
+    /// Variables can contain dots, to refer to the fields of table-valued loop variables.
+    fn parse_name(&mut self) -> Result<Expression, String> {
+        let start = self.position;
+        while self.peek().is_some_and(|character| {
+            character.is_alphanumeric() || character == '_' || character == '.'
+        }) {
+            self.position += 1;
+        }
+        let name: String = self.characters[start..self.position].iter().collect();
+
+        if self.next_operator(&['(']).is_none() {
+            return Ok(match name.as_str() {
+                "pi" => Expression::Number(PI),
+                _ => Expression::Variable(name),
+            });
+        }
+
+        let mut arguments = vec![self.parse_sum()?];
+        while self.next_operator(&[',']).is_some() {
+            arguments.push(self.parse_sum()?);
+        }
+        self.expect(')')?;
+
+        Ok(Expression::Function(name, arguments))
+    }
+}
+
+impl Expression {
+    fn evaluate(
+        &self,
+        get_variable: &mut dyn FnMut(&str) -> Result<f64, String>,
+    ) -> Result<f64, String> {
+        match self {
+            Expression::Number(number) => Ok(*number),
+            Expression::Variable(name) => get_variable(name),
+            Expression::Negation(expression) => Ok(-expression.evaluate(get_variable)?),
+            Expression::Binary(operator, left, right) => {
+                let (left, right) = (left.evaluate(get_variable)?, right.evaluate(get_variable)?);
+                Ok(match operator {
+                    '+' => left + right,
+                    '-' => left - right,
+                    '*' => left * right,
+                    '/' => left / right,
+                    '%' => left.rem_euclid(right),
+                    _ => left.powf(right),
+                })
+            }
+            Expression::Function(name, arguments) => {
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| argument.evaluate(get_variable))
+                    .collect::<Result<Vec<f64>, String>>()?;
+                evaluate_function(name, &arguments)
+            }
+        }
+    }
+}
+
+fn evaluate_function(name: &str, arguments: &[f64]) -> Result<f64, String> {
+    let single_argument = |function: fn(f64) -> f64| match arguments {
+        [argument] => Ok(function(*argument)),
+        _ => Err(format!("Function {} takes a single argument.", name)),
+    };
+
+    match name {
+        "sqrt" => single_argument(f64::sqrt),
+        "abs" => single_argument(f64::abs),
+        "sin" => single_argument(f64::sin),
+        "cos" => single_argument(f64::cos),
+        "tan" => single_argument(f64::tan),
+        "asin" => single_argument(f64::asin),
+        "acos" => single_argument(f64::acos),
+        "atan" => single_argument(f64::atan),
+        "exp" => single_argument(f64::exp),
+        "ln" => single_argument(f64::ln),
+        "log10" => single_argument(f64::log10),
+        "floor" => single_argument(f64::floor),
+        "ceil" => single_argument(f64::ceil),
+        "round" => single_argument(f64::round),
+        "atan2" => match arguments {
+            [y, x] => Ok(y.atan2(*x)),
+            _ => Err("Function atan2 takes two arguments.".to_string()),
+        },
+        "min" => Ok(arguments.iter().cloned().fold(f64::INFINITY, f64::min)),
+        "max" => Ok(arguments.iter().cloned().fold(f64::NEG_INFINITY, f64::max)),
+        _ => Err(format!("Unknown function {}.", name)),
+    }
+}
+
+/// Evaluates an arithmetic expression, e.g. ```"(plate_count - 1) * pitch / 2"```, with the given variables. Fails on unknown variables and non-finite results.
+pub fn evaluate_expression(
+    expression: &str,
+    variables: &HashMap<String, f64>,
+) -> Result<f64, String> {
+    let value = ExpressionParser::parse(expression)?.evaluate(&mut |name| {
+        variables
+            .get(name)
+            .copied()
+            .ok_or_else(|| format!("Variable {} is not defined.", name))
+    })?;
+
+    if !value.is_finite() {
+        return Err(format!(
+            "Expression \"{}\" does not evaluate to a finite number.",
+            expression
+        ));
+    }
+    Ok(value)
+}
+
+/// Converts the result of an expression into a TOML value, as an integer if it is one, so that it can also be used for the orders of parts.
+fn number_to_value(number: f64) -> Value {
+    if number.fract() == 0.0 && number.abs() < 2_f64.powi(53) {
+        Value::Integer(number as i64)
+    } else {
+        Value::Float(number)
+    }
+}
+
+fn format_number(number: f64) -> String {
+    match number_to_value(number) {
+        Value::Integer(integer) => integer.to_string(),
+        _ => number.to_string(),
+    }
+}
+
+/// Replaces the ```${expression}``` placeholders in a string. A string consisting of a single placeholder becomes a number, other strings get the formatted numbers inserted, e.g. for names.
+fn interpolate(string: &str, variables: &HashMap<String, f64>) -> Result<Value, String> {
+    let mut interpolated_string = String::new();
+    let mut remainder = string;
+
+    while let Some(start) = remainder.find("${") {
+        let length = remainder[start..]
+            .find('}')
+            .ok_or_else(|| format!("Unclosed placeholder in \"{}\".", string))?;
+        let value = evaluate_expression(&remainder[start + 2..start + length], variables)?;
+
+        if start == 0 && length + 1 == remainder.len() && interpolated_string.is_empty() {
+            return Ok(number_to_value(value));
+        }
+
+        interpolated_string.push_str(&remainder[..start]);
+        interpolated_string.push_str(&format_number(value));
+        remainder = &remainder[start + length + 1..];
+    }
+    interpolated_string.push_str(remainder);
+
+    Ok(Value::String(interpolated_string))
+}
+
+/// Numeric value of a parameter or loop value: a number, or a string with an expression, with or without ```${}```.
+fn evaluate_number(value: &Value, variables: &HashMap<String, f64>) -> Result<f64, String> {
+    match value {
+        Value::Integer(integer) => Ok(*integer as f64),
+        Value::Float(float) => Ok(*float),
+        Value::String(string) if string.contains("${") => match interpolate(string, variables)? {
+            Value::Integer(integer) => Ok(integer as f64),
+            Value::Float(float) => Ok(float),
+            _ => Err(format!("\"{}\" is not a single expression.", string)),
+        },
+        Value::String(expression) => evaluate_expression(expression, variables),
+        _ => Err(format!(
+            "Expected a number or an expression, found {}.",
+            value
+        )),
+    }
+}
+
+/// Values a single loop variable takes: a range ```"start..end"``` (end excluded) or ```"start..=end"``` of integer expressions, or an array of numbers or of tables of numbers.
+/// The fields of a table are available as ```variable.field```, e.g. for arrays of offsets.
+fn get_loop_values(
+    variable: &str,
+    values: &Value,
+    variables: &HashMap<String, f64>,
+) -> Result<Vec<Vec<(String, f64)>>, String> {
+    match values {
+        Value::String(range) => {
+            let (start, end, inclusive) = match range.split_once("..") {
+                Some((start, end)) => match end.strip_prefix('=') {
+                    Some(end) => (start, end, true),
+                    None => (start, end, false),
+                },
+                None => {
+                    return Err(format!(
+                        "Loop range \"{}\" is not of the form start..end.",
+                        range
+                    ))
+                }
+            };
+            let (start, end) = (
+                evaluate_number(&Value::String(start.to_string()), variables)?,
+                evaluate_number(&Value::String(end.to_string()), variables)?,
+            );
+            if start.fract() != 0.0 || end.fract() != 0.0 {
+                return Err(format!("Loop range \"{}\" needs integer bounds.", range));
+            }
+
+            let (start, end) = (start as i64, end as i64 + i64::from(inclusive));
+            Ok((start..end)
+                .map(|value| vec![(variable.to_string(), value as f64)])
+                .collect())
+        }
+        Value::Array(values) => values
+            .iter()
+            .map(|value| match value {
+                Value::Table(fields) => fields
+                    .iter()
+                    .map(|(field, value)| {
+                        Ok((
+                            format!("{}.{}", variable, field),
+                            evaluate_number(value, variables)?,
+                        ))
+                    })
+                    .collect(),
+                _ => Ok(vec![(
+                    variable.to_string(),
+                    evaluate_number(value, variables)?,
+                )]),
+            })
+            .collect(),
+        _ => Err(format!(
+            "Loop variable {} needs a range or an array of values.",
+            variable
+        )),
+    }
+}
+
+/// Expands a table of a TOML array with a ```for``` table into one copy per combination of the loop variables, nested in the order in which they are listed.
+fn expand_loop(mut table: Table, variables: &HashMap<String, f64>) -> Result<Vec<Value>, String> {
+    let loop_variables = match table.remove("for") {
+        Some(Value::Table(loop_variables)) => loop_variables,
+        _ => return Err("The loop variables in for have to be given as a table.".to_string()),
+    };
+
+    let mut combinations = vec![variables.clone()];
+    for (variable, values) in &loop_variables {
+        if variables.contains_key(variable) {
+            return Err(format!(
+                "Loop variable {} is already defined as a parameter or outer loop variable.",
+                variable
+            ));
+        }
+
+        combinations = combinations
+            .into_iter()
+            .map(|combination| {
+                Ok(get_loop_values(variable, values, &combination)?
+                    .into_iter()
+                    .map(move |loop_values| {
+                        let mut combination = combination.clone();
+                        combination.extend(loop_values);
+                        combination
+                    }))
+            })
+            .collect::<Result<Vec<_>, String>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+    }
+
+    combinations
+        .iter()
+        .map(|combination| expand_value(&Value::Table(table.clone()), combination))
+        .collect()
+}
+
+/// Replaces all placeholders in a value, and expands the tables in arrays that have a ```for``` loop.
+fn expand_value(value: &Value, variables: &HashMap<String, f64>) -> Result<Value, String> {
+    Ok(match value {
+        Value::String(string) => interpolate(string, variables)?,
+        Value::Array(values) => {
+            let mut expanded_values = Vec::new();
+            for value in values {
+                match value {
+                    Value::Table(table) if table.contains_key("for") => {
+                        expanded_values.extend(expand_loop(table.clone(), variables)?)
+                    }
+                    _ => expanded_values.push(expand_value(value, variables)?),
+                }
+            }
+            Value::Array(expanded_values)
+        }
+        Value::Table(table) => Value::Table(expand_table(table, variables)?),
+        _ => value.clone(),
+    })
+}
+
+fn expand_table(table: &Table, variables: &HashMap<String, f64>) -> Result<Table, String> {
+    table
+        .iter()
+        .map(|(key, value)| Ok((key.clone(), expand_value(value, variables)?)))
+        .collect()
+}
+
+/// Evaluates the parameters of a geometry file, which can refer to each other in any order. Overrides replace the value of a parameter, and have to refer to a defined parameter.
+fn evaluate_parameters(
+    parameters: &Table,
+    parameter_overrides: &HashMap<String, f64>,
+) -> Result<HashMap<String, f64>, String> {
+    if let Some(name) = parameter_overrides
+        .keys()
+        .find(|name| !parameters.contains_key(*name))
+    {
+        return Err(format!(
+            "Parameter {} is not defined in the geometry.",
+            name
+        ));
+    }
+
+    fn resolve(
+        name: &str,
+        parameters: &Table,
+        values: &mut HashMap<String, f64>,
+        parameter_path: &mut Vec<String>,
+    ) -> Result<f64, String> {
+        if let Some(value) = values.get(name) {
+            return Ok(*value);
+        }
+        let value = parameters
+            .get(name)
+            .ok_or_else(|| format!("Variable {} is not defined.", name))?;
+        if parameter_path.iter().any(|path_name| path_name == name) {
+            return Err(format!("Parameter {} depends on itself.", name));
+        }
+
+        parameter_path.push(name.to_string());
+        let value = match value {
+            Value::String(string) => {
+                let expression = string
+                    .strip_prefix("${")
+                    .and_then(|expression| expression.strip_suffix('}'))
+                    .unwrap_or(string);
+                ExpressionParser::parse(expression)?
+                    .evaluate(&mut |name| resolve(name, parameters, values, parameter_path))?
+            }
+            _ => evaluate_number(value, &HashMap::new())?,
+        };
+        parameter_path.pop();
+
+        if !value.is_finite() {
+            return Err(format!(
+                "Parameter {} does not evaluate to a finite number.",
+                name
+            ));
+        }
+        values.insert(name.to_string(), value);
+        Ok(value)
+    }
+
+    let mut values = parameter_overrides.clone();
+    for name in parameters.keys() {
+        resolve(name, parameters, &mut values, &mut Vec::new())?;
+    }
+
+    Ok(values)
+}
+
+/// Appends the entries of an included geometry to those of the including one. Tables that can only be defined once, like the boundary, cannot be in both.
+fn merge_geometry(geometry: &mut Table, included_geometry: Table) -> Result<(), String> {
+    for (key, included_value) in included_geometry {
+        match (geometry.get_mut(&key), included_value) {
+            (None, included_value) => {
+                geometry.insert(key, included_value);
+            }
+            (Some(Value::Array(values)), Value::Array(included_values)) => {
+                values.extend(included_values)
+            }
+            (Some(_), _) => {
+                return Err(format!(
+                    "{} is defined both in the geometry and in an included geometry.",
+                    key
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+fn load_template(
+    toml_path: &Path,
+    parameter_overrides: &HashMap<String, f64>,
+    include_path: &mut Vec<PathBuf>,
+) -> Result<Table, String> {
+    let toml_str = fs::read_to_string(toml_path)
+        .map_err(|error| format!("Failed to read {}: {}.", toml_path.display(), error))?;
+    let mut geometry: Table = toml_str
+        .parse()
+        .map_err(|error| format!("Failed to parse {}: {}", toml_path.display(), error))?;
+
+    let parameters = match geometry.remove("parameters") {
+        Some(Value::Table(parameters)) => parameters,
+        Some(_) => return Err("The parameters have to be given as a table.".to_string()),
+        None => Table::new(),
+    };
+    let variables = evaluate_parameters(&parameters, parameter_overrides)?;
+
+    let includes = geometry.remove("includes");
+    let mut geometry = expand_table(&geometry, &variables)?;
+
+    let includes = match includes.map(|includes| expand_value(&includes, &variables)) {
+        Some(Ok(Value::Array(includes))) => includes,
+        Some(Ok(_)) => return Err("The includes have to be given as an array.".to_string()),
+        Some(Err(error)) => return Err(error),
+        None => Vec::new(),
+    };
+
+    for include in includes {
+        let (path, included_parameters) = match include {
+            Value::String(path) => (path, Table::new()),
+            Value::Table(mut include) => match include.remove("path") {
+                Some(Value::String(path)) => match include.remove("parameters") {
+                    Some(Value::Table(included_parameters)) => (path, included_parameters),
+                    None => (path, Table::new()),
+                    Some(_) => {
+                        return Err(format!(
+                            "The parameters of the include {} have to be given as a table.",
+                            path
+                        ))
+                    }
+                },
+                _ => return Err("Every include needs a path.".to_string()),
+            },
+            _ => return Err("Every include needs a path.".to_string()),
+        };
+
+        let included_parameters = included_parameters
+            .iter()
+            .map(|(name, value)| Ok((name.clone(), evaluate_number(value, &variables)?)))
+            .collect::<Result<HashMap<String, f64>, String>>()?;
+
+        // Included paths are relative to the including file.
+        let included_path = toml_path.parent().unwrap_or(Path::new("")).join(&path);
+        let canonical_path = fs::canonicalize(&included_path)
+            .map_err(|error| format!("Failed to find {}: {}.", included_path.display(), error))?;
+        if include_path.contains(&canonical_path) {
+            return Err(format!("{} includes itself.", included_path.display()));
+        }
+
+        include_path.push(canonical_path);
+        let included_geometry =
+            load_template(&included_path, &included_parameters, include_path)
+                .map_err(|error| format!("In {}: {}", included_path.display(), error))?;
+        include_path.pop();
+
+        merge_geometry(&mut geometry, included_geometry)?;
+    }
+
+    Ok(geometry)
+}
+
+/// Reads a geometry TOML and expands its templating into a plain geometry table, which is then loaded like any other geometry.
+/// - ```[parameters]``` defines named numbers, either directly or as expressions of other parameters. The overrides, e.g. from the simulation config, replace their values.
+/// - Any string ```"${expression}"``` becomes the number the expression evaluates to; placeholders within a longer string, e.g. a name, are replaced by the formatted number.
+/// - An entry in an array of tables with a ```for``` table, e.g. ```for = { i = "0..plate_count" }```, is repeated for every value of the loop variables, with several variables nested in the order they are listed.
+/// - ```includes``` lists other geometry files, relative to this one, by path or as ```{ path, parameters }```. Their arrays are appended after the entries of this file, so includes can also be repeated with loops and different parameters.
+pub fn expand_geometry_template(
+    toml_path: &Path,
+    parameter_overrides: &HashMap<String, f64>,
+) -> Table {
+    let canonical_path = fs::canonicalize(toml_path).unwrap_or(toml_path.to_path_buf());
+    load_template(toml_path, parameter_overrides, &mut vec![canonical_path]).unwrap_or_else(
+        |error| panic!("Failed to load geometry {}: {}", toml_path.display(), error),
+    )
+}
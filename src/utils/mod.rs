@@ -1,3 +1,5 @@
+/// Parsing continuous-energy ACE tables directly into material data.
+pub mod ace_loading;
 /// Loading the user config from a standard TOML.
 pub mod config_loading;
 /// Interpreting data with binary search and interpolation.
@@ -6,6 +8,10 @@ pub mod data_handling;
 pub mod data_loading;
 /// Writing various data to file.
 pub mod data_writing;
+/// Parsing ENDF-6 evaluations directly into material data.
+pub mod endf_loading;
+/// Parameters, expressions, includes and loops in geometry TOMLs, expanded before the parts are loaded.
+pub mod geometry_templates;
 /// Loading the parts for the geometry from a user-specified TOML.
 pub mod parts_loading;
 /// Three-dimensional vectors with various operations.
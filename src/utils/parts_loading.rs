@@ -1,14 +1,110 @@
-use std::fs;
-
-use crate::geometry::components::PartComposition;
+use crate::geometry::boundary::{Boundary, BoundaryTOML};
+use crate::geometry::components::{FractionType, PartComposition};
+use crate::geometry::lattice::{Lattice, LatticeIndex, UniverseInstance};
+use crate::geometry::parts::annular_cylinder::AnnularCylinder;
+use crate::geometry::parts::cell::{parse_region, Cell, Region};
+use crate::geometry::parts::cone::Cone;
 use crate::geometry::parts::cuboid::Cuboid;
 use crate::geometry::parts::cylinder::Cylinder;
+use crate::geometry::parts::ellipsoid::Ellipsoid;
+use crate::geometry::parts::hexagonal_prism::HexagonalPrism;
+use crate::geometry::parts::mesh::Mesh;
 use crate::geometry::parts::parts::PartTypes;
+use crate::geometry::parts::plane::Plane;
+use crate::geometry::parts::primitives::Primitive;
+use crate::geometry::parts::solid::Solid;
 use crate::geometry::parts::sphere::Sphere;
-use crate::materials::material_properties::MaterialNames;
+use crate::geometry::parts::torus::Torus;
+use crate::geometry::transform::{RotationTOML, Transform};
+use crate::materials::compounds::{get_compound_by_name, CompoundTOML};
+use crate::utils::geometry_templates::expand_geometry_template;
 use crate::utils::vectors::Vec3D;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::Path;
+use toml::Value;
+
+/// Loading in the material of a part from a TOML.
+/// Either the legacy ```material_composition_vector```, a mass density in kg/m3 with atom or weight fractions, or a compound from the compound library by name.
+/// For a compound, the density optionally overrides the compound's density.
+#[derive(Deserialize, Debug)]
+pub struct PartMaterialTOML {
+    pub material: Option<String>,
+    pub material_composition_vector: Option<Vec<PartComposition>>,
+    pub density: Option<f64>,
+    pub atom_fractions: Option<Vec<PartComposition>>,
+    pub weight_fractions: Option<Vec<PartComposition>>,
+}
+
+impl PartMaterialTOML {
+    /// Converts the specified material into the part's composition vector.
+    pub fn get_material_composition_vector(
+        self,
+        compounds: &[CompoundTOML],
+    ) -> Vec<PartComposition> {
+        if let Some(compound_name) = &self.material {
+            assert!(
+                self.material_composition_vector.is_none()
+                    && self.atom_fractions.is_none()
+                    && self.weight_fractions.is_none(),
+                "Compound {} cannot be combined with other compositions in the same part.",
+                compound_name
+            );
+
+            return get_compound_by_name(compounds, compound_name)
+                .unwrap_or_else(|| {
+                    panic!(
+                        "Compound {} is not defined in the compound library.",
+                        compound_name
+                    )
+                })
+                .get_material_composition_vector(self.density);
+        }
+
+        let (fractions, fraction_type) = match (
+            self.material_composition_vector,
+            self.atom_fractions,
+            self.weight_fractions,
+        ) {
+            (Some(material_composition_vector), None, None) => {
+                assert!(
+                    self.density.is_none(),
+                    "A density cannot be combined with the legacy material_composition_vector."
+                );
+                return material_composition_vector;
+            }
+            (None, Some(atom_fractions), None) => (atom_fractions, FractionType::Atom),
+            (None, None, Some(weight_fractions)) => (weight_fractions, FractionType::Weight),
+            _ => panic!("Specify exactly one of material, material_composition_vector, atom_fractions or weight_fractions for each part."),
+        };
+
+        let mass_density = self
+            .density
+            .expect("Parts with atom or weight fractions require a density.");
+
+        fractions
+            .into_iter()
+            .map(|part_composition| PartComposition {
+                fraction_type,
+                mass_density,
+                ..part_composition
+            })
+            .collect()
+    }
+}
+
+/// Optional placement of a part or primitive. The shape is defined in its local frame, rotated around the origin and then translated.
+#[derive(Deserialize, Debug, Default)]
+pub struct TransformTOML {
+    pub rotation: Option<RotationTOML>,
+    pub translation: Option<Vec3D>,
+}
+
+impl TransformTOML {
+    pub fn get_transform(&self) -> Option<Transform> {
+        Transform::from_toml(self.rotation.as_ref(), self.translation)
+    }
+}
 
 /// Loading in data for cylinders from a TOML.
 #[derive(Deserialize, Debug)]
@@ -17,9 +113,12 @@ pub struct CylinderTOML {
     pub direction: Vec3D,
     pub length: f64,
     pub radius: f64,
-    pub material_name: MaterialNames,
-    pub material_composition_vector: Vec<PartComposition>,
+    pub material_name: String,
+    #[serde(flatten)]
+    pub material: PartMaterialTOML,
     pub order: i32,
+    #[serde(flatten)]
+    pub transform: TransformTOML,
 }
 
 /// Loading in data for cuboids from a TOML.
@@ -29,9 +128,12 @@ pub struct CuboidTOML {
     pub width: f64,
     pub depth: f64,
     pub height: f64,
-    pub material_name: MaterialNames,
-    pub material_composition_vector: Vec<PartComposition>,
+    pub material_name: String,
+    #[serde(flatten)]
+    pub material: PartMaterialTOML,
     pub order: i32,
+    #[serde(flatten)]
+    pub transform: TransformTOML,
 }
 
 /// Loading in data for spheres from a TOML.
@@ -39,25 +141,185 @@ pub struct CuboidTOML {
 pub struct SphereTOML {
     pub center: Vec3D,
     pub radius: f64,
-    pub material_name: MaterialNames,
-    pub material_composition_vector: Vec<PartComposition>,
+    pub material_name: String,
+    #[serde(flatten)]
+    pub material: PartMaterialTOML,
     pub order: i32,
+    #[serde(flatten)]
+    pub transform: TransformTOML,
 }
 
-/// Combining all the data for spheres, cuboids and cylinders into a single struct for serde.
+/// Loading in data for the other shapes from a TOML, with the fields of the shape next to the material and placement.
+#[derive(Deserialize, Debug)]
+pub struct SolidTOML<T> {
+    #[serde(flatten)]
+    pub shape: T,
+    pub material_name: String,
+    #[serde(flatten)]
+    pub material: PartMaterialTOML,
+    pub order: i32,
+    #[serde(flatten)]
+    pub transform: TransformTOML,
+}
+
+/// Loading in a named primitive from a TOML, which the regions of cells refer to.
+#[derive(Deserialize, Debug)]
+pub struct PrimitiveTOML {
+    pub name: String,
+    #[serde(flatten)]
+    pub primitive: Primitive,
+    #[serde(flatten)]
+    pub transform: TransformTOML,
+}
+
+/// Loading in data for CSG cells from a TOML. The region is a boolean expression over the named primitives, see ```parse_region```.
+/// The order is only needed when cells overlap other parts, and defaults to 0.
+#[derive(Deserialize, Debug)]
+pub struct CellTOML {
+    pub name: String,
+    pub region: String,
+    pub material_name: String,
+    #[serde(flatten)]
+    pub material: PartMaterialTOML,
+    #[serde(default)]
+    pub order: i32,
+}
+
+/// Loading in a lattice from a TOML. The universes are given by name as a map of rows, with the top row (largest y) first; an empty name leaves a cell empty.
+/// The lattice is placed relative to the universe it is part of, or to the origin at the top level.
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LatticeTOML {
+    /// Pitch along x and y, with rows of equal length.
+    Rectangular {
+        center: Vec3D,
+        pitch: [f64; 2],
+        universes: Vec<Vec<String>>,
+    },
+    /// Pitch between the centers of neighbouring cells, with rows staggered by half a pitch, see ```Lattice::hexagonal```.
+    Hexagonal {
+        center: Vec3D,
+        pitch: f64,
+        universes: Vec<Vec<String>>,
+    },
+}
+
+/// Combining all the data for the parts, cells and lattices into a single struct for serde. Used both for the top level and for universes.
 #[derive(Deserialize, Debug)]
 struct Objects {
     spheres: Option<Vec<SphereTOML>>,
     cuboids: Option<Vec<CuboidTOML>>,
     cylinders: Option<Vec<CylinderTOML>>,
+    planes: Option<Vec<SolidTOML<Plane>>>,
+    cones: Option<Vec<SolidTOML<Cone>>>,
+    tori: Option<Vec<SolidTOML<Torus>>>,
+    ellipsoids: Option<Vec<SolidTOML<Ellipsoid>>>,
+    hexagonal_prisms: Option<Vec<SolidTOML<HexagonalPrism>>>,
+    annular_cylinders: Option<Vec<SolidTOML<AnnularCylinder>>>,
+    meshes: Option<Vec<SolidTOML<Mesh>>>,
+    primitives: Option<Vec<PrimitiveTOML>>,
+    cells: Option<Vec<CellTOML>>,
+    lattices: Option<Vec<LatticeTOML>>,
+}
+
+/// Reusable group of parts and lattices, defined around its own origin, which is placed at the center of each lattice cell it fills.
+#[derive(Deserialize, Debug)]
+struct UniverseTOML {
+    name: String,
+    #[serde(flatten)]
+    objects: Objects,
+}
+
+#[derive(Deserialize, Debug)]
+struct GeometryTOML {
+    #[serde(flatten)]
+    objects: Objects,
+    universes: Option<Vec<UniverseTOML>>,
+    boundary: Option<BoundaryTOML>,
+}
+
+/// Everything defined in a geometry TOML: the parts, including the copies of the universes, the lattice index for the point lookup and the optional outer boundary.
+#[derive(Debug)]
+pub struct Geometry {
+    pub parts_vector: Vec<PartTypes>,
+    pub lattice_index: LatticeIndex,
+    pub boundary: Option<Boundary>,
+}
+
+/// Parts of a universe in its own frame, which are copied into every instance, and its lattices.
+struct Universe {
+    parts_vector: Vec<PartTypes>,
+    lattices: Vec<LatticeTOML>,
 }
 
 /// Loading geometries from a specified TOML path into a vector, which can then be read by the simulation.
-pub fn load_geometries(toml_path: &Path) -> Vec<PartTypes> {
-    let toml_str = fs::read_to_string(toml_path).expect("Failed to read geometries TOML.");
-    let objects: Objects =
-        toml::from_str(&toml_str).expect("Failed to parse object from TOML string using serde.");
+/// Parts can reference the compounds by name. Geometries with lattices or a boundary should be loaded with ```load_geometry``` instead.
+pub fn load_geometries(toml_path: &Path, compounds: &[CompoundTOML]) -> Vec<PartTypes> {
+    load_geometry(toml_path, compounds).parts_vector
+}
+
+/// Loading geometries from a specified TOML path, together with the index of the lattices that speeds up the point lookup and the outer boundary.
+/// Every universe in a lattice cell is copied into the parts vector, so each copy can have its own temperature and tallies.
+pub fn load_geometry(toml_path: &Path, compounds: &[CompoundTOML]) -> Geometry {
+    load_parametric_geometry(toml_path, compounds, &HashMap::new())
+}
+
+/// Loading geometries like ```load_geometry```, with the values of some of the geometry's parameters replaced, e.g. for parameter studies.
+/// The parameters, expressions, includes and loops are expanded first, see ```expand_geometry_template```.
+pub fn load_parametric_geometry(
+    toml_path: &Path,
+    compounds: &[CompoundTOML],
+    parameter_overrides: &HashMap<String, f64>,
+) -> Geometry {
+    let geometry: GeometryTOML =
+        Value::Table(expand_geometry_template(toml_path, parameter_overrides))
+            .try_into()
+            .expect("Failed to parse object from TOML string using serde.");
 
+    let mut universes = HashMap::new();
+    for toml_universe in geometry.universes.unwrap_or_default() {
+        assert!(
+            !universes.contains_key(&toml_universe.name),
+            "Universe {} is defined more than once.",
+            toml_universe.name
+        );
+        let (parts_vector, lattices) = load_objects(toml_universe.objects, compounds);
+        universes.insert(
+            toml_universe.name,
+            Universe {
+                parts_vector,
+                lattices,
+            },
+        );
+    }
+
+    let (mut parts_vector, lattices) = load_objects(geometry.objects, compounds);
+    let mut lattice_index = LatticeIndex::new(parts_vector.len());
+
+    for toml_lattice in &lattices {
+        let root_lattice_index = place_lattice(
+            toml_lattice,
+            Vec3D::default(),
+            &universes,
+            &mut parts_vector,
+            &mut lattice_index,
+            &mut Vec::new(),
+        );
+        lattice_index.root.lattice_indices.push(root_lattice_index);
+    }
+
+    Geometry {
+        parts_vector,
+        lattice_index,
+        boundary: geometry.boundary.as_ref().map(Boundary::from_toml),
+    }
+}
+
+/// Creates the parts of a universe or the top level in their own frame, and returns them together with the lattices that still have to be placed.
+fn load_objects(
+    objects: Objects,
+    compounds: &[CompoundTOML],
+) -> (Vec<PartTypes>, Vec<LatticeTOML>) {
     let mut parts_vector = Vec::<PartTypes>::default();
 
     if let Some(spheres) = objects.spheres {
@@ -66,9 +328,12 @@ pub fn load_geometries(toml_path: &Path) -> Vec<PartTypes> {
                 toml_sphere.center,
                 toml_sphere.radius,
                 toml_sphere.material_name,
-                toml_sphere.material_composition_vector,
+                toml_sphere
+                    .material
+                    .get_material_composition_vector(compounds),
                 toml_sphere.order,
-            );
+            )
+            .with_transform(toml_sphere.transform.get_transform());
             parts_vector.push(PartTypes::Sphere(sphere));
         }
     }
@@ -81,9 +346,12 @@ pub fn load_geometries(toml_path: &Path) -> Vec<PartTypes> {
                 toml_cuboid.depth,
                 toml_cuboid.height,
                 toml_cuboid.material_name,
-                toml_cuboid.material_composition_vector,
+                toml_cuboid
+                    .material
+                    .get_material_composition_vector(compounds),
                 toml_cuboid.order,
-            );
+            )
+            .with_transform(toml_cuboid.transform.get_transform());
             parts_vector.push(PartTypes::Cuboid(cuboid));
         }
     }
@@ -96,12 +364,204 @@ pub fn load_geometries(toml_path: &Path) -> Vec<PartTypes> {
                 toml_cylinder.length,
                 toml_cylinder.radius,
                 toml_cylinder.material_name,
-                toml_cylinder.material_composition_vector,
+                toml_cylinder
+                    .material
+                    .get_material_composition_vector(compounds),
                 toml_cylinder.order,
-            );
+            )
+            .with_transform(toml_cylinder.transform.get_transform());
             parts_vector.push(PartTypes::Cylinder(cylinder));
         }
     }
 
-    parts_vector
+    push_solids(
+        objects.planes,
+        Primitive::Plane,
+        compounds,
+        &mut parts_vector,
+    );
+    push_solids(objects.cones, Primitive::Cone, compounds, &mut parts_vector);
+    push_solids(objects.tori, Primitive::Torus, compounds, &mut parts_vector);
+    push_solids(
+        objects.ellipsoids,
+        Primitive::Ellipsoid,
+        compounds,
+        &mut parts_vector,
+    );
+    push_solids(
+        objects.hexagonal_prisms,
+        Primitive::HexagonalPrism,
+        compounds,
+        &mut parts_vector,
+    );
+    push_solids(
+        objects.annular_cylinders,
+        Primitive::AnnularCylinder,
+        compounds,
+        &mut parts_vector,
+    );
+    push_solids(
+        objects.meshes,
+        Primitive::Mesh,
+        compounds,
+        &mut parts_vector,
+    );
+
+    let mut primitives = HashMap::new();
+    for toml_primitive in objects.primitives.unwrap_or_default() {
+        let name = toml_primitive.name;
+        assert!(
+            !primitives.contains_key(&name),
+            "Primitive {} is defined more than once.",
+            name
+        );
+        let region = Region::Primitive(toml_primitive.primitive.normalized());
+        let region = match toml_primitive.transform.get_transform() {
+            Some(transform) => Region::Transformed(Box::new(region), transform),
+            None => region,
+        };
+        primitives.insert(name, region);
+    }
+
+    if let Some(cells) = objects.cells {
+        for toml_cell in cells {
+            let cell = Cell::new(
+                toml_cell.name,
+                parse_region(&toml_cell.region, &primitives),
+                toml_cell.material_name,
+                toml_cell
+                    .material
+                    .get_material_composition_vector(compounds),
+                toml_cell.order,
+            );
+            parts_vector.push(PartTypes::Cell(cell));
+        }
+    }
+
+    (parts_vector, objects.lattices.unwrap_or_default())
+}
+
+/// Places the lattice with its universes, translated by the offset of the universe it is part of. Returns its index in the lattice index.
+fn place_lattice(
+    toml_lattice: &LatticeTOML,
+    offset: Vec3D,
+    universes: &HashMap<String, Universe>,
+    parts_vector: &mut Vec<PartTypes>,
+    lattice_index: &mut LatticeIndex,
+    universe_path: &mut Vec<String>,
+) -> usize {
+    let (mut lattice, universe_names) = match toml_lattice {
+        LatticeTOML::Rectangular {
+            center,
+            pitch,
+            universes,
+        } => {
+            let column_count = universes.first().map_or(0, Vec::len);
+            assert!(
+                universes.iter().all(|row| row.len() == column_count),
+                "All rows of a rectangular lattice need the same number of universes."
+            );
+            (
+                Lattice::rectangular(center.add(offset), *pitch, universes.len(), column_count),
+                universes,
+            )
+        }
+        LatticeTOML::Hexagonal {
+            center,
+            pitch,
+            universes,
+        } => {
+            let row_lengths: Vec<usize> = universes.iter().map(Vec::len).collect();
+            (
+                Lattice::hexagonal(center.add(offset), *pitch, &row_lengths),
+                universes,
+            )
+        }
+    };
+
+    lattice.universe_instances = universe_names
+        .iter()
+        .flatten()
+        .zip(lattice.cell_centers.clone())
+        .map(|(universe_name, cell_center)| {
+            if universe_name.is_empty() {
+                return None;
+            }
+            Some(place_universe(
+                universe_name,
+                cell_center,
+                universes,
+                parts_vector,
+                lattice_index,
+                universe_path,
+            ))
+        })
+        .collect();
+
+    lattice_index.lattices.push(lattice);
+    lattice_index.lattices.len() - 1
+}
+
+/// Copies the parts of a universe into the parts vector at the given position, and places its nested lattices. Returns the index of the instance.
+fn place_universe(
+    universe_name: &str,
+    position: Vec3D,
+    universes: &HashMap<String, Universe>,
+    parts_vector: &mut Vec<PartTypes>,
+    lattice_index: &mut LatticeIndex,
+    universe_path: &mut Vec<String>,
+) -> usize {
+    let universe = universes
+        .get(universe_name)
+        .unwrap_or_else(|| panic!("Universe {} is not defined.", universe_name));
+    assert!(
+        !universe_path.iter().any(|name| name == universe_name),
+        "Universe {} contains itself through its lattices.",
+        universe_name
+    );
+    universe_path.push(universe_name.to_string());
+
+    let mut universe_instance = UniverseInstance::default();
+    for part in &universe.parts_vector {
+        parts_vector.push(part.clone().translated(position));
+        universe_instance.part_indices.push(parts_vector.len() - 1);
+    }
+
+    for toml_lattice in &universe.lattices {
+        let nested_lattice_index = place_lattice(
+            toml_lattice,
+            position,
+            universes,
+            parts_vector,
+            lattice_index,
+            universe_path,
+        );
+        universe_instance.lattice_indices.push(nested_lattice_index);
+    }
+
+    universe_path.pop();
+
+    lattice_index.universe_instances.push(universe_instance);
+    lattice_index.universe_instances.len() - 1
+}
+
+/// Converts the loaded shapes of one type into solid parts.
+fn push_solids<T>(
+    toml_solids: Option<Vec<SolidTOML<T>>>,
+    to_primitive: fn(T) -> Primitive,
+    compounds: &[CompoundTOML],
+    parts_vector: &mut Vec<PartTypes>,
+) {
+    for toml_solid in toml_solids.unwrap_or_default() {
+        let solid = Solid::new(
+            to_primitive(toml_solid.shape),
+            toml_solid.material_name,
+            toml_solid
+                .material
+                .get_material_composition_vector(compounds),
+            toml_solid.order,
+        )
+        .with_transform(toml_solid.transform.get_transform());
+        parts_vector.push(PartTypes::Solid(solid));
+    }
 }
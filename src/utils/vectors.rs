@@ -26,6 +26,19 @@ impl fmt::Display for Vec3D {
 }
 
 impl Vec3D {
+    /// Components as an array, for code that handles the axes in a loop.
+    pub fn to_array(&self) -> [f64; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    pub fn from_array(array: [f64; 3]) -> Vec3D {
+        Vec3D {
+            x: array[0],
+            y: array[1],
+            z: array[2],
+        }
+    }
+
     pub fn norm(&self) -> f64 {
         f64::sqrt(self.x * self.x + self.y * self.y + self.z * self.z)
     }
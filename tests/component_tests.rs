@@ -1,14 +1,44 @@
 use log::debug;
 use nuclear;
+use nuclear::geometry::boundary::{Boundary, BoundaryCondition};
+use nuclear::geometry::components::get_simulation_range_squared;
+use nuclear::geometry::components::resolve_part_composition;
+use nuclear::geometry::components::BoundingBox;
 use nuclear::geometry::components::Components;
+use nuclear::geometry::components::FractionType;
 use nuclear::geometry::components::PartComposition;
+use nuclear::geometry::components::PartFeedback;
+use nuclear::geometry::geometry_check::{check_geometry, GeometryCheckSettings, GeometryIssueKind};
+use nuclear::geometry::lattice::{Lattice, LatticeIndex};
+use nuclear::geometry::part_grid::PartGrid;
+use nuclear::geometry::parts::cell::{parse_region, Cell, Region};
 use nuclear::geometry::parts::cuboid::Cuboid;
 use nuclear::geometry::parts::cylinder::Cylinder;
+use nuclear::geometry::parts::hexagonal_prism::HexagonalPrism;
+use nuclear::geometry::parts::mesh::Mesh;
+use nuclear::geometry::parts::parts::PartTypes;
+use nuclear::geometry::parts::plane::Plane;
+use nuclear::geometry::parts::primitives::Primitive;
 use nuclear::geometry::parts::sphere::Sphere;
 use nuclear::geometry::presets::create_spheres::create_reference_sphere;
-use nuclear::materials::material_properties::{get_material_data_vector, MaterialNames};
-use nuclear::materials::material_properties::{map_enum_to_indices, map_indices_to_enum};
+use nuclear::geometry::transform::{RotationTOML, Transform};
+use nuclear::geometry::volume_calculation::calculate_volumes;
+use nuclear::materials::compounds::{
+    get_compound_by_name, get_enriched_uranium, get_uranium_atom_fractions, load_compound_library,
+    UraniumIsotope,
+};
+use nuclear::materials::material_data::MaterialData;
+use nuclear::materials::material_library::{get_material_index_by_name, load_material_constants};
+use nuclear::materials::material_properties::get_material_data_vector;
+use nuclear::simulation::thermal_coupling::coupled_runs;
+use nuclear::utils::config_loading::load_config;
+use nuclear::utils::geometry_templates::{evaluate_expression, expand_geometry_template};
+use nuclear::utils::parts_loading::{load_geometries, load_geometry, load_parametric_geometry};
 use nuclear::utils::vectors::Vec3D;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::path::Path;
 
 #[test]
 fn check_bounding_boxes() {
@@ -28,11 +58,8 @@ fn check_bounding_boxes() {
     let depth = 2.0;
     let height = 2.0;
 
-    let material_name: MaterialNames = MaterialNames::U235;
-    let u235_composition = PartComposition {
-        material_name: MaterialNames::U235,
-        material_fraction: 1.0,
-    };
+    let material_name = "U235".to_string();
+    let u235_composition = PartComposition::new("U235", 1.0);
     let material_composition_vector = vec![u235_composition];
     let order = 1;
 
@@ -40,7 +67,7 @@ fn check_bounding_boxes() {
     let sphere: Sphere = Sphere::new(
         center,
         radius,
-        material_name,
+        material_name.clone(),
         material_composition_vector.clone(),
         order,
     );
@@ -50,7 +77,7 @@ fn check_bounding_boxes() {
         direction,
         length,
         radius,
-        material_name,
+        material_name.clone(),
         material_composition_vector.clone(),
         order,
     );
@@ -154,29 +181,1177 @@ fn check_material_indices() {
         Components::new(get_material_data_vector(), create_reference_sphere());
     components.update_cache_properties(1e6);
 
+    // Void is always the default material at index 0.
+    assert_eq!(components.material_data_vector[0].name, "Void");
+
     for (vector_index, material_data) in components.material_data_vector.iter().enumerate() {
-        let material_name_in_vector = material_data.name;
-        let material_name_in_index_to_enum = map_indices_to_enum(vector_index);
-        let index_in_enum_to_index = map_enum_to_indices(&material_name_in_vector);
+        let index_by_name =
+            get_material_index_by_name(&components.material_data_vector, &material_data.name);
 
         debug!(
-            "Vector index: {}, Vector name: {:?}, index name: {:?}, enum index: {}",
-            vector_index,
-            material_name_in_vector,
-            material_name_in_index_to_enum,
-            index_in_enum_to_index
+            "Vector index: {}, Vector name: {:?}, index by name: {:?}",
+            vector_index, material_data.name, index_by_name
         );
 
-        assert_eq!(material_name_in_index_to_enum, material_name_in_vector);
-        assert_eq!(vector_index, index_in_enum_to_index);
+        assert_eq!(Some(vector_index), index_by_name);
+        assert_eq!(
+            components.cached_material_properties[vector_index].name,
+            material_data.name
+        );
+    }
+
+    // The part compositions are resolved to the library indices by name.
+    for part in &components.parts_vector {
+        for part_composition in part.material_composition_vector() {
+            assert_eq!(
+                components.material_data_vector[part_composition.material_index].name,
+                part_composition.material_name
+            );
+        }
+    }
+}
+
+#[test]
+fn check_part_feedback() {
+    let config = load_config(Path::new("config/simulation/default.toml"));
+    let coupling_parameters = config.coupling_parameters;
+
+    // At the reference temperature, nothing should change.
+    let reference_feedback = PartFeedback::from_temperature(
+        coupling_parameters.reference_temperature,
+        &coupling_parameters,
+    );
+    assert!((reference_feedback.density_factor - 1.0).abs() < 1e-12);
+    assert!((reference_feedback.doppler_factor - 1.0).abs() < 1e-12);
+
+    // Hotter parts expand and absorb more.
+    let hot_feedback = PartFeedback::from_temperature(900.0, &coupling_parameters);
+    assert!(hot_feedback.density_factor < 1.0);
+    assert!(hot_feedback.doppler_factor > 1.0);
+
+    // The highest-order part is selected, and positions outside all parts give None.
+    // No cross-section data is needed for the part lookup itself, only the material names.
+    let material_data_vector: Vec<MaterialData> = ["U235", "U238"]
+        .iter()
+        .map(|material_name| MaterialData {
+            name: material_name.to_string(),
+            ..MaterialData::get_void()
+        })
+        .collect();
+    let mut components: Components =
+        Components::new(material_data_vector, create_reference_sphere());
+    let part_temperatures = vec![900.0; components.parts_vector.len()];
+    components.set_part_temperatures(&part_temperatures, &coupling_parameters);
+
+    let outside_position = Vec3D {
+        x: 10.0,
+        y: 0.0,
+        z: 0.0,
+    };
+    assert_eq!(components.get_part_index(&Vec3D::default()), Some(0));
+    assert_eq!(components.get_part_index(&outside_position), None);
+    assert_eq!(components.part_feedback_vector[0].temperature, 900.0);
+}
+
+#[test]
+fn check_lazy_part_cross_sections() {
+    let config = load_config(Path::new("config/simulation/default.toml"));
+    let coupling_parameters = config.coupling_parameters;
+
+    // The number density cancels out the barn conversion, so the macroscopic cross-section equals the microscopic one.
+    let material_data_vector = vec![
+        MaterialData::get_void(),
+        MaterialData {
+            energy_scattering_cross_sections: vec![1.0, 1e7],
+            elastic_cross_sections: vec![10.0, 20.0],
+            number_density: 1e28,
+            atomic_mass: 1.0,
+            name: "Test".to_string(),
+            ..MaterialData::get_void()
+        },
+    ];
+    let mut components = Components::new(
+        material_data_vector,
+        vec![PartTypes::Sphere(Sphere::new(
+            Vec3D::default(),
+            1.0,
+            "Test".to_string(),
+            vec![PartComposition::new("Test", 1.0)],
+            1,
+        ))],
+    );
+
+    components.update_cache_properties(1.0);
+    let part_index = components.get_part_index(&Vec3D::default());
+    assert!((components.get_part_total_cross_section(part_index) - 10.0).abs() < 1e-12);
+    assert_eq!(components.get_part_total_cross_section(None), 0.0);
+
+    let mut rng = SmallRng::seed_from_u64(0);
+    assert_eq!(
+        components.select_collision_material(&mut rng, part_index, 10.0),
+        1
+    );
+
+    // The cached cross-section is recomputed once the energy or the feedback changes.
+    components.update_cache_properties(1e7);
+    assert!((components.get_part_total_cross_section(part_index) - 20.0).abs() < 1e-12);
+
+    components.set_part_temperatures(&[900.0], &coupling_parameters);
+    let density_factor = components.part_feedback_vector[0].density_factor;
+    assert!(
+        (components.get_part_total_cross_section(part_index) - 20.0 * density_factor).abs() < 1e-12
+    );
+}
+
+#[test]
+fn check_mixture_number_densities() {
+    // Only the atomic masses and library number densities are needed for the conversion.
+    let material_data_vector = || -> Vec<MaterialData> {
+        [("H1", 1.0, 3.34272e28), ("O16", 16.0, 3.34272e28)]
+            .iter()
+            .map(
+                |(material_name, atomic_mass, number_density)| MaterialData {
+                    name: material_name.to_string(),
+                    atomic_mass: *atomic_mass,
+                    number_density: *number_density,
+                    ..MaterialData::get_void()
+                },
+            )
+            .collect()
+    };
+
+    let water_part = |material_composition_vector: Vec<PartComposition>| {
+        vec![PartTypes::Sphere(Sphere::new(
+            Vec3D::default(),
+            1.0,
+            "H1".to_string(),
+            material_composition_vector,
+            1,
+        ))]
+    };
+
+    // Water with a mean molar mass of 6 g/mol per atom, so 2/3 * 1e6 / 6 * N_A hydrogen atoms per m3.
+    let atom_components = Components::new(
+        material_data_vector(),
+        water_part(PartComposition::from_fractions(
+            1000.0,
+            &[("H1", 2.0), ("O16", 1.0)],
+            FractionType::Atom,
+        )),
+    );
+    let weight_components = Components::new(
+        material_data_vector(),
+        water_part(PartComposition::from_fractions(
+            1000.0,
+            &[("H1", 2.0 / 18.0), ("O16", 16.0 / 18.0)],
+            FractionType::Weight,
+        )),
+    );
+
+    let expected_hydrogen_number_density = 2.0 / 3.0 * 1e6 / 6.0 * 6.02214076e23;
+
+    for components in [&atom_components, &weight_components] {
+        let material_composition_vector = components.parts_vector[0].material_composition_vector();
+        let hydrogen_number_density = material_composition_vector[0].material_fraction * 3.34272e28;
+        let oxygen_number_density = material_composition_vector[1].material_fraction * 3.34272e28;
+
+        assert!((hydrogen_number_density / expected_hydrogen_number_density - 1.0).abs() < 1e-12);
+        assert!((hydrogen_number_density / oxygen_number_density - 2.0).abs() < 1e-12);
+
+        // Mixtures do not have to sum up to one.
+        components.check_material_fractions_sum();
+    }
+
+    // The legacy form is left untouched.
+    let legacy_components = Components::new(
+        material_data_vector(),
+        water_part(vec![
+            PartComposition::new("H1", 2.0 / 3.0),
+            PartComposition::new("O16", 1.0 / 3.0),
+        ]),
+    );
+    assert_eq!(
+        legacy_components.parts_vector[0].material_composition_vector()[0].material_fraction,
+        2.0 / 3.0
+    );
+}
+
+#[test]
+fn check_compounds() {
+    // The atomic masses match the material library, which also resolves the weight fractions.
+    let material_data_vector: Vec<MaterialData> = [
+        ("O16", 15.9949, 1e28),
+        ("U235", 235.0, 1e28),
+        ("U238", 238.0, 1e28),
+    ]
+    .iter()
+    .map(
+        |(material_name, atomic_mass, number_density)| MaterialData {
+            name: material_name.to_string(),
+            atomic_mass: *atomic_mass,
+            number_density: *number_density,
+            ..MaterialData::get_void()
+        },
+    )
+    .collect();
+
+    // Natural uranium: 0.711 weight-% is 0.720 atom-% U-235.
+    let (u235_atom_fraction, u238_atom_fraction) =
+        get_uranium_atom_fractions(0.711, &material_data_vector);
+    assert!((u235_atom_fraction - 0.00720).abs() < 1e-5);
+    assert!((u235_atom_fraction + u238_atom_fraction - 1.0).abs() < 1e-12);
+
+    let enriched_uranium = get_enriched_uranium(19050.0, 4.95);
+    assert_eq!(enriched_uranium[0].material_name, "U235");
+    assert!((enriched_uranium[0].material_fraction - 0.0495).abs() < 1e-12);
+    assert_eq!(enriched_uranium[0].fraction_type, FractionType::Weight);
+
+    let compounds = load_compound_library(Path::new("config/materials/compounds.toml"));
+    assert!(get_compound_by_name(&compounds, "light_water").is_some());
+    assert!(get_compound_by_name(&compounds, "unobtainium").is_none());
+
+    // Resolving UO2 should give back the enrichment by weight, and two oxygen atoms per uranium atom.
+    let uo2 = get_compound_by_name(&compounds, "uo2_4.95pct").unwrap();
+    for part_composition in uo2.get_material_composition_vector(None) {
+        let uranium_isotope = match part_composition.material_name.as_str() {
+            "U235" => Some(UraniumIsotope::U235(4.95)),
+            "U238" => Some(UraniumIsotope::U238(4.95)),
+            _ => None,
+        };
+        assert_eq!(part_composition.uranium_isotope, uranium_isotope);
+    }
+    let parts_vector = vec![PartTypes::Sphere(Sphere::new(
+        Vec3D::default(),
+        1.0,
+        "U235".to_string(),
+        uo2.get_material_composition_vector(None),
+        1,
+    ))];
+    let components = Components::new(material_data_vector, parts_vector);
+
+    let number_density = |material_name: &str| {
+        components.parts_vector[0]
+            .material_composition_vector()
+            .iter()
+            .find(|part_composition| part_composition.material_name == material_name)
+            .unwrap()
+            .material_fraction
+            * 1e28
+    };
+
+    let u235_mass = number_density("U235") * 235.0;
+    let u238_mass = number_density("U238") * 238.0;
+    assert!((u235_mass / (u235_mass + u238_mass) - 0.0495).abs() < 1e-9);
+    assert!(
+        (number_density("O16") / (number_density("U235") + number_density("U238")) - 2.0).abs()
+            < 1e-9
+    );
+
+    // Total mass density is preserved.
+    let total_mass = (u235_mass + u238_mass + number_density("O16") * 15.9949) / 6.02214076e23;
+    assert!((total_mass / 1e3 / 10970.0 - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn check_csg_cells() {
+    let parts_vector = load_geometries(Path::new("config/geometries/csg_examples.toml"), &[]);
+    assert_eq!(parts_vector.len(), 2);
+
+    let point = |x: f64, y: f64, z: f64| Vec3D { x, y, z };
+    let shell = &parts_vector[0];
+    let fuel_rod = &parts_vector[1];
+
+    // The shell only contains positions between both spheres, outside of the hole.
+    assert!(shell.is_inside(&point(0.0, 0.45, 0.0)));
+    assert!(!shell.is_inside(&point(0.0, 0.3, 0.0)));
+    assert!(!shell.is_inside(&point(0.0, 0.55, 0.0)));
+    assert!(!shell.is_inside(&point(0.45, 0.0, 0.0)));
+    assert!(shell.is_inside(&point(0.45, 0.0, 0.1)));
+
+    // The rod is cut off above the plane.
+    assert!(fuel_rod.is_inside(&point(0.0, 0.0, -0.25)));
+    assert!(fuel_rod.is_inside(&point(0.05, 0.0, 0.05)));
+    assert!(!fuel_rod.is_inside(&point(0.0, 0.0, 0.2)));
+    assert!(!fuel_rod.is_inside(&point(0.15, 0.0, 0.0)));
+
+    // The bounding boxes are taken from the bounded operands.
+    let PartTypes::Cell(shell_cell) = shell else {
+        panic!("Expected a cell.");
+    };
+    assert!((shell_cell.bounding_box.max.x - 0.5).abs() < 1e-12);
+
+    // A complement is unbounded, and precedence is complement, then intersection, then union.
+    let primitives: HashMap<String, Region> = [
+        (
+            "a".to_string(),
+            Region::Primitive(Primitive::Sphere {
+                center: point(0.0, 0.0, 0.0),
+                radius: 1.0,
+            }),
+        ),
+        (
+            "b".to_string(),
+            Region::Primitive(Primitive::Plane(Plane {
+                normal: point(1.0, 0.0, 0.0),
+                offset: 0.0,
+            })),
+        ),
+    ]
+    .into_iter()
+    .collect();
+
+    let region = parse_region("~b & a | ~a", &primitives);
+    assert!(!region.bounding_box().is_bounded());
+    assert!(region.is_inside(&point(0.5, 0.0, 0.0)));
+    assert!(!region.is_inside(&point(-0.5, 0.0, 0.0)));
+    assert!(region.is_inside(&point(-2.0, 0.0, 0.0)));
+    assert!(!parse_region("~(b | ~a)", &primitives).is_inside(&point(-0.5, 0.0, 0.0)));
+
+    // Six axis-aligned planes bound a cuboid cell, which has to be inside the simulation range.
+    let plane =
+        |normal: Vec3D, offset: f64| Region::Primitive(Primitive::Plane(Plane { normal, offset }));
+    let planes: HashMap<String, Region> = [
+        ("x_max", plane(point(1.0, 0.0, 0.0), 0.3)),
+        ("x_min", plane(point(-1.0, 0.0, 0.0), 0.1)),
+        ("y_max", plane(point(0.0, 2.0, 0.0), 0.4)),
+        ("y_min", plane(point(0.0, -1.0, 0.0), 0.2)),
+        ("z_max", plane(point(0.0, 0.0, 1.0), 0.5)),
+        ("z_min", plane(point(0.0, 0.0, -1.0), 0.5)),
+    ]
+    .into_iter()
+    .map(|(name, region)| (name.to_string(), region))
+    .collect();
+    let cuboid_cell = Cell::new(
+        "cuboid".to_string(),
+        parse_region("x_max & x_min & y_max & y_min & z_max & z_min", &planes),
+        "U235".to_string(),
+        vec![PartComposition::new("U235", 1.0)],
+        1,
+    );
+    assert!(cuboid_cell.bounding_box.is_bounded());
+    assert!((cuboid_cell.bounding_box.min.x + 0.1).abs() < 1e-12);
+    assert!((cuboid_cell.bounding_box.max.y - 0.2).abs() < 1e-12);
+    assert!(cuboid_cell.is_inside(&point(0.25, 0.15, -0.45)));
+
+    let simulation_range_squared = get_simulation_range_squared(&[PartTypes::Cell(cuboid_cell)]);
+    assert!(simulation_range_squared >= point(0.3, 0.2, 0.5).norm_squared());
+
+    // A single plane stays unbounded in the other directions, and an oblique plane in all of them.
+    assert!(planes["x_max"].bounding_box().min.x.is_infinite());
+    assert!(planes["x_max"].bounding_box().max.y.is_infinite());
+    assert!(plane(point(1.0, 1.0, 0.0), 0.3)
+        .bounding_box()
+        .max
+        .x
+        .is_infinite());
+}
+
+#[test]
+#[should_panic(expected = "primitive c is not defined")]
+fn check_csg_undefined_primitive() {
+    parse_region("c & (a", &HashMap::new());
+}
+
+#[test]
+fn check_transforms() {
+    let point = |x: f64, y: f64, z: f64| Vec3D { x, y, z };
+
+    // The three forms of the same rotation give the same matrix.
+    let euler_rotation = RotationTOML::EulerAngles {
+        euler_angles: point(0.0, 0.0, 90.0),
+    }
+    .get_rotation_matrix();
+    let axis_angle_rotation = RotationTOML::AxisAngle {
+        axis: point(0.0, 0.0, 2.0),
+        angle: 90.0,
+    }
+    .get_rotation_matrix();
+    let matrix_rotation = RotationTOML::Matrix {
+        matrix: [[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]],
     }
+    .get_rotation_matrix();
+    for row in 0..3 {
+        for column in 0..3 {
+            assert!((euler_rotation[row][column] - matrix_rotation[row][column]).abs() < 1e-12);
+            assert!(
+                (axis_angle_rotation[row][column] - matrix_rotation[row][column]).abs() < 1e-12
+            );
+        }
+    }
+
+    let transform = Transform::from_toml(
+        Some(&RotationTOML::EulerAngles {
+            euler_angles: point(10.0, 20.0, 30.0),
+        }),
+        Some(point(1.0, 2.0, 3.0)),
+    )
+    .unwrap();
+    let local_position = point(0.1, -0.2, 0.3);
+    let round_trip = transform.to_local(&transform.to_global(&local_position));
+    assert!(round_trip.subtract(local_position).norm() < 1e-12);
+    assert!(Transform::from_toml(None, None).is_none());
+
+    let parts_vector = load_geometries(Path::new("config/geometries/transform_examples.toml"), &[]);
+    let (plate, support, shield) = (&parts_vector[0], &parts_vector[1], &parts_vector[2]);
 
-    for (vector_index, material_data) in components.cached_material_properties.iter().enumerate() {
-        let material_name_in_vector = material_data.name;
-        let material_name_in_index_to_enum = map_indices_to_enum(vector_index);
-        let index_in_enum_to_index = map_enum_to_indices(&material_name_in_vector);
+    // Along the rotated plate, but outside of the axis-aligned one.
+    assert!(plate.is_inside(&point(0.2, 0.1, 0.0)));
+    assert!(plate.is_inside(&point(0.4, -0.1, 0.2)));
+    assert!(!plate.is_inside(&point(0.4, 0.1, 0.0)));
+    assert!(!plate.is_inside(&point(0.3, 0.1, 0.0)));
+    assert!(!plate.is_inside(&point(0.31, 0.0, 0.3)));
 
-        assert_eq!(material_name_in_index_to_enum, material_name_in_vector);
-        assert_eq!(vector_index, index_in_enum_to_index);
+    // The tilted support reaches from the lower left to the upper right.
+    let tilt = 30.0_f64.to_radians();
+    assert!(support.is_inside(&point(-0.3 + 0.4 * tilt.sin(), 0.0, 0.4 * tilt.cos())));
+    assert!(!support.is_inside(&point(-0.3, 0.0, 0.4)));
+
+    // The shield is 0.6 m along z after the rotation, and translated along y.
+    assert!(shield.is_inside(&point(0.0, 0.5, 0.25)));
+    assert!(!shield.is_inside(&point(0.0, 0.75, 0.0)));
+
+    // The bounding boxes contain the rotated parts.
+    let PartTypes::Cuboid(plate_cuboid) = plate else {
+        panic!("Expected a cuboid.");
+    };
+    let half_diagonal = (0.01 + 0.25) / 2.0_f64.sqrt();
+    assert!((plate_cuboid.bounding_box.max.x - (0.3 + half_diagonal)).abs() < 1e-12);
+    assert!((plate_cuboid.bounding_box.min.y + half_diagonal).abs() < 1e-12);
+    let PartTypes::Cell(shield_cell) = shield else {
+        panic!("Expected a cell.");
+    };
+    assert!((shield_cell.bounding_box.max.z - 0.3).abs() < 1e-12);
+    assert!((shield_cell.bounding_box.max.y - 0.55).abs() < 1e-12);
+
+    // A translation opposite to the center moves the part farther from the origin, so the simulation range has to grow with it.
+    let sphere = || {
+        Sphere::new(
+            point(1.0, 0.0, 0.0),
+            0.1,
+            "U235".to_string(),
+            vec![PartComposition::new("U235", 1.0)],
+            1,
+        )
+    };
+    let transformed_sphere = PartTypes::Sphere(
+        sphere().with_transform(Transform::from_toml(None, Some(point(-3.0, 0.0, 0.0)))),
+    );
+    let translated_sphere = PartTypes::Sphere(sphere()).translated(point(-3.0, 0.0, 0.0));
+    for moved_sphere in [transformed_sphere, translated_sphere] {
+        assert!(moved_sphere.is_inside(&point(-2.05, 0.0, 0.0)));
+        assert!(!moved_sphere.is_inside(&point(1.0, 0.0, 0.0)));
+        assert!(get_simulation_range_squared(&[moved_sphere]) >= 2.1_f64.powi(2));
     }
 }
+
+#[test]
+#[should_panic(expected = "is not a proper rotation")]
+fn check_invalid_rotation_matrix() {
+    RotationTOML::Matrix {
+        matrix: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, -1.0]],
+    }
+    .get_rotation_matrix();
+}
+
+#[test]
+fn check_additional_shapes() {
+    let point = |x: f64, y: f64, z: f64| Vec3D { x, y, z };
+
+    let parts_vector = load_geometries(Path::new("config/geometries/shape_examples.toml"), &[]);
+    assert_eq!(parts_vector.len(), 6);
+    let [floor, cone, torus, ellipsoid, assembly, cladding] = &parts_vector[..] else {
+        panic!("Expected six parts.");
+    };
+
+    // The half-space lies below the plane and is left out of the simulation range.
+    assert!(floor.is_inside(&point(3.0, -2.0, -0.6)));
+    assert!(!floor.is_inside(&point(0.0, 0.0, -0.4)));
+
+    // The cone narrows from 0.2 m at the base to 0.1 m at the top.
+    assert!(cone.is_inside(&point(0.15, 0.0, 0.0)));
+    assert!(!cone.is_inside(&point(0.15, 0.0, 0.4)));
+    assert!(!cone.is_inside(&point(0.0, 0.0, 0.41)));
+
+    assert!(torus.is_inside(&point(0.0, 0.3, 0.0)));
+    assert!(!torus.is_inside(&point(0.0, 0.0, 0.0)));
+    assert!(!torus.is_inside(&point(0.0, 0.3, 0.06)));
+
+    assert!(ellipsoid.is_inside(&point(0.0, 0.0, 0.29)));
+    assert!(!ellipsoid.is_inside(&point(0.15, 0.0, 0.15)));
+
+    // The assembly is rotated by 30 degrees, so that its corners point along x.
+    let assembly_center = point(-0.5, 0.0, 0.0);
+    assert!(assembly.is_inside(&assembly_center.add(point(0.1 / 3.0_f64.sqrt() - 1e-6, 0.0, 0.0))));
+    assert!(!assembly.is_inside(&assembly_center.add(point(0.0, 0.051, 0.0))));
+
+    assert!(cladding.is_inside(&point(0.5, 0.0, 0.0).add(point(0.0045, 0.0, 0.1))));
+    assert!(!cladding.is_inside(&point(0.5, 0.0, 0.0)));
+
+    // The bounding boxes are conservative and the unbounded plane does not enlarge the simulation range.
+    let PartTypes::Solid(cone_solid) = cone else {
+        panic!("Expected a solid.");
+    };
+    assert!((cone_solid.bounding_box.max.x - 0.2).abs() < 1e-12);
+    assert!((cone_solid.bounding_box.max.z - 0.6).abs() < 1e-12);
+    let PartTypes::Solid(floor_solid) = floor else {
+        panic!("Expected a solid.");
+    };
+    assert!(!floor_solid.bounding_box.is_bounded());
+
+    let hexagonal_prism = HexagonalPrism {
+        center: point(0.0, 0.0, 0.0),
+        flat_to_flat: 0.2,
+        height: 1.0,
+    };
+    assert!(hexagonal_prism.is_inside(&point(0.099, 0.0, 0.0)));
+    assert!(hexagonal_prism.is_inside(&point(0.0, 0.115, 0.0)));
+    assert!(!hexagonal_prism.is_inside(&point(0.099, 0.1, 0.0)));
+    assert!((hexagonal_prism.bounding_box().max.y - 0.2 / 3.0_f64.sqrt()).abs() < 1e-12);
+}
+
+#[test]
+fn check_lattices() {
+    let point = |x: f64, y: f64, z: f64| Vec3D { x, y, z };
+
+    let geometry = load_geometry(Path::new("config/geometries/lattice_examples.toml"), &[]);
+    let (parts_vector, lattice_index) = (geometry.parts_vector, geometry.lattice_index);
+
+    // The vessel, three assemblies of nine pins with two parts each, and seven hexagonal pins.
+    assert_eq!(parts_vector.len(), 1 + 3 * 9 * 2 + 7 * 2);
+    assert_eq!(lattice_index.root.part_indices, vec![0]);
+    assert_eq!(lattice_index.root.lattice_indices.len(), 2);
+
+    let material_name = |position: Vec3D| {
+        let part_index = lattice_index
+            .get_part_index(&parts_vector, &position)
+            .expect("Expected a part.");
+        parts_vector[part_index].material_composition_vector()[0]
+            .material_name
+            .clone()
+    };
+
+    // Top-left assembly, around its center at (-0.0189, 0.0189).
+    assert_eq!(
+        material_name(point(-0.0189 - 0.0126, 0.0189 + 0.0126, 0.0)),
+        "U238"
+    );
+    assert_eq!(
+        material_name(point(-0.0189 - 0.0126 + 0.005, 0.0189 + 0.0126, 0.0)),
+        "H1"
+    );
+    assert_eq!(material_name(point(-0.0189 + 0.0058, 0.0189, 0.0)), "Fe54");
+    assert_eq!(material_name(point(-0.0189, 0.0189, 0.0)), "H1");
+
+    // The empty corner of the core and the space between the lattices fall through to the vessel.
+    assert_eq!(material_name(point(0.0189, -0.0189, 0.0)), "Fe54");
+    assert_eq!(material_name(point(0.1, 0.0, 0.0)), "Fe54");
+
+    // Hexagonal bundle around (0.2, 0.0).
+    assert_eq!(material_name(point(0.2 + 0.0126, 0.0, 0.0)), "U238");
+    assert_eq!(material_name(point(0.2 + 0.0063, 0.0109, 0.0)), "U238");
+    assert_eq!(material_name(point(0.2 + 0.0062, 0.0, 0.0)), "H1");
+    assert_eq!(material_name(point(0.2 + 0.0252, 0.0, 0.0)), "Fe54");
+
+    // As the universes fill their cells, the lattice lookup agrees with checking every part.
+    let full_scan = LatticeIndex::new(parts_vector.len());
+    let mut rng = SmallRng::seed_from_u64(5);
+    for _ in 0..10_000 {
+        let position = point(
+            rng.gen_range(-0.1..0.3),
+            rng.gen_range(-0.1..0.1),
+            rng.gen_range(-0.25..0.25),
+        );
+        assert_eq!(
+            lattice_index.get_part_index(&parts_vector, &position),
+            full_scan.get_part_index(&parts_vector, &position)
+        );
+    }
+
+    // The hexagonal cells are found by rounding to the nearest cell center.
+    let lattice = Lattice::hexagonal(point(0.0, 0.0, 0.0), 1.0, &[2, 3, 2]);
+    assert_eq!(lattice.get_cell_index(&point(0.0, 0.0, 0.0)), Some(3));
+    assert_eq!(lattice.get_cell_index(&point(0.49, 0.0, 0.0)), Some(3));
+    assert_eq!(lattice.get_cell_index(&point(0.51, 0.0, 0.0)), Some(4));
+    assert_eq!(lattice.get_cell_index(&point(0.5, 0.85, 0.0)), Some(1));
+    assert_eq!(lattice.get_cell_index(&point(1.5, 0.85, 0.0)), None);
+}
+
+#[test]
+#[should_panic(expected = "have to differ in length by an odd number")]
+fn check_invalid_hexagonal_lattice() {
+    Lattice::hexagonal(Vec3D::default(), 1.0, &[2, 2]);
+}
+
+#[test]
+fn check_boundary_conditions() {
+    let point = |x: f64, y: f64, z: f64| Vec3D { x, y, z };
+    let mut rng = SmallRng::seed_from_u64(3);
+
+    let boundary = Boundary::new(
+        BoundingBox {
+            min: point(-1.0, -1.0, -1.0),
+            max: point(1.0, 1.0, 1.0),
+        },
+        [
+            [BoundaryCondition::Reflective, BoundaryCondition::Reflective],
+            [BoundaryCondition::Periodic, BoundaryCondition::Periodic],
+            [BoundaryCondition::Vacuum, BoundaryCondition::White],
+        ],
+    );
+
+    // Reflection mirrors the position and the direction at the face.
+    let (mut position, mut direction) = (point(1.2, 0.0, 0.0), point(1.0, 0.0, 0.0));
+    assert!(boundary.apply(&mut position, &mut direction, &mut rng));
+    assert!((position.x - 0.8).abs() < 1e-12 && (direction.x + 1.0).abs() < 1e-12);
+
+    // Periodic faces move the neutron to the opposite side, keeping its direction.
+    let (mut position, mut direction) = (point(0.0, -1.1, 0.0), point(0.0, -1.0, 0.0));
+    assert!(boundary.apply(&mut position, &mut direction, &mut rng));
+    assert!((position.y - 0.9).abs() < 1e-12 && (direction.y + 1.0).abs() < 1e-12);
+
+    // White faces send the neutron back inside in a random direction.
+    for _ in 0..100 {
+        let (mut position, mut direction) = (point(0.0, 0.0, 1.1), point(0.0, 0.0, 1.0));
+        assert!(boundary.apply(&mut position, &mut direction, &mut rng));
+        assert!(direction.z < 0.0 && (direction.norm() - 1.0).abs() < 1e-12);
+        assert!(position.z <= 1.0 && position.z >= 0.9);
+    }
+
+    // Neutrons escape through vacuum faces, and are unaffected inside.
+    let (mut position, mut direction) = (point(0.0, 0.0, -1.1), point(0.0, 0.0, -1.0));
+    assert!(!boundary.apply(&mut position, &mut direction, &mut rng));
+    let (mut position, mut direction) = (point(0.5, 0.5, 0.5), point(0.0, 0.0, 1.0));
+    assert!(boundary.apply(&mut position, &mut direction, &mut rng));
+    assert!((position.z - 0.5).abs() < 1e-12);
+
+    // The boundary is loaded from the geometry, with the condition applied to all faces.
+    let geometry = load_geometry(Path::new("config/geometries/pin_cell.toml"), &[]);
+    let boundary = geometry.boundary.expect("Expected a boundary.");
+    assert_eq!(boundary.conditions, [[BoundaryCondition::Reflective; 2]; 3]);
+}
+
+#[test]
+#[should_panic(expected = "Periodic boundary conditions have to be set on both faces")]
+fn check_unpaired_periodic_boundary() {
+    Boundary::new(
+        BoundingBox {
+            min: Vec3D::default(),
+            max: Vec3D {
+                x: 1.0,
+                y: 1.0,
+                z: 1.0,
+            },
+        },
+        [
+            [BoundaryCondition::Periodic, BoundaryCondition::Vacuum],
+            [BoundaryCondition::Vacuum; 2],
+            [BoundaryCondition::Vacuum; 2],
+        ],
+    );
+}
+
+#[test]
+fn check_part_grids() {
+    let point = |x: f64, y: f64, z: f64| Vec3D { x, y, z };
+    let compounds = load_compound_library(Path::new("config/materials/compounds.toml"));
+
+    // The grid only skips parts that cannot contain the position, so the selected parts are those of the full scan.
+    for geometry_path in [
+        "config/geometries/working_reactor.toml",
+        "config/geometries/reactor_vessel.toml",
+        "config/geometries/shape_examples.toml",
+    ] {
+        let parts_vector = load_geometries(Path::new(geometry_path), &compounds);
+        let scan_index = LatticeIndex::new(parts_vector.len());
+        let mut grid_index = scan_index.clone();
+        grid_index.build_part_grids(&parts_vector);
+
+        let mut rng = SmallRng::seed_from_u64(7);
+        for _ in 0..20_000 {
+            let position = point(
+                rng.gen_range(-1.5..1.5),
+                rng.gen_range(-1.5..1.5),
+                rng.gen_range(-1.5..1.5),
+            );
+            assert_eq!(
+                grid_index.get_part_index(&parts_vector, &position),
+                scan_index.get_part_index(&parts_vector, &position),
+                "Different part in {} at {}.",
+                geometry_path,
+                position
+            );
+        }
+    }
+
+    // Unbounded parts are candidates everywhere, also outside the grid.
+    let parts_vector = load_geometries(Path::new("config/geometries/shape_examples.toml"), &[]);
+    let part_grid = PartGrid::new(&parts_vector, &(0..parts_vector.len()).collect::<Vec<_>>());
+    let (cell_part_indices, unbounded_part_indices) =
+        part_grid.get_candidates(&point(10.0, 10.0, -10.0));
+    assert!(cell_part_indices.is_empty());
+    assert_eq!(unbounded_part_indices, &[0]);
+    let (cell_part_indices, _) = part_grid.get_candidates(&point(0.0, 0.3, 0.0));
+    assert!(cell_part_indices.contains(&2));
+}
+
+#[test]
+fn check_geometry_issues() {
+    let point = |x: f64, y: f64, z: f64| Vec3D { x, y, z };
+    let cuboid = |center: Vec3D, width: f64, order: i32| {
+        PartTypes::Cuboid(Cuboid::new(
+            center,
+            width,
+            2.0,
+            2.0,
+            "Fe54".to_string(),
+            vec![PartComposition::new("Fe54", 1.0)],
+            order,
+        ))
+    };
+
+    // Two slabs with a gap between them in a boundary, the right one reaching through it, and a sphere and a thin slab in the left one.
+    let parts_vector = vec![
+        cuboid(point(-0.5, 0.0, 0.0), 1.0, 1),
+        cuboid(point(0.65, 0.0, 0.0), 1.1, 1),
+        PartTypes::Sphere(Sphere::new(
+            point(-0.5, 0.0, 0.0),
+            0.2,
+            "U235".to_string(),
+            vec![PartComposition::new("U235", 1.0)],
+            0,
+        )),
+        cuboid(point(-0.9, 0.0, 0.0), 0.1, 1),
+    ];
+    let boundary = Boundary::new(
+        BoundingBox {
+            min: point(-1.0, -1.0, -1.0),
+            max: point(1.0, 1.0, 1.0),
+        },
+        [[BoundaryCondition::Vacuum; 2]; 3],
+    );
+
+    let mut rng = SmallRng::seed_from_u64(5);
+    let issues = check_geometry(
+        &parts_vector,
+        &LatticeIndex::new(parts_vector.len()),
+        Some(&boundary),
+        &GeometryCheckSettings {
+            ray_count: 200,
+            step_length: 0.01,
+            part_point_count: 500,
+        },
+        &mut rng,
+    );
+    let kinds: Vec<GeometryIssueKind> = issues.iter().map(|issue| issue.kind).collect();
+
+    // The slab in the left one is both overlapping and hidden, as the left one comes first. The void in the gap is also found between the slabs and the boundary.
+    assert_eq!(
+        kinds[..4],
+        [
+            GeometryIssueKind::EqualOrderOverlap {
+                part_indices: [0, 3],
+                order: 1
+            },
+            GeometryIssueKind::HiddenPart {
+                part_index: 2,
+                covering_part_index: 0
+            },
+            GeometryIssueKind::HiddenPart {
+                part_index: 3,
+                covering_part_index: 0
+            },
+            GeometryIssueKind::OutsideSimulationRange { part_index: 1 },
+        ]
+    );
+    assert!(kinds.contains(&GeometryIssueKind::UnexpectedVoid {
+        enclosing_part_indices: [Some(0), Some(1)]
+    }));
+
+    // The coordinates of each finding are inside the region it describes.
+    for issue in &issues {
+        match issue.kind {
+            GeometryIssueKind::EqualOrderOverlap { .. } => {
+                assert!(issue.position.x >= -0.95 && issue.position.x <= -0.85)
+            }
+            GeometryIssueKind::HiddenPart { part_index, .. } => {
+                assert!(parts_vector[part_index].is_inside(&issue.position))
+            }
+            GeometryIssueKind::OutsideSimulationRange { .. } => assert!(issue.position.x > 1.0),
+            GeometryIssueKind::UnexpectedVoid { .. } => {
+                assert!(issue.position.x > 0.0 && issue.position.x < 0.1)
+            }
+        }
+        assert!(!issue.describe(&parts_vector).is_empty());
+    }
+
+    // The bundled lattice and pin cell geometries are free of issues.
+    for geometry_path in [
+        "config/geometries/lattice_examples.toml",
+        "config/geometries/pin_cell.toml",
+    ] {
+        let geometry = load_geometry(Path::new(geometry_path), &[]);
+        let issues = check_geometry(
+            &geometry.parts_vector,
+            &geometry.lattice_index,
+            geometry.boundary.as_ref(),
+            &GeometryCheckSettings::default(),
+            &mut rng,
+        );
+        assert!(
+            issues.is_empty(),
+            "Issues in {}: {:?}",
+            geometry_path,
+            issues
+        );
+    }
+}
+
+#[test]
+fn check_volume_calculation() {
+    let point = |x: f64, y: f64, z: f64| Vec3D { x, y, z };
+    let material_data_vector =
+        load_material_constants(Path::new("config/materials/material_library.toml"));
+    assert_eq!(material_data_vector[0].name, "Void");
+
+    // A steel sphere overriding the center of a water cube, in a box twice as large.
+    let mut parts_vector = vec![
+        PartTypes::Cuboid(Cuboid::new(
+            Vec3D::default(),
+            1.0,
+            1.0,
+            1.0,
+            "Water".to_string(),
+            PartComposition::from_fractions(
+                1000.0,
+                &[("H1", 0.111894), ("O16", 0.888106)],
+                FractionType::Weight,
+            ),
+            1,
+        )),
+        PartTypes::Sphere(Sphere::new(
+            Vec3D::default(),
+            0.3,
+            "Fe54".to_string(),
+            vec![PartComposition::new("Fe54", 1.0)],
+            2,
+        )),
+    ];
+    for part in parts_vector.iter_mut() {
+        resolve_part_composition(
+            part.material_composition_vector_mut(),
+            &material_data_vector,
+        );
+    }
+
+    let sampling_box = BoundingBox {
+        min: point(-1.0, -1.0, -1.0),
+        max: point(1.0, 1.0, 1.0),
+    };
+    let mut rng = SmallRng::seed_from_u64(11);
+    let volume_calculation = calculate_volumes(
+        &parts_vector,
+        &LatticeIndex::new(parts_vector.len()),
+        &material_data_vector,
+        &sampling_box,
+        200_000,
+        &mut rng,
+    );
+
+    let sphere_volume = 4.0 / 3.0 * std::f64::consts::PI * 0.3_f64.powi(3);
+    let iron_index = get_material_index_by_name(&material_data_vector, "Fe54").unwrap();
+    let iron_density = 0.08487e30 * 54.0 / 6.02214076e23 * 1e-3;
+
+    // The estimates agree with the exact volumes within their uncertainties, which shrink with the number of samples.
+    let assert_estimate = |estimate: f64, uncertainty: f64, exact: f64| {
+        assert!(uncertainty > 0.0 && uncertainty < 0.05 * exact.max(1.0));
+        assert!(
+            (estimate - exact).abs() < 4.0 * uncertainty,
+            "Estimate {} ± {} differs from {}.",
+            estimate,
+            uncertainty,
+            exact
+        );
+    };
+    let cube_estimate = volume_calculation.part_estimates[0];
+    assert_estimate(
+        cube_estimate.volume,
+        cube_estimate.volume_uncertainty,
+        1.0 - sphere_volume,
+    );
+    assert!((cube_estimate.mass / cube_estimate.volume - 1000.0).abs() < 1e-6);
+    let sphere_estimate = volume_calculation.part_estimates[1];
+    assert_estimate(
+        sphere_estimate.volume,
+        sphere_estimate.volume_uncertainty,
+        sphere_volume,
+    );
+
+    // Void gets the rest of the box, and the materials the volume and mass of their parts.
+    let void_estimate = volume_calculation.material_estimates[0];
+    assert_estimate(void_estimate.volume, void_estimate.volume_uncertainty, 7.0);
+    assert_eq!(void_estimate.mass, 0.0);
+    let iron_estimate = volume_calculation.material_estimates[iron_index];
+    assert_estimate(
+        iron_estimate.mass,
+        iron_estimate.mass_uncertainty,
+        sphere_volume * iron_density,
+    );
+    assert!(
+        (void_estimate.volume + cube_estimate.volume + sphere_estimate.volume - 8.0).abs() < 1e-9
+    );
+}
+
+#[test]
+fn check_meshes() {
+    let point = |x: f64, y: f64, z: f64| Vec3D { x, y, z };
+
+    // Unit cube with each face split along a diagonal, wound outwards.
+    let corner = |index: usize| {
+        point(
+            (index & 1) as f64,
+            ((index >> 1) & 1) as f64,
+            ((index >> 2) & 1) as f64,
+        )
+    };
+    let cube_triangles: Vec<[Vec3D; 3]> = [
+        [0, 2, 3, 1],
+        [4, 5, 7, 6],
+        [0, 1, 5, 4],
+        [2, 6, 7, 3],
+        [0, 4, 6, 2],
+        [1, 3, 7, 5],
+    ]
+    .iter()
+    .flat_map(|face| {
+        [
+            [corner(face[0]), corner(face[1]), corner(face[2])],
+            [corner(face[0]), corner(face[2]), corner(face[3])],
+        ]
+    })
+    .collect();
+    let cube = Mesh::new(cube_triangles.clone()).expect("The cube is watertight.");
+    assert_eq!(cube.triangle_count(), 12);
+
+    // Rays along the diagonals of the top and bottom faces, or through their corners, cross each face once.
+    let mut rng = SmallRng::seed_from_u64(13);
+    for _ in 0..10_000 {
+        let position = point(
+            rng.gen_range(-0.5..1.5),
+            rng.gen_range(-0.5..1.5),
+            rng.gen_range(-0.5..1.5),
+        );
+        let is_inside = [position.x, position.y, position.z]
+            .iter()
+            .all(|coordinate| (0.0..=1.0).contains(coordinate));
+        assert_eq!(cube.is_inside(&position), is_inside, "At {}.", position);
+    }
+    for coordinate in [0.25, 0.5, 0.75] {
+        assert!(cube.is_inside(&point(coordinate, coordinate, 0.5)));
+        assert!(cube.is_inside(&point(coordinate, 1.0 - coordinate, 0.5)));
+        assert!(!cube.is_inside(&point(coordinate, coordinate, 1.5)));
+    }
+
+    // A ray through the apex of a pyramid, shared by four triangles, is counted once.
+    let apex = point(0.5, 0.5, 1.0);
+    let pyramid = Mesh::new(vec![
+        [corner(0), corner(2), corner(3)],
+        [corner(0), corner(3), corner(1)],
+        [corner(0), corner(1), apex],
+        [corner(1), corner(3), apex],
+        [corner(3), corner(2), apex],
+        [corner(2), corner(0), apex],
+    ])
+    .expect("The pyramid is watertight.");
+    assert!(pyramid.is_inside(&point(0.5, 0.5, 0.5)));
+    assert!(!pyramid.is_inside(&point(0.5, 0.5, 1.5)));
+    assert!(!pyramid.is_inside(&point(0.5, 0.5, -0.5)));
+    assert!(!pyramid.is_inside(&point(0.9, 0.9, 0.9)));
+
+    // Meshes with holes are rejected.
+    let error = Mesh::new(cube_triangles[1..].to_vec()).unwrap_err();
+    assert!(error.contains("not watertight"), "{}", error);
+
+    // Binary STLs give the same mesh as the triangles they were written from, scaled into m.
+    let mut stl_bytes = vec![0_u8; 80];
+    stl_bytes.extend((cube_triangles.len() as u32).to_le_bytes());
+    for triangle in &cube_triangles {
+        stl_bytes.extend([0.0_f32; 3].iter().flat_map(|value| value.to_le_bytes()));
+        for vertex in triangle {
+            for coordinate in [vertex.x, vertex.y, vertex.z] {
+                stl_bytes.extend((coordinate as f32).to_le_bytes());
+            }
+        }
+        stl_bytes.extend([0_u8; 2]);
+    }
+    let stl_path = std::env::temp_dir().join("nuclear_mesh_test_cube.stl");
+    std::fs::write(&stl_path, stl_bytes).expect("Failed to write the test STL.");
+    let scaled_cube = Mesh::load(&stl_path, 2.0).expect("Failed to load the test STL.");
+    std::fs::remove_file(&stl_path).ok();
+    assert_eq!(scaled_cube.triangle_count(), 12);
+    assert!((scaled_cube.bounding_box().max.z - 2.0).abs() < 1e-12);
+    assert!(scaled_cube.is_inside(&point(1.5, 1.5, 1.5)));
+
+    // Mesh parts and primitives are loaded from STL and OBJ files, and placed like other parts.
+    let parts_vector = load_geometries(Path::new("config/geometries/mesh_examples.toml"), &[]);
+    assert_eq!(parts_vector.len(), 4);
+    let (column, frame, hollow_column) = (&parts_vector[1], &parts_vector[2], &parts_vector[3]);
+    assert!(column.is_inside(&point(0.3, 0.0, 0.0)));
+    assert!(column.is_inside(&point(0.34, 0.0, 0.29)));
+    assert!(!column.is_inside(&point(0.3, 0.0, 0.31)));
+    let diagonal = 0.175 / 2.0_f64.sqrt();
+    assert!(frame.is_inside(&point(diagonal, diagonal, 0.4)));
+    assert!(!frame.is_inside(&point(0.0, 0.0, 0.4)));
+    assert!(!frame.is_inside(&point(0.175, 0.0, 0.4)));
+    assert!(hollow_column.is_inside(&point(-0.27, 0.0, 0.0)));
+    assert!(!hollow_column.is_inside(&point(-0.3, 0.0, 0.0)));
+}
+
+#[test]
+fn check_geometry_templates() {
+    let point = |x, y, z| Vec3D { x, y, z };
+    let variables = HashMap::from([("pitch".to_string(), 0.02), ("count".to_string(), 4.0)]);
+    let evaluate = |expression| evaluate_expression(expression, &variables).unwrap();
+    assert_eq!(evaluate("1 + 2 * 3 ^ 2"), 19.0);
+    assert_eq!(evaluate("-2 ^ 2 + 10 % 4"), -2.0);
+    assert!((evaluate("(count - 1) * pitch / 2") - 0.03).abs() < 1e-15);
+    assert!((evaluate("sqrt(2) * cos(pi / 4) + max(1, 3, 2) + 1.5e-1") - 4.15).abs() < 1e-12);
+    assert!(evaluate_expression("pitch * spacing", &variables)
+        .unwrap_err()
+        .contains("spacing is not defined"));
+    assert!(evaluate_expression("1 / (count - 4)", &variables).is_err());
+    assert!(evaluate_expression("2 * (pitch", &variables).is_err());
+
+    // The parametric reactor reproduces the geometry generated by the script.
+    let generated_parts = load_geometries(Path::new("config/geometries/working_reactor.toml"), &[]);
+    let parametric_parts =
+        load_geometries(Path::new("config/geometries/parametric_reactor.toml"), &[]);
+    assert_eq!(parametric_parts.len(), generated_parts.len());
+    for (parametric_part, generated_part) in parametric_parts.iter().zip(&generated_parts) {
+        assert_eq!(parametric_part.name(), generated_part.name());
+        assert_eq!(
+            parametric_part.material_name(),
+            generated_part.material_name()
+        );
+        assert_eq!(parametric_part.order(), generated_part.order());
+        let (parametric_box, generated_box) = (
+            parametric_part.bounding_box(),
+            generated_part.bounding_box(),
+        );
+        assert!(parametric_box.min.subtract(generated_box.min).norm() < 1e-12);
+        assert!(parametric_box.max.subtract(generated_box.max).norm() < 1e-12);
+    }
+
+    // Overridden parameters change the number of repeated parts.
+    let geometry = load_parametric_geometry(
+        Path::new("config/geometries/parametric_reactor.toml"),
+        &[],
+        &HashMap::from([
+            ("group_count".to_string(), 3.0),
+            ("plates_per_group".to_string(), 2.0),
+        ]),
+    );
+    assert_eq!(geometry.parts_vector.len(), 3 * 5 + 3 * 2 * 2 + 10);
+
+    // Included files are placed repeatedly with their own parameters, and names are interpolated to keep the primitives apart.
+    let directory = std::env::temp_dir().join("nuclear_geometry_template_test");
+    std::fs::create_dir_all(&directory).expect("Failed to create the test directory.");
+    std::fs::write(
+        directory.join("layer.toml"),
+        r#"
+[parameters]
+layer = 1
+z = "layer / 10"
+
+[[primitives]]
+name = "slab_${layer}"
+type = "cuboid"
+center = { x = 0.0, y = 0.0, z = "${z}" }
+width = 1.0
+depth = 1.0
+height = 0.1
+
+[[primitives]]
+for = { hole = [{ x = -0.25, y = 0.0 }, { x = 0.25, y = 0.0 }] }
+name = "hole_${layer}_${(hole.x + 0.25) * 4}"
+type = "sphere"
+center = { x = "${hole.x}", y = "${hole.y}", z = "${z}" }
+radius = 0.05
+
+[[cells]]
+name = "layer_${layer}"
+region = "slab_${layer} & ~hole_${layer}_0 & ~hole_${layer}_2"
+material_name = "Fe54"
+material_composition_vector = [{ material_name = "Fe54", material_fraction = 1.0 }]
+"#,
+    )
+    .expect("Failed to write the test geometry.");
+    std::fs::write(
+        directory.join("stack.toml"),
+        r#"
+[parameters]
+layer_count = 3
+
+[[includes]]
+for = { layer = "1..=layer_count" }
+path = "layer.toml"
+parameters = { layer = "${layer}" }
+"#,
+    )
+    .expect("Failed to write the test geometry.");
+    let parts_vector = load_geometries(&directory.join("stack.toml"), &[]);
+    std::fs::remove_dir_all(&directory).ok();
+
+    let names: Vec<&str> = parts_vector.iter().map(|part| part.name()).collect();
+    assert_eq!(names, ["layer_1", "layer_2", "layer_3"]);
+    let top_layer = &parts_vector[2];
+    assert!(top_layer.is_inside(&point(0.0, 0.0, 0.3)));
+    assert!(!top_layer.is_inside(&point(0.25, 0.0, 0.3)));
+    assert!(!top_layer.is_inside(&point(0.0, 0.0, 0.2)));
+}
+
+#[test]
+#[should_panic(expected = "Parameter plate_width depends on itself")]
+fn check_cyclic_geometry_parameters() {
+    let toml_path = std::env::temp_dir().join("nuclear_cyclic_parameters_test.toml");
+    std::fs::write(
+        &toml_path,
+        "[parameters]\nplate_width = \"2 * plate_gap\"\nplate_gap = \"plate_width / 2\"\n",
+    )
+    .expect("Failed to write the test geometry.");
+    expand_geometry_template(&toml_path, &HashMap::new());
+}
+
+#[test]
+fn check_coupled_runs() {
+    let config_path = Path::new("config/simulation/coupling_reference.toml");
+    let config = load_config(config_path);
+    let coupling_parameters = config.coupling_parameters;
+
+    let picard_iteration_data = coupled_runs(config_path).expect("Coupled runs failed.");
+
+    // Removing the iteration results written by this run.
+    for entry in std::fs::read_dir("results/coupling").expect("Failed to read the results.") {
+        let path = entry.expect("Failed to read the results.").path();
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        if file_name.starts_with(&config.simulation_parameters.run_name) {
+            std::fs::remove_dir_all(&path).ok();
+        }
+    }
+
+    let last_iteration = picard_iteration_data
+        .last()
+        .expect("No Picard iterations were recorded.");
+    assert!(
+        last_iteration.temperature_residual < coupling_parameters.temperature_tolerance
+            && last_iteration.power_residual < coupling_parameters.power_tolerance,
+        "Coupled solution did not converge: {:?}",
+        picard_iteration_data
+    );
+    assert!(last_iteration.heat_diffusion_converged);
+    assert!(
+        last_iteration.peak_fuel_temperature > coupling_parameters.reference_temperature
+            && last_iteration.peak_fuel_temperature.is_finite(),
+        "Unexpected peak fuel temperature: {}",
+        last_iteration.peak_fuel_temperature
+    );
+}
@@ -2,12 +2,24 @@ use log::info;
 use nuclear;
 use nuclear::diagnostics::geometry_diagnostics::GeometryDiagnostics;
 use nuclear::diagnostics::NeutronDiagnostics;
-use nuclear::geometry::components::Components;
-use nuclear::geometry::presets::create_spheres::{create_default_sphere, create_reference_sphere};
+use nuclear::geometry::boundary::{Boundary, BoundaryCondition};
+use nuclear::geometry::components::{BoundingBox, Components};
+use nuclear::geometry::parts::cuboid::Cuboid;
+use nuclear::geometry::parts::cylinder::Cylinder;
+use nuclear::geometry::parts::parts::PartTypes;
+use nuclear::geometry::presets::create_spheres::{
+    create_default_sphere, create_jezebel_sphere, create_reference_sphere, create_sphere,
+};
+use nuclear::materials::compounds::get_enriched_uranium;
+use nuclear::materials::material_data::MaterialData;
+use nuclear::materials::material_library::{
+    load_selected_materials, read_material_tomls, DEFAULT_MATERIAL_LIBRARY_PATH,
+};
 use nuclear::materials::material_properties::get_material_data_vector;
 use nuclear::neutrons::neutron_scheduler::NeutronScheduler;
 use nuclear::simulation::Simulation;
 use nuclear::utils::config_loading::load_config;
+use nuclear::utils::vectors::Vec3D;
 use rand::rngs::SmallRng;
 use rand::SeedableRng;
 use std::path::Path;
@@ -67,6 +79,71 @@ fn godiva_test() {
     assert!(k_estimate / k_known <= error_margin);
 }
 
+/// Bare Pu-239 sphere, based on the Jezebel benchmark. Pu-239 is only in the opt-in ENDF library, whose evaluations are not part of the repository.
+#[test]
+#[ignore = "needs data/pu-239/n-094_Pu_239.endf"]
+fn jezebel_test() {
+    let rng = SmallRng::from_entropy();
+
+    // Loading config
+    let config = load_config(Path::new("config/simulation/reference.toml"));
+
+    let simulation_parameters = config.simulation_parameters;
+    let neutron_bin_parameters = config.neutron_bins;
+
+    // Required structs.
+    let components: Components = Components::new(
+        load_selected_materials(
+            Path::new("config/materials/endf_materials.toml"),
+            &["Pu239"],
+        ),
+        create_jezebel_sphere(),
+    );
+    components.check_material_fractions_sum();
+
+    let neutron_scheduler: NeutronScheduler = NeutronScheduler::default();
+    let bin_parameters = GeometryDiagnostics::new(neutron_bin_parameters);
+
+    let neutron_diagnostics: NeutronDiagnostics = NeutronDiagnostics::new(
+        simulation_parameters.estimate_k,
+        simulation_parameters.track_bins,
+        simulation_parameters.track_fission_positions,
+        simulation_parameters.track_from_generation,
+        bin_parameters,
+        simulation_parameters.initial_neutron_count,
+    );
+
+    // Instantiating simulation.
+    let mut simulation: Simulation = Simulation {
+        rng,
+        components,
+        neutron_scheduler,
+        neutron_diagnostics,
+        simulation_parameters,
+    };
+
+    use std::time::Instant;
+    let now = Instant::now();
+    let simulation_result: bool = simulation.run_simulation();
+    info!("Simulation finished: {}", simulation_result);
+    let simulation_time = now.elapsed();
+    info!("Simulation time: {:.2?}", simulation_time);
+
+    assert!(simulation_result);
+
+    let (k_estimate, _) = simulation
+        .neutron_diagnostics
+        .estimate_k()
+        .expect("Too few generations to give a reasonable k-estimate.");
+    info!("k estimate: {}", k_estimate);
+
+    let k_known = 1.0;
+    let error_margin: f64 = 1.05;
+
+    assert!(k_estimate / k_known <= error_margin);
+    assert!(k_estimate / k_known >= 1.0 / error_margin);
+}
+
 #[test]
 fn infinite_medium_test() {
     let rng = SmallRng::from_entropy();
@@ -124,3 +201,236 @@ fn infinite_medium_test() {
 
     assert!(k_estimate / k_known <= error_margin);
 }
+
+/// Approximate k_inf of 3 wt-% enriched uranium metal, from a rational fit in the enrichment through natural uranium metal (0.36), the infinite-medium critical enrichment of about 5.6 wt-% and highly enriched uranium (2.3).
+const LOW_ENRICHED_URANIUM_K_INFINITE: f64 = 0.72;
+
+/// Mean and standard error of the generation-to-generation k estimates, up to the first generation below the minimum count.
+/// In subcritical media, the population decays and the last generations are too small to give a stable estimate.
+fn estimate_k_from_generations(
+    generation_counts: &[i64],
+    track_from_generation: usize,
+    minimum_generation_count: i64,
+) -> (f64, f64) {
+    let k_estimates: Vec<f64> = generation_counts[track_from_generation..]
+        .windows(2)
+        .take_while(|generation_count_window| {
+            generation_count_window[0] >= minimum_generation_count
+        })
+        .map(|generation_count_window| {
+            generation_count_window[1] as f64 / generation_count_window[0] as f64
+        })
+        .collect();
+    assert!(
+        k_estimates.len() >= 2,
+        "Too few generations of at least {} neutrons: {:?}",
+        minimum_generation_count,
+        generation_counts
+    );
+
+    let count = k_estimates.len() as f64;
+    let mean = k_estimates.iter().sum::<f64>() / count;
+    let variance = k_estimates
+        .iter()
+        .map(|k_estimate| (k_estimate - mean).powi(2))
+        .sum::<f64>()
+        / (count - 1.0);
+
+    (mean, (variance / count).sqrt())
+}
+
+/// Infinite medium of 3% enriched uranium metal, with U-238 read from the ENDF/B-VIII.0 evaluation. This is well below critical, as ANSI/ANS-8.1 gives 5 wt-% as the subcritical limit for the enrichment of uranium metal.
+/// Without capture in U-238, the neutrons would only scatter until absorbed by U-235, giving k close to 2 instead.
+#[test]
+#[ignore = "needs data/u-238/n-092_U_238.endf"]
+fn low_enriched_uranium_test() {
+    let rng = SmallRng::from_entropy();
+
+    // Everything but the Watt parameters is taken from the evaluation, including the inelastic part of the scattering.
+    let material_data_vector: Vec<MaterialData> = std::iter::once(MaterialData::get_void())
+        .chain(
+            read_material_tomls(Path::new(DEFAULT_MATERIAL_LIBRARY_PATH))
+                .into_iter()
+                .filter(|material_toml| ["U235", "U238"].contains(&material_toml.name.as_str()))
+                .map(|mut material_toml| {
+                    if material_toml.name == "U238" {
+                        material_toml.endf_path = Some("data/u-238/n-092_U_238.endf".to_string());
+                        material_toml.scattering_path = None;
+                        material_toml.fissionable = true;
+                    }
+                    MaterialData::from_toml(material_toml)
+                }),
+        )
+        .collect();
+
+    // Loading config
+    let config = load_config(Path::new("config/simulation/reference.toml"));
+
+    let mut simulation_parameters = config.simulation_parameters;
+    let neutron_bin_parameters = config.neutron_bins;
+
+    // The population decays, so more neutrons and an earlier start are needed to have enough generations for the estimate.
+    simulation_parameters.initial_neutron_count = 10000;
+    simulation_parameters.track_from_generation = 1;
+
+    // Required structs.
+    let components: Components = Components::new(
+        material_data_vector,
+        create_sphere(
+            Vec3D::default(),
+            1000.0,
+            get_enriched_uranium(19050.0, 3.0),
+            1,
+        ),
+    );
+    components.check_material_fractions_sum();
+
+    let neutron_scheduler: NeutronScheduler = NeutronScheduler::default();
+    let bin_parameters = GeometryDiagnostics::new(neutron_bin_parameters);
+
+    let neutron_diagnostics: NeutronDiagnostics = NeutronDiagnostics::new(
+        simulation_parameters.estimate_k,
+        simulation_parameters.track_bins,
+        simulation_parameters.track_fission_positions,
+        simulation_parameters.track_from_generation,
+        bin_parameters,
+        simulation_parameters.initial_neutron_count,
+    );
+
+    // Instantiating simulation.
+    let mut simulation: Simulation = Simulation {
+        rng,
+        components,
+        neutron_scheduler,
+        neutron_diagnostics,
+        simulation_parameters,
+    };
+
+    let simulation_result: bool = simulation.run_simulation();
+    info!("Simulation finished: {}", simulation_result);
+
+    let (_, generation_counts) = simulation
+        .neutron_diagnostics
+        .estimate_k()
+        .expect("Too few generations to give a reasonable k-estimate.");
+    let (k_estimate, k_standard_error) = estimate_k_from_generations(&generation_counts, 1, 1000);
+
+    info!("k estimate: {} +- {}", k_estimate, k_standard_error);
+
+    // Three standard errors, plus the uncertainty of the approximate reference.
+    let tolerance = 3.0 * k_standard_error + 0.05;
+    assert!(
+        (k_estimate - LOW_ENRICHED_URANIUM_K_INFINITE).abs() < tolerance,
+        "k estimate: {} +- {}, reference: {}",
+        k_estimate,
+        k_standard_error,
+        LOW_ENRICHED_URANIUM_K_INFINITE
+    );
+}
+
+/// Pin cell with reflective faces, which is an infinite lattice of pins. With the pin and the surrounding cell of the same 3% enriched uranium metal, this is an infinite medium, so the reflected pin cell has to reproduce the k_inf of the large sphere.
+#[test]
+fn reflected_pin_cell_test() {
+    let estimate_k = |components: Components| {
+        let config = load_config(Path::new("config/simulation/reference.toml"));
+        let mut simulation_parameters = config.simulation_parameters;
+        simulation_parameters.initial_neutron_count = 10000;
+        simulation_parameters.track_from_generation = 1;
+
+        components.check_material_fractions_sum();
+
+        let neutron_diagnostics: NeutronDiagnostics = NeutronDiagnostics::new(
+            simulation_parameters.estimate_k,
+            simulation_parameters.track_bins,
+            simulation_parameters.track_fission_positions,
+            simulation_parameters.track_from_generation,
+            GeometryDiagnostics::new(config.neutron_bins),
+            simulation_parameters.initial_neutron_count,
+        );
+
+        // Seeded, so that a failure can be reproduced.
+        let mut simulation: Simulation = Simulation {
+            rng: SmallRng::seed_from_u64(45),
+            components,
+            neutron_scheduler: NeutronScheduler::default(),
+            neutron_diagnostics,
+            simulation_parameters,
+        };
+        simulation.run_simulation();
+
+        let (_, generation_counts) = simulation
+            .neutron_diagnostics
+            .estimate_k()
+            .expect("Too few generations to give a reasonable k-estimate.");
+        let (k_estimate, _) = estimate_k_from_generations(&generation_counts, 1, 1000);
+
+        k_estimate
+    };
+
+    let k_infinite_medium = estimate_k(Components::new(
+        get_material_data_vector(),
+        create_sphere(
+            Vec3D::default(),
+            1000.0,
+            get_enriched_uranium(19050.0, 3.0),
+            1,
+        ),
+    ));
+
+    let pin = Cylinder::new(
+        Vec3D::default(),
+        Vec3D {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        },
+        0.4,
+        0.004,
+        "U235".to_string(),
+        get_enriched_uranium(19050.0, 3.0),
+        2,
+    );
+    let cell = Cuboid::new(
+        Vec3D::default(),
+        0.0126,
+        0.0126,
+        0.4,
+        "U235".to_string(),
+        get_enriched_uranium(19050.0, 3.0),
+        1,
+    );
+    let boundary = Boundary::new(
+        BoundingBox {
+            min: Vec3D {
+                x: -0.0063,
+                y: -0.0063,
+                z: -0.2,
+            },
+            max: Vec3D {
+                x: 0.0063,
+                y: 0.0063,
+                z: 0.2,
+            },
+        },
+        [[BoundaryCondition::Reflective; 2]; 3],
+    );
+    let k_reflected_pin_cell = estimate_k(
+        Components::new(
+            get_material_data_vector(),
+            vec![PartTypes::Cylinder(pin), PartTypes::Cuboid(cell)],
+        )
+        .with_boundary(Some(boundary)),
+    );
+
+    info!(
+        "k infinite medium: {}, k reflected pin cell: {}",
+        k_infinite_medium, k_reflected_pin_cell
+    );
+
+    assert!(
+        (k_reflected_pin_cell / k_infinite_medium - 1.0).abs() < 0.05,
+        "k infinite medium: {}, k reflected pin cell: {}",
+        k_infinite_medium,
+        k_reflected_pin_cell
+    );
+}
@@ -0,0 +1,76 @@
+use nuclear::diagnostics::multigroup::{
+    write_multigroup_cross_sections, EnergyGroupStructure, MultigroupTallies,
+};
+use nuclear::neutrons::neutron_dynamics::InteractionTypes;
+use std::fs;
+
+#[test]
+fn check_energy_groups() {
+    let group_structure = EnergyGroupStructure::new(&[1e-5, 0.625, 2e7]);
+
+    assert_eq!(group_structure.group_count(), 2);
+    assert_eq!(group_structure.boundaries, vec![2e7, 0.625, 1e-5]);
+
+    assert_eq!(group_structure.get_group(1e6), Some(0));
+    assert_eq!(group_structure.get_group(0.625), Some(0));
+    assert_eq!(group_structure.get_group(0.1), Some(1));
+    assert_eq!(group_structure.get_group(1e-5), Some(1));
+    assert_eq!(group_structure.get_group(1e-6), None);
+    assert_eq!(group_structure.get_group(3e7), None);
+}
+
+#[test]
+fn check_multigroup_cross_sections() {
+    let mut multigroup_tallies = MultigroupTallies::new(&[1e-5, 0.625, 2e7], &[2]);
+
+    // Parts that are not selected are ignored.
+    multigroup_tallies.track_flux(1, 1e6, 10.0);
+
+    multigroup_tallies.track_flux(2, 1e6, 2.0);
+    multigroup_tallies.track_flux(2, 0.1, 4.0);
+
+    multigroup_tallies.track_collision(2, 1e6, &InteractionTypes::Scattering, 0.0);
+    multigroup_tallies.track_scattering(2, 1e6, 0.1, 0.5);
+    multigroup_tallies.track_collision(2, 1e6, &InteractionTypes::Fission, 2.5);
+    multigroup_tallies.track_collision(2, 0.1, &InteractionTypes::Absorption, 0.0);
+    multigroup_tallies.track_collision(2, 0.1, &InteractionTypes::Fission, 2.4);
+    multigroup_tallies.track_fission_neutrons(2, 1e6, 3);
+    multigroup_tallies.track_fission_neutrons(2, 0.1, 1);
+
+    // Combining runs doubles all tallies, which leaves the cross-sections unchanged.
+    let other_tallies = multigroup_tallies.clone();
+    multigroup_tallies.add(&other_tallies);
+
+    let cross_sections = multigroup_tallies.get_cross_sections();
+    assert_eq!(cross_sections.len(), 1);
+
+    let region_cross_sections = &cross_sections[0];
+    assert_eq!(region_cross_sections.part_index, 2);
+    assert_eq!(region_cross_sections.flux, vec![4.0, 8.0]);
+    assert_eq!(region_cross_sections.total, vec![1.0, 0.5]);
+    assert_eq!(region_cross_sections.absorption, vec![0.5, 0.5]);
+    assert_eq!(region_cross_sections.fission, vec![0.5, 0.25]);
+    assert_eq!(region_cross_sections.nu_fission, vec![1.25, 0.6]);
+    assert_eq!(region_cross_sections.chi, vec![0.75, 0.25]);
+    assert_eq!(
+        region_cross_sections.scattering_p0,
+        vec![vec![0.0, 0.5], vec![0.0, 0.0]]
+    );
+    assert_eq!(
+        region_cross_sections.scattering_p1,
+        vec![vec![0.0, 0.25], vec![0.0, 0.0]]
+    );
+    assert_eq!(region_cross_sections.transport, vec![0.75, 0.5]);
+    assert!((region_cross_sections.diffusion_coefficient[0] - 1.0 / 2.25).abs() < 1e-12);
+    assert!((region_cross_sections.diffusion_coefficient[1] - 1.0 / 1.5).abs() < 1e-12);
+
+    // The written file is plain TOML, with the documentation in comments.
+    let directory = std::env::temp_dir().join("nuclear_multigroup_test");
+    write_multigroup_cross_sections(&cross_sections, &directory);
+    let written_string = fs::read_to_string(directory.join("part_2_cross_sections.toml"))
+        .expect("Failed to read multigroup file.");
+    let written_table: toml::Table = written_string.parse().expect("Invalid multigroup TOML.");
+    assert_eq!(written_table["group_count"].as_integer(), Some(2));
+    assert_eq!(written_table["scattering_p0"][0][1].as_float(), Some(0.5));
+    fs::remove_dir_all(&directory).expect("Failed to remove multigroup directory.");
+}
@@ -0,0 +1,694 @@
+use nuclear::geometry::components::PartComposition;
+use nuclear::materials::data_cache::{
+    get_cache_file_path, load_cached_material_library, load_shared_material_library,
+};
+use nuclear::materials::data_inspection::{
+    get_log_energy_grid, inspect_composition, TOTAL_ROW_NAME,
+};
+use nuclear::materials::data_validation::{validate_material_data, IssueSeverity};
+use nuclear::materials::energy_grid::EnergyGrid;
+use nuclear::materials::material_data::{MaterialData, ThermalProperty};
+use nuclear::materials::material_library::{load_material_library, load_selected_materials};
+use nuclear::neutrons::Neutron;
+use nuclear::utils::ace_loading::AceTable;
+use nuclear::utils::data_handling::{
+    get_watt_parameters_with_scheme, interpolate_table, linear_interpolation, sum_on_union_grid,
+    InterpolationLaw, OutOfRange, TableScheme,
+};
+use nuclear::utils::data_loading::parse_table_scheme;
+use nuclear::utils::endf_loading::{parse_endf_float, EndfEvaluation};
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+const ENDF_EXCERPT_PATH: &str = "tests/data/endf_excerpt.endf";
+const ACE_EXCERPT_PATH: &str = "tests/data/ace_excerpt.ace";
+
+fn assert_relative_close(value: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (value - expected).abs() <= tolerance * expected.abs(),
+        "Expected {} but got {}.",
+        expected,
+        value
+    );
+}
+
+#[test]
+fn check_endf_float_parsing() {
+    assert_eq!(parse_endf_float(" 1.000000-5"), 1e-5);
+    assert_eq!(parse_endf_float(" 2.360000+2"), 236.0);
+    assert_eq!(parse_endf_float("-1.000000+6"), -1e6);
+    assert_eq!(parse_endf_float(" 1.5E+3"), 1500.0);
+    assert_eq!(parse_endf_float("         11"), 11.0);
+    assert_eq!(parse_endf_float("           "), 0.0);
+}
+
+#[test]
+fn check_endf_sections() {
+    let endf_evaluation = EndfEvaluation::from_file(Path::new(ENDF_EXCERPT_PATH));
+
+    assert!(endf_evaluation.has_section(1, 452));
+    assert!(endf_evaluation.has_section(3, 18));
+    assert!(!endf_evaluation.has_section(3, 16));
+    assert_eq!(endf_evaluation.atomic_weight_ratio(), Some(236.0));
+    assert!(!endf_evaluation.has_unreconstructed_resonances());
+
+    // With LRP = 1, the resonances are only given as parameters.
+    let endf_string = fs::read_to_string(ENDF_EXCERPT_PATH).unwrap().replacen(
+        " 9.299900+4 2.360000+2          0",
+        " 9.299900+4 2.360000+2          1",
+        1,
+    );
+    assert!(EndfEvaluation::from_string(&endf_string).has_unreconstructed_resonances());
+
+    let fission_table = endf_evaluation.cross_section(18).unwrap();
+    assert_eq!(fission_table.x.len(), 4);
+    assert_eq!(fission_table.c1, 2e8);
+
+    // Interval (1e3, 1e6) is lin-lin, (1e6, 2e7) is a histogram.
+    assert_relative_close(fission_table.evaluate(5.005e5), 3.0, 1e-9);
+    assert_relative_close(fission_table.evaluate(1e7), 1.0, 1e-9);
+}
+
+#[test]
+fn check_endf_linearisation() {
+    let endf_evaluation = EndfEvaluation::from_file(Path::new(ENDF_EXCERPT_PATH));
+    let elastic_table = endf_evaluation.cross_section(2).unwrap();
+
+    let (energies, cross_sections) = elastic_table.linearise();
+
+    assert!(energies.len() > elastic_table.x.len());
+    assert!(energies.windows(2).all(|pair| pair[0] < pair[1]));
+
+    // The table is log-log, so the linearised data has to reproduce the power law in between the points.
+    for energy in [1e-4, 1e-2, 0.5, 1e3, 1e6] {
+        let exact_cross_section = elastic_table.evaluate(energy);
+        let linearised_cross_section = linear_interpolation(&energies, &cross_sections, energy).0;
+        assert_relative_close(linearised_cross_section, exact_cross_section, 2e-3);
+    }
+    assert_relative_close(
+        elastic_table.evaluate(1e-2),
+        100.0 * 1e3f64.powf(-0.2),
+        1e-9,
+    );
+}
+
+#[test]
+fn check_endf_material_data() {
+    let endf_evaluation = EndfEvaluation::from_file(Path::new(ENDF_EXCERPT_PATH));
+    let material_data = MaterialData::from_endf(&endf_evaluation, "Test");
+
+    assert_eq!(material_data.name, "Test");
+    assert!(material_data.fissionable);
+    assert_relative_close(material_data.atomic_mass, 236.0 * 1.00866491595, 1e-9);
+
+    // Absorption sums MT102 and the threshold reaction MT103, which is zero below 1 MeV.
+    let absorption = |energy: f64| {
+        linear_interpolation(
+            &material_data.energy_absorption_cross_sections,
+            &material_data.absorption_cross_sections,
+            energy,
+        )
+        .0
+    };
+    assert_relative_close(absorption(1.0), 3.0, 1e-9);
+    assert_relative_close(
+        absorption(1e7),
+        0.1 - 0.09 * 9.0 / 19.0 + 0.2 * 9.0 / 19.0,
+        1e-9,
+    );
+
+    let nu_bar = linear_interpolation(&material_data.energy_nu_bar, &material_data.nu_bar, 1e7).0;
+    assert_relative_close(nu_bar, 2.4 + 3.0 * (1e7 - 1e-5) / (2e7 - 1e-5), 1e-9);
+
+    // Scattering includes the inelastic MT4, which is also kept separately and starts at 1 MeV.
+    let inelastic = |energy: f64| {
+        linear_interpolation(
+            &material_data.energy_inelastic_cross_sections,
+            &material_data.inelastic_cross_sections,
+            energy,
+        )
+        .0
+    };
+    let scattering = linear_interpolation(
+        &material_data.energy_scattering_cross_sections,
+        &material_data.elastic_cross_sections,
+        1.5e6,
+    )
+    .0;
+    assert_eq!(inelastic(5e5), 0.0);
+    assert_relative_close(inelastic(1.5e6), 0.5, 1e-9);
+    assert_relative_close(
+        scattering - inelastic(1.5e6),
+        endf_evaluation.cross_section(2).unwrap().evaluate(1.5e6),
+        1e-3,
+    );
+    assert_eq!(material_data.inelastic_threshold(), 1e6);
+
+    // Watt parameters are converted to MeV.
+    assert_eq!(material_data.energy_watt_parameters.len(), 3);
+    assert_relative_close(material_data.energy_watt_parameters[0], 1e-11, 1e-9);
+    assert_relative_close(material_data.energy_watt_parameters[2], 20.0, 1e-9);
+    assert_relative_close(material_data.watt_parameters_a[0], 0.988, 1e-9);
+    assert_relative_close(material_data.watt_parameters_b[1], 2.2, 1e-9);
+}
+
+#[test]
+fn check_ace_table() {
+    let ace_table = AceTable::from_file(Path::new(ACE_EXCERPT_PATH));
+
+    assert_eq!(ace_table.zaid, "92999.80c");
+    assert_eq!(ace_table.atomic_weight_ratio, 236.0);
+    assert_eq!(ace_table.energies(), &[1e-11, 1e-6, 1.0, 20.0]);
+    assert_eq!(ace_table.elastic_cross_sections(), &[10.0, 5.0, 4.0, 1.0]);
+    assert_eq!(ace_table.reaction_mts(), vec![18, 102]);
+
+    // MT18 starts at the second energy, so it is zero below.
+    assert_eq!(
+        ace_table.fission_cross_sections(),
+        Some(vec![0.0, 10.0, 1.5, 2.0])
+    );
+    assert_eq!(ace_table.reaction_cross_sections(16), None);
+}
+
+#[test]
+fn check_ace_material_data() {
+    let ace_table = AceTable::from_file(Path::new(ACE_EXCERPT_PATH));
+    let material_data = MaterialData::from_ace(&ace_table, "Test");
+
+    assert!(material_data.fissionable);
+
+    // Energies are converted to eV.
+    assert_eq!(material_data.energy_fission_cross_sections.len(), 4);
+    assert_relative_close(material_data.energy_fission_cross_sections[3], 2e7, 1e-12);
+
+    // Inelastic scattering is included in the scattering, so the total is preserved.
+    for index in 0..4 {
+        let total = material_data.elastic_cross_sections[index]
+            + material_data.absorption_cross_sections[index]
+            + material_data.fission_cross_sections[index];
+        assert_relative_close(total, ace_table.total_cross_sections()[index], 1e-12);
+    }
+    assert_relative_close(material_data.elastic_cross_sections[2], 6.4, 1e-12);
+    assert_relative_close(material_data.inelastic_cross_sections[2], 2.4, 1e-12);
+
+    let nu_bar = linear_interpolation(&material_data.energy_nu_bar, &material_data.nu_bar, 1e7).0;
+    assert_relative_close(nu_bar, 2.4 + 3.0 * (1e7 - 1e-5) / (2e7 - 1e-5), 1e-9);
+
+    assert_eq!(material_data.energy_watt_parameters, vec![1e-11, 1.0, 20.0]);
+    assert_relative_close(material_data.watt_parameters_a[2], 1.1, 1e-12);
+    assert_relative_close(material_data.watt_parameters_b[1], 2.2, 1e-12);
+}
+
+#[test]
+fn check_union_grid_sum() {
+    // The second table starts at a threshold, so it is zero below it.
+    let (energies, values) = sum_on_union_grid(&[
+        (&[1.0, 3.0], &[2.0, 4.0], 1.0),
+        (&[2.0, 3.0], &[10.0, 20.0], 0.5),
+    ]);
+
+    assert_eq!(energies, vec![1.0, 2.0, 3.0]);
+    assert_eq!(values, vec![2.0, 8.0, 14.0]);
+}
+
+#[test]
+fn check_isotope_mixture() {
+    let endf_evaluation = EndfEvaluation::from_file(Path::new(ENDF_EXCERPT_PATH));
+    let isotope = || {
+        let mut isotope = MaterialData::from_endf(&endf_evaluation, "Isotope");
+        isotope.fissionable = false;
+        isotope
+    };
+
+    // Two identical isotopes give the same cross-sections, regardless of the abundances.
+    let mixture = MaterialData::from_isotopes(&[(isotope(), 0.2), (isotope(), 0.6)], "Mixture");
+    let isotope = isotope();
+
+    assert_eq!(mixture.name, "Mixture");
+    assert!(!mixture.fissionable);
+    assert_eq!(
+        mixture.energy_scattering_cross_sections,
+        isotope.energy_scattering_cross_sections
+    );
+    for (mixture_value, isotope_value) in mixture
+        .elastic_cross_sections
+        .iter()
+        .zip(isotope.elastic_cross_sections.iter())
+    {
+        assert_relative_close(*mixture_value, *isotope_value, 1e-12);
+    }
+    assert_relative_close(mixture.atomic_mass, isotope.atomic_mass, 1e-12);
+}
+
+#[test]
+fn check_energy_grid_lookup() {
+    let endf_evaluation = EndfEvaluation::from_file(Path::new(ENDF_EXCERPT_PATH));
+    let mut material_data = MaterialData::from_endf(&endf_evaluation, "Test");
+    material_data.energy_scattering_cross_sections =
+        (0..1000).map(|index| 1e-5 * 1.02_f64.powi(index)).collect();
+    material_data.elastic_cross_sections =
+        (0..1000).map(|index| 10.0 + (index as f64).sin()).collect();
+    material_data.energy_watt_parameters = vec![0.0, 1.0, 20.0];
+    material_data.watt_parameters_a = vec![0.9, 1.0, 1.1];
+    material_data.watt_parameters_b = vec![3.0, 2.0, 1.0];
+
+    let material_data_vector = vec![MaterialData::get_void(), material_data];
+    let energy_grid = EnergyGrid::new(&material_data_vector, 64);
+    let material_data = &material_data_vector[1];
+    let grid_indices = energy_grid.hash_material(material_data);
+
+    let table_schemes = [
+        TableScheme::default(),
+        TableScheme {
+            interpolation: InterpolationLaw::LogLog,
+            out_of_range: OutOfRange::Clamp,
+        },
+        TableScheme {
+            interpolation: InterpolationLaw::LinLin,
+            out_of_range: OutOfRange::Extrapolate,
+        },
+        TableScheme {
+            interpolation: InterpolationLaw::LinLog,
+            out_of_range: OutOfRange::Zero,
+        },
+    ];
+
+    // The grid lookup has to give the same results as the binary search, including outside the tables.
+    for (energy, table_scheme) in
+        (0..500)
+            .map(|index| 1e-6 * 1.07_f64.powi(index))
+            .flat_map(|energy| {
+                table_schemes
+                    .iter()
+                    .map(move |table_scheme| (energy, table_scheme))
+            })
+    {
+        let grid_position = energy_grid.get_position(energy);
+
+        for (energies, values, indices) in [
+            (
+                &material_data.energy_scattering_cross_sections,
+                &material_data.elastic_cross_sections,
+                &grid_indices.scattering,
+            ),
+            (
+                &material_data.energy_fission_cross_sections,
+                &material_data.fission_cross_sections,
+                &grid_indices.fission,
+            ),
+            (
+                &material_data.energy_nu_bar,
+                &material_data.nu_bar,
+                &grid_indices.nu_bar,
+            ),
+        ] {
+            assert_relative_close(
+                indices.interpolate(energies, values, table_scheme, &grid_position),
+                interpolate_table(energies, values, table_scheme, energy),
+                1e-12,
+            );
+        }
+
+        let (a, b) = grid_indices.watt_parameters.interpolate_watt_parameters(
+            &material_data.energy_watt_parameters,
+            &material_data.watt_parameters_a,
+            &material_data.watt_parameters_b,
+            table_scheme,
+            &grid_position,
+        );
+        let (expected_a, expected_b) = get_watt_parameters_with_scheme(
+            &material_data.energy_watt_parameters,
+            &material_data.watt_parameters_a,
+            &material_data.watt_parameters_b,
+            table_scheme,
+            energy,
+        );
+        assert_relative_close(a, expected_a, 1e-12);
+        assert_relative_close(b, expected_b, 1e-12);
+    }
+}
+
+#[test]
+fn check_inelastic_scattering() {
+    let atomic_mass = 238.05;
+    let inelastic_threshold = 4.51e4;
+
+    // Mean of x exp(-x) on [0, X], in units of the nuclear temperature T, with X T the energy available above the threshold.
+    let expected_mean_energy = |energy: f64| {
+        let available_energy = energy - inelastic_threshold;
+        let nuclear_temperature = f64::sqrt(available_energy * 1e-6 / (atomic_mass / 10.0)) * 1e6;
+        let maximum_ratio = available_energy / nuclear_temperature;
+        nuclear_temperature
+            * (2.0 - f64::exp(-maximum_ratio) * (maximum_ratio.powi(2) + 2.0 * maximum_ratio + 2.0))
+            / (1.0 - f64::exp(-maximum_ratio) * (maximum_ratio + 1.0))
+    };
+
+    // Far above the threshold and just above it, where the spectrum is cut off at the available energy.
+    let mut rng = SmallRng::seed_from_u64(0);
+    let sample_count = 10000;
+    for energy in [2e6, 5e4] {
+        let mean_energy = (0..sample_count)
+            .map(|_| {
+                let mut neutron = Neutron {
+                    energy,
+                    ..Default::default()
+                };
+                neutron.scatter_inelastic(atomic_mass, inelastic_threshold, &mut rng);
+                assert!(neutron.energy <= energy - inelastic_threshold && neutron.has_scattered);
+                neutron.energy
+            })
+            .sum::<f64>()
+            / sample_count as f64;
+        assert_relative_close(mean_energy, expected_mean_energy(energy), 0.03);
+    }
+
+    // Below the threshold, no energy can be lost to the excitation.
+    let mut neutron = Neutron {
+        energy: 4e4,
+        ..Default::default()
+    };
+    neutron.scatter_inelastic(atomic_mass, inelastic_threshold, &mut rng);
+    assert_eq!(neutron.energy, 4e4);
+}
+
+#[test]
+fn check_table_schemes() {
+    let energies = [1.0, 10.0, 100.0];
+    let values = [1.0, 10.0, 100.0];
+    let table_scheme = |interpolation, out_of_range| TableScheme {
+        interpolation,
+        out_of_range,
+    };
+
+    // A power law is exact with log-log interpolation, but not with lin-lin.
+    let log_log = table_scheme(InterpolationLaw::LogLog, OutOfRange::Extrapolate);
+    assert_relative_close(
+        interpolate_table(&energies, &values, &log_log, 3.0),
+        3.0,
+        1e-12,
+    );
+    assert_relative_close(
+        interpolate_table(&energies, &values, &log_log, 1e3),
+        1e3,
+        1e-12,
+    );
+    assert_relative_close(
+        interpolate_table(&energies, &values, &TableScheme::default(), 3.0),
+        3.0,
+        1e-12,
+    );
+    assert_relative_close(
+        interpolate_table(&[1.0, 10.0], &[1.0, 100.0], &TableScheme::default(), 3.0),
+        23.0,
+        1e-12,
+    );
+
+    // Histogram keeps the value of the left point.
+    let histogram = table_scheme(InterpolationLaw::Histogram, OutOfRange::Clamp);
+    assert_eq!(
+        interpolate_table(&energies, &values, &histogram, 50.0),
+        10.0
+    );
+
+    // Out-of-range behaviour, with clamping as the default.
+    assert_eq!(
+        interpolate_table(&energies, &values, &TableScheme::default(), 0.1),
+        1.0
+    );
+    assert_eq!(
+        interpolate_table(&energies, &values, &TableScheme::default(), 1e4),
+        100.0
+    );
+    let zero = table_scheme(InterpolationLaw::LinLin, OutOfRange::Zero);
+    assert_eq!(interpolate_table(&energies, &values, &zero, 0.1), 0.0);
+    assert_eq!(interpolate_table(&energies, &values, &zero, 100.0), 100.0);
+    assert_eq!(
+        linear_interpolation(&energies.to_vec(), &values, 0.1).0,
+        1.0
+    );
+
+    // The scheme is read from the comment lines at the top of a CSV.
+    let header = "# Aggregated absorption\n# interpolation = log-log\n# out_of_range = \"zero\"\nenergy,cross_section";
+    assert_eq!(
+        parse_table_scheme(header.lines()),
+        table_scheme(InterpolationLaw::LogLog, OutOfRange::Zero)
+    );
+    assert_eq!(
+        parse_table_scheme("energy,cross_section".lines()),
+        TableScheme::default()
+    );
+
+    // Other comments with an equals sign, such as unit conversions or sources, are ignored.
+    let header = "# 1 b = 1e-28 m2\n# Source: ENDF/B-VIII, E = energy\n# interpolation = log-log\nenergy,cross_section";
+    assert_eq!(
+        parse_table_scheme(header.lines()),
+        table_scheme(InterpolationLaw::LogLog, OutOfRange::Clamp)
+    );
+}
+
+#[test]
+#[should_panic(expected = "outside the tabulated range")]
+fn check_table_scheme_error() {
+    let error = TableScheme {
+        interpolation: InterpolationLaw::LinLin,
+        out_of_range: OutOfRange::Error,
+    };
+    interpolate_table(&[1.0, 10.0], &[1.0, 10.0], &error, 20.0);
+}
+
+#[test]
+fn check_material_library_cache() {
+    let test_directory = std::env::temp_dir().join(format!("nuclear_cache_{}", std::process::id()));
+    let cache_directory = test_directory.join("cache");
+    fs::create_dir_all(&test_directory).unwrap();
+
+    let library_path = test_directory.join("material_library.toml");
+    let endf_path = fs::canonicalize(ENDF_EXCERPT_PATH).unwrap();
+    let library_toml = format!(
+        "[[materials]]\nname = \"Test\"\natomic_mass = 236.0\nnumber_density = 1e28\nendf_path = {:?}\n",
+        endf_path
+    );
+    fs::write(&library_path, &library_toml).unwrap();
+
+    let material_data_vector = load_cached_material_library(&library_path, &cache_directory);
+    let cache_file_path = get_cache_file_path(&library_path, &cache_directory);
+    assert!(cache_file_path.exists());
+
+    // The cached library has to be identical to the one loaded from the sources.
+    let cached_material_data_vector = load_cached_material_library(&library_path, &cache_directory);
+    assert_eq!(
+        format!("{:?}", cached_material_data_vector),
+        format!("{:?}", material_data_vector)
+    );
+
+    // A corrupt cache is rebuilt from the sources.
+    fs::write(&cache_file_path, b"corrupt").unwrap();
+    let rebuilt_material_data_vector =
+        load_cached_material_library(&library_path, &cache_directory);
+    assert_eq!(
+        format!("{:?}", rebuilt_material_data_vector),
+        format!("{:?}", material_data_vector)
+    );
+
+    // All simulations share the same library until one of its sources changes.
+    let shared_library = load_shared_material_library(&library_path, Some(&cache_directory));
+    assert!(Arc::ptr_eq(
+        &shared_library,
+        &load_shared_material_library(&library_path, Some(&cache_directory))
+    ));
+    fs::write(
+        &library_path,
+        library_toml + "thermal_conductivity = 10.0\n",
+    )
+    .unwrap();
+    let changed_library = load_shared_material_library(&library_path, Some(&cache_directory));
+    assert!(!Arc::ptr_eq(&shared_library, &changed_library));
+    assert_eq!(
+        changed_library[1].thermal_conductivity.evaluate(300.0),
+        10.0
+    );
+
+    fs::remove_dir_all(&test_directory).unwrap();
+}
+
+#[test]
+fn check_data_validation() {
+    let valid_material_data = MaterialData {
+        energy_scattering_cross_sections: vec![1e-5, 1.0, 2e7],
+        elastic_cross_sections: vec![20.0, 10.0, 2.0],
+        name: "Valid".to_string(),
+        ..MaterialData::get_void()
+    };
+    assert!(validate_material_data(&valid_material_data).is_empty());
+
+    // Decreasing energies and negative values break the interpolation, the MeV energies and limited coverage are only suspicious.
+    let invalid_material_data = MaterialData {
+        energy_scattering_cross_sections: vec![1e-5, 10.0, 1.0],
+        elastic_cross_sections: vec![20.0, 10.0, 2.0],
+        energy_absorption_cross_sections: vec![1e-5, 1.0, 20.0],
+        absorption_cross_sections: vec![1.0, -1.0, 0.5],
+        name: "Invalid".to_string(),
+        ..MaterialData::get_void()
+    };
+    let issues = validate_material_data(&invalid_material_data);
+    let has_issue = |table_name: &str, severity: IssueSeverity, message: &str| {
+        issues.iter().any(|issue| {
+            issue.table_name == table_name
+                && issue.severity == severity
+                && issue.message.contains(message)
+        })
+    };
+
+    assert!(has_issue(
+        "scattering",
+        IssueSeverity::Error,
+        "Energies decrease at index 2"
+    ));
+    assert!(has_issue(
+        "absorption",
+        IssueSeverity::Error,
+        "Negative value"
+    ));
+    assert!(has_issue("absorption", IssueSeverity::Warning, "not MeV"));
+    assert!(has_issue("absorption", IssueSeverity::Warning, "Covers"));
+    assert!(issues.iter().all(|issue| issue.material_name == "Invalid"));
+}
+
+#[test]
+fn check_data_inspection() {
+    let energies = get_log_energy_grid(1e-5, 2e7, 50);
+    assert_eq!(energies.len(), 50);
+    assert_relative_close(energies[0], 1e-5, 1e-12);
+    assert_relative_close(energies[49], 2e7, 1e-12);
+
+    let endf_evaluation = EndfEvaluation::from_file(Path::new(ENDF_EXCERPT_PATH));
+    let material_data = MaterialData {
+        number_density: 1e28,
+        ..MaterialData::from_endf(&endf_evaluation, "Test")
+    };
+    let microscopic_absorption = linear_interpolation(
+        &material_data.energy_absorption_cross_sections,
+        &material_data.absorption_cross_sections,
+        1.0,
+    )
+    .0;
+    let material_data_vector = vec![MaterialData::get_void(), material_data];
+
+    // With a fraction of 2, the number density is twice the library's.
+    let cross_section_samples = inspect_composition(
+        &material_data_vector,
+        &[PartComposition::new("Test", 2.0)],
+        &[1.0],
+    );
+    assert_eq!(cross_section_samples.len(), 2);
+
+    let material_sample = &cross_section_samples[0];
+    assert_eq!(material_sample.material_name, "Test");
+    assert_relative_close(material_sample.number_density, 2e28, 1e-12);
+    assert_relative_close(
+        material_sample.microscopic_absorption.unwrap(),
+        microscopic_absorption,
+        1e-12,
+    );
+    assert_relative_close(
+        material_sample.macroscopic_absorption,
+        microscopic_absorption * 1e-28 * 2e28,
+        1e-12,
+    );
+
+    let total_sample = &cross_section_samples[1];
+    assert_eq!(total_sample.material_name, TOTAL_ROW_NAME);
+    assert!(total_sample.microscopic_total.is_none());
+    assert_relative_close(
+        total_sample.macroscopic_total,
+        material_sample.macroscopic_total,
+        1e-12,
+    );
+
+    // Selected materials are loaded without reading the data files of the others, which may be missing.
+    let test_directory =
+        std::env::temp_dir().join(format!("nuclear_inspection_{}", std::process::id()));
+    fs::create_dir_all(&test_directory).unwrap();
+    let library_path = test_directory.join("material_library.toml");
+    fs::write(
+        &library_path,
+        format!(
+            "[[materials]]\nname = \"Test\"\natomic_mass = 236.0\nnumber_density = 1e28\nendf_path = {:?}\n\n\
+             [[materials]]\nname = \"Missing\"\natomic_mass = 1.0\nnumber_density = 1e28\nabsorption_path = \"missing.csv\"\n",
+            fs::canonicalize(ENDF_EXCERPT_PATH).unwrap()
+        ),
+    )
+    .unwrap();
+    let selected_material_data_vector = load_selected_materials(&library_path, &["Test"]);
+    fs::remove_dir_all(&test_directory).unwrap();
+
+    assert_eq!(selected_material_data_vector.len(), 2);
+    assert_eq!(selected_material_data_vector[1].name, "Test");
+    assert_eq!(
+        selected_material_data_vector[1].absorption_cross_sections,
+        material_data_vector[1].absorption_cross_sections
+    );
+}
+
+#[test]
+fn check_thermal_properties() {
+    assert_eq!(ThermalProperty::Constant(27.0).evaluate(1000.0), 27.0);
+
+    // Tables are interpolated linearly and clamped outside.
+    let table = ThermalProperty::Table {
+        temperatures: vec![300.0, 600.0],
+        values: vec![10.0, 16.0],
+    };
+    assert_relative_close(table.evaluate(400.0), 12.0, 1e-12);
+    assert_eq!(table.evaluate(100.0), 10.0);
+    assert_eq!(table.evaluate(1000.0), 16.0);
+
+    // Polynomials are clamped to their range of validity.
+    let polynomial = ThermalProperty::Polynomial {
+        coefficients: vec![1.0, 2.0, 3.0],
+        minimum_temperature: 0.0,
+        maximum_temperature: 10.0,
+    };
+    assert_eq!(polynomial.evaluate(2.0), 17.0);
+    assert_eq!(polynomial.evaluate(20.0), 321.0);
+
+    // All forms can be given in the library, including a CSV table.
+    let test_directory =
+        std::env::temp_dir().join(format!("nuclear_thermal_{}", std::process::id()));
+    fs::create_dir_all(&test_directory).unwrap();
+    let conductivity_path = test_directory.join("conductivity.csv");
+    fs::write(
+        &conductivity_path,
+        "temperature,value\n300.0,20.0\n1300.0,40.0\n",
+    )
+    .unwrap();
+
+    let library_path = test_directory.join("material_library.toml");
+    fs::write(
+        &library_path,
+        format!(
+            "[[materials]]\nname = \"Test\"\natomic_mass = 1.0\nnumber_density = 1.0\nmelting_point = 1408.0\n\
+             thermal_conductivity = {{ path = {:?} }}\n\
+             density = {{ temperatures = [300.0, 1300.0], values = [19000.0, 18000.0] }}\n\
+             heat_capacity = {{ coefficients = [100.0, 0.1], maximum_temperature = 1000.0 }}\n",
+            conductivity_path
+        ),
+    )
+    .unwrap();
+
+    let material_data = &load_material_library(&library_path)[1];
+    assert_relative_close(
+        material_data.thermal_conductivity.evaluate(800.0),
+        30.0,
+        1e-12,
+    );
+    assert_relative_close(material_data.density.evaluate(800.0), 18500.0, 1e-12);
+    assert_relative_close(material_data.heat_capacity.evaluate(500.0), 150.0, 1e-12);
+    assert_relative_close(material_data.heat_capacity.evaluate(1500.0), 200.0, 1e-12);
+    assert_eq!(material_data.melting_point, Some(1408.0));
+
+    fs::remove_dir_all(&test_directory).unwrap();
+}